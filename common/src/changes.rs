@@ -36,6 +36,25 @@ impl ChangeSource {
             .fetch::<String>(base_url.join("changes.csv")?)
             .await?;
 
+        Self::parse(&changes)
+    }
+
+    /// Retrieve a file using a [`Fetcher`], returning [`None`] if the file doesn't exist.
+    pub async fn retrieve_optional(
+        fetcher: &Fetcher,
+        base_url: &Url,
+    ) -> Result<Option<Self>, Error> {
+        let Some(changes) = fetcher
+            .fetch::<Option<String>>(base_url.join("changes.csv")?)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self::parse(&changes)?))
+    }
+
+    fn parse(changes: &str) -> Result<Self, Error> {
         log::info!("Found 'changes.csv', processing data");
 
         let reader = csv::ReaderBuilder::new()
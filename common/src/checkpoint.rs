@@ -0,0 +1,151 @@
+//! Recording completed documents, allowing to resume an interrupted run
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use url::Url;
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+struct Entry {
+    url: Url,
+    modified: SystemTime,
+}
+
+/// A file recording completed documents.
+///
+/// Each completed document is appended to the file as a line of JSON, right away. So when a run
+/// gets interrupted, the next run can skip documents already completed. Once a run was
+/// successful, the file should be removed.
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    completed: HashMap<Url, SystemTime>,
+    file: Mutex<File>,
+}
+
+impl Checkpoint {
+    /// Open a checkpoint file, loading the documents completed by a previous run.
+    pub fn open(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut completed = HashMap::new();
+        for line in content.lines() {
+            // an interrupted run might leave an incomplete last line
+            match serde_json::from_str::<Entry>(line) {
+                Ok(entry) => {
+                    completed.insert(entry.url, entry.modified);
+                }
+                Err(err) => log::warn!("Ignoring invalid checkpoint entry: {err}"),
+            }
+        }
+
+        if !completed.is_empty() {
+            log::info!(
+                "Resuming from checkpoint ({}) - {} documents completed",
+                path.display(),
+                completed.len()
+            );
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        if !content.is_empty() && !content.ends_with('\n') {
+            // terminate the incomplete line, so that it doesn't swallow the next entry
+            file.write_all(b"\n")?;
+        }
+
+        Ok(Self {
+            path,
+            completed,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// The path of the checkpoint file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Check if a document was completed by a previous run, and not modified since.
+    pub fn is_completed(&self, url: &Url, modified: SystemTime) -> bool {
+        self.completed
+            .get(url)
+            .is_some_and(|completed| *completed >= modified)
+    }
+
+    /// Record a document as completed.
+    pub fn complete(&self, url: &Url, modified: SystemTime) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(&Entry {
+            url: url.clone(),
+            modified,
+        })?;
+        line.push(b'\n');
+
+        // a single write, so that an interruption doesn't mix up entries
+        let mut file = self.file.lock().unwrap_or_else(|err| err.into_inner());
+        file.write_all(&line)?;
+
+        Ok(())
+    }
+
+    /// Remove the checkpoint file, once a run was successful.
+    pub fn remove(self) -> anyhow::Result<()> {
+        drop(self.file);
+        match std::fs::remove_file(&self.path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn resume() {
+        let dir = tempfile::tempdir().expect("must create");
+        let path = dir.path().join("checkpoint.jsonl");
+
+        let a = Url::parse("https://example.com/a.json").expect("must parse");
+        let b = Url::parse("https://example.com/b.json").expect("must parse");
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+
+        let checkpoint = Checkpoint::open(&path).expect("must open");
+        assert!(!checkpoint.is_completed(&a, modified));
+        checkpoint.complete(&a, modified).expect("must record");
+        drop(checkpoint);
+
+        // simulate an interruption while writing
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("must open");
+        file.write_all(br#"{"url":"https://exa"#)
+            .expect("must write");
+        drop(file);
+
+        let checkpoint = Checkpoint::open(&path).expect("must open");
+        assert!(checkpoint.is_completed(&a, modified));
+        // modified after it was completed
+        assert!(!checkpoint.is_completed(&a, modified + Duration::from_secs(1)));
+        assert!(!checkpoint.is_completed(&b, modified));
+        checkpoint.complete(&b, modified).expect("must record");
+        drop(checkpoint);
+
+        let checkpoint = Checkpoint::open(&path).expect("must open");
+        assert!(checkpoint.is_completed(&a, modified));
+        assert!(checkpoint.is_completed(&b, modified));
+
+        checkpoint.remove().expect("must remove");
+        assert!(!path.exists());
+    }
+}
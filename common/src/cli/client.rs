@@ -1,4 +1,9 @@
-use crate::fetcher::{Fetcher, FetcherOptions};
+use crate::{
+    client::{ClientIdentity, ClientOptions},
+    fetcher::{CacheMode, Fetcher, FetcherOptions},
+};
+use std::path::PathBuf;
+use url::Url;
 
 #[derive(Debug, clap::Parser)]
 #[command(next_help_heading = "Client")]
@@ -10,13 +15,110 @@ pub struct ClientArguments {
     /// Per-request retries count
     #[arg(short, long, default_value = "5")]
     pub retries: usize,
+
+    /// Maximum delay between retries, in humantime duration format, defaults to 60s. This also caps the delay requested
+    /// by a server using `Retry-After`.
+    #[arg(long)]
+    pub retry_max_delay: Option<humantime::Duration>,
+
+    /// Randomize the delay between retries
+    #[arg(long)]
+    pub retry_jitter: bool,
+
+    /// Maximum number of requests per second, per host
+    #[arg(long, value_parser = parse_rate_limit)]
+    pub rate_limit: Option<f64>,
+
+    /// Maximum number of concurrent requests, per host
+    #[arg(long)]
+    pub max_in_flight: Option<usize>,
+
+    /// Proxy to use for all requests, overriding the proxy environment variables
+    #[arg(long)]
+    pub proxy: Option<Url>,
+
+    /// Hosts which should not use the proxy
+    #[arg(long, value_delimiter = ',', requires = "proxy")]
+    pub no_proxy: Vec<String>,
+
+    /// Additional root certificates
+    #[arg(long = "tls-ca-certificate")]
+    pub additional_root_certificates: Vec<PathBuf>,
+
+    /// Allow using TLS in an insecure mode (DANGER!)
+    #[arg(long)]
+    pub tls_insecure: bool,
+
+    /// Client certificate (PEM) for mutual TLS
+    #[arg(long, requires = "tls_client_key")]
+    pub tls_client_certificate: Option<PathBuf>,
+
+    /// Private key (PKCS #8, PEM) of the client certificate
+    #[arg(long, requires = "tls_client_certificate")]
+    pub tls_client_key: Option<PathBuf>,
+
+    /// Override the user agent
+    #[arg(long)]
+    pub user_agent: Option<String>,
+
+    /// Additional header sent with every request, in the format of `<name>: <value>`
+    #[arg(long = "header", value_parser = parse_header)]
+    pub headers: Vec<(String, String)>,
+
+    /// Record all responses into a directory
+    #[arg(long, conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Replay responses recorded into a directory, instead of contacting the server
+    #[arg(long)]
+    pub replay: Option<PathBuf>,
+}
+
+fn parse_rate_limit(value: &str) -> Result<f64, String> {
+    let rate_limit: f64 = value.parse().map_err(|err| format!("{err}"))?;
+    match rate_limit.is_finite() && rate_limit > 0.0 {
+        true => Ok(rate_limit),
+        false => Err(format!("Must be a positive number: {value}")),
+    }
+}
+
+/// Parse a header, in the format of `<name>: <value>`.
+pub fn parse_header(value: &str) -> Result<(String, String), String> {
+    let (name, value) = value
+        .split_once(':')
+        .ok_or_else(|| format!("Header must be in the format of '<name>: <value>': {value}"))?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
 }
 
 impl From<ClientArguments> for FetcherOptions {
     fn from(value: ClientArguments) -> Self {
+        let identity = match (value.tls_client_certificate, value.tls_client_key) {
+            (Some(certificate), Some(key)) => Some(ClientIdentity { certificate, key }),
+            _ => None,
+        };
+
+        let cache = match (value.record, value.replay) {
+            (Some(record), _) => Some(CacheMode::Record(record)),
+            (None, Some(replay)) => Some(CacheMode::Replay(replay)),
+            (None, None) => None,
+        };
+
         FetcherOptions {
             timeout: value.timeout.into(),
             retries: value.retries,
+            max_delay: value.retry_max_delay.map(Into::into),
+            jitter: value.retry_jitter,
+            requests_per_second: value.rate_limit,
+            max_in_flight: value.max_in_flight,
+            client: ClientOptions::new()
+                .proxy(value.proxy)
+                .no_proxy(value.no_proxy)
+                .additional_root_certificates(value.additional_root_certificates)
+                .tls_insecure(value.tls_insecure)
+                .identity(identity)
+                .user_agent(value.user_agent)
+                .headers(value.headers),
+            cache,
         }
     }
 }
@@ -27,3 +129,27 @@ impl ClientArguments {
         Fetcher::new(self.into()).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn header() {
+        assert_eq!(
+            parse_header("Authorization: Bearer foo:bar"),
+            Ok(("Authorization".to_string(), "Bearer foo:bar".to_string()))
+        );
+        assert!(parse_header("Authorization").is_err());
+    }
+
+    #[test]
+    fn rate_limit() {
+        assert_eq!(parse_rate_limit("0.5"), Ok(0.5));
+        assert!(parse_rate_limit("0").is_err());
+        assert!(parse_rate_limit("-1").is_err());
+        assert!(parse_rate_limit("inf").is_err());
+        assert!(parse_rate_limit("NaN").is_err());
+        assert!(parse_rate_limit("fast").is_err());
+    }
+}
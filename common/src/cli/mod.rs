@@ -1,5 +1,6 @@
 //! Command line helpers
 pub mod client;
+pub mod retrieval;
 pub mod runner;
 
 #[cfg(feature = "openpgp")]
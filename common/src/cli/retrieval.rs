@@ -0,0 +1,22 @@
+use crate::retrieve::RetrievalOptions;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, clap::Parser)]
+#[command(next_help_heading = "Retrieval")]
+pub struct RetrievalArguments {
+    /// Maximum size of a document (and its decompressed content) in bytes, zero means unlimited
+    #[arg(long, default_value = "0")]
+    pub max_document_size: usize,
+
+    /// Write retrieved documents to temporary files in this directory, instead of keeping them in memory
+    #[arg(long)]
+    pub spool_dir: Option<PathBuf>,
+}
+
+impl From<RetrievalArguments> for RetrievalOptions {
+    fn from(value: RetrievalArguments) -> Self {
+        RetrievalOptions::new()
+            .max_size(value.max_document_size)
+            .spool_dir(value.spool_dir)
+    }
+}
@@ -1,7 +1,80 @@
+use futures_util::future::{BoxFuture, FutureExt, select_all};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
 #[derive(Debug, clap::Parser)]
 #[command(next_help_heading = "Runner")]
 pub struct RunnerArguments {
     /// Number of workers, too many parallel requests might make you violate request rates. NOTE: A number of zero will spawn an unlimited amount of workers.
     #[arg(short, long, default_value = "1")]
     pub workers: usize,
+
+    /// Stop processing after this duration, completing the documents currently being processed.
+    #[arg(long)]
+    pub max_duration: Option<humantime::Duration>,
+}
+
+impl RunnerArguments {
+    /// Create a token, which gets cancelled on SIGINT or SIGTERM, or when the maximum duration
+    /// has passed.
+    ///
+    /// Interrupting a second time aborts the process right away. This must be called from within
+    /// a Tokio runtime.
+    pub fn cancellation(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+
+        let mut reasons: Vec<BoxFuture<'static, &'static str>> = vec![
+            async {
+                match tokio::signal::ctrl_c().await {
+                    Ok(()) => "Interrupted",
+                    Err(_) => std::future::pending().await,
+                }
+            }
+            .boxed(),
+        ];
+
+        #[cfg(unix)]
+        reasons.push(
+            async {
+                use tokio::signal::unix::{SignalKind, signal};
+                match signal(SignalKind::terminate()) {
+                    Ok(mut signal) => {
+                        signal.recv().await;
+                        "Terminated"
+                    }
+                    Err(_) => std::future::pending().await,
+                }
+            }
+            .boxed(),
+        );
+
+        if let Some(max_duration) = self.max_duration {
+            let max_duration: Duration = max_duration.into();
+            reasons.push(
+                async move {
+                    tokio::time::sleep(max_duration).await;
+                    "Maximum duration reached"
+                }
+                .boxed(),
+            );
+        }
+
+        tokio::spawn({
+            let token = token.clone();
+            async move {
+                let (reason, _, _) = select_all(reasons).await;
+                log::warn!(
+                    "{reason}, completing the documents currently being processed (interrupt again to abort)"
+                );
+                token.cancel();
+
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    log::error!("Aborted");
+                    std::process::exit(130);
+                }
+            }
+        });
+
+        token
+    }
 }
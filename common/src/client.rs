@@ -0,0 +1,170 @@
+//! Configuration of HTTP clients
+
+use crate::USER_AGENT;
+use anyhow::Context;
+use reqwest::{
+    ClientBuilder, NoProxy, Proxy,
+    header::{HeaderMap, HeaderName, HeaderValue},
+    tls::{Certificate, Identity},
+};
+use std::path::PathBuf;
+use url::Url;
+
+/// Client identity for mutual TLS, using PEM encoded files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientIdentity {
+    /// The client certificate (chain)
+    pub certificate: PathBuf,
+    /// The PKCS #8 private key of the certificate
+    pub key: PathBuf,
+}
+
+/// Options for configuring an HTTP client, shared between the
+/// [`crate::fetcher::Fetcher`] and the [`crate::sender::HttpSender`].
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientOptions {
+    /// Proxy to use for all requests
+    pub proxy: Option<Url>,
+    /// Hosts which should not use the proxy
+    pub no_proxy: Vec<String>,
+    pub additional_root_certificates: Vec<PathBuf>,
+    pub tls_insecure: bool,
+    pub identity: Option<ClientIdentity>,
+    /// User agent, defaults to [`USER_AGENT`]
+    pub user_agent: Option<String>,
+    /// Headers sent with every request
+    pub headers: Vec<(String, String)>,
+}
+
+impl ClientOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn proxy(mut self, proxy: impl Into<Option<Url>>) -> Self {
+        self.proxy = proxy.into();
+        self
+    }
+
+    pub fn no_proxy<I>(mut self, no_proxy: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.no_proxy = Vec::from_iter(no_proxy);
+        self
+    }
+
+    pub fn additional_root_certificates<I>(mut self, additional_root_certificates: I) -> Self
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        self.additional_root_certificates = Vec::from_iter(additional_root_certificates);
+        self
+    }
+
+    pub fn add_additional_root_certificate(
+        mut self,
+        additional_root_certificate: impl Into<PathBuf>,
+    ) -> Self {
+        self.additional_root_certificates
+            .push(additional_root_certificate.into());
+        self
+    }
+
+    pub fn tls_insecure(mut self, tls_insecure: bool) -> Self {
+        self.tls_insecure = tls_insecure;
+        self
+    }
+
+    pub fn identity(mut self, identity: impl Into<Option<ClientIdentity>>) -> Self {
+        self.identity = identity.into();
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<Option<String>>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        self.headers = Vec::from_iter(headers);
+        self
+    }
+
+    pub fn add_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Create a new client builder, applying the options.
+    pub fn builder(&self) -> anyhow::Result<ClientBuilder> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            headers.append(
+                HeaderName::try_from(name)
+                    .with_context(|| format!("Invalid header name: {name}"))?,
+                HeaderValue::try_from(value)
+                    .with_context(|| format!("Invalid value for header: {name}"))?,
+            );
+        }
+
+        let mut client = ClientBuilder::new()
+            .user_agent(self.user_agent.as_deref().unwrap_or(USER_AGENT))
+            .default_headers(headers);
+
+        if let Some(proxy) = &self.proxy {
+            let no_proxy = NoProxy::from_string(&self.no_proxy.join(","));
+            client = client.proxy(Proxy::all(proxy.clone())?.no_proxy(no_proxy));
+        }
+
+        for cert in &self.additional_root_certificates {
+            let cert = std::fs::read(cert)
+                .with_context(|| format!("Reading certificate: {}", cert.display()))?;
+            client = client.add_root_certificate(Certificate::from_pem(&cert)?);
+        }
+
+        if let Some(identity) = &self.identity {
+            let certificate = std::fs::read(&identity.certificate).with_context(|| {
+                format!(
+                    "Reading client certificate: {}",
+                    identity.certificate.display()
+                )
+            })?;
+            let key = std::fs::read(&identity.key)
+                .with_context(|| format!("Reading client key: {}", identity.key.display()))?;
+            client = client.identity(Identity::from_pkcs8_pem(&certificate, &key)?);
+        }
+
+        if self.tls_insecure {
+            log::warn!("Disabling TLS validation");
+            client = client
+                .danger_accept_invalid_hostnames(true)
+                .danger_accept_invalid_certs(true);
+        }
+
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn invalid_header() {
+        let result = ClientOptions::new().add_header("Foo Bar", "baz").builder();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn missing_certificate() {
+        let result = ClientOptions::new()
+            .add_additional_root_certificate("this-should-not-exist.pem")
+            .builder();
+        assert!(result.is_err());
+    }
+}
@@ -6,9 +6,41 @@ mod limit;
 pub use detecting::*;
 pub use limit::*;
 
+use crate::retrieve::RetrievedData;
 use anyhow::anyhow;
 use bytes::Bytes;
-use std::io::Write;
+use std::io::{Cursor, Read, Write};
+
+/// The number of bytes required to detect a compression by its magic bytes.
+const MAGIC_LEN: u64 = 6;
+
+/// Get a reader for retrieved data, decompressing it if required, blocking the thread.
+///
+/// Uncompressed data will be read directly from its source, like a spooled file. Compressed data
+/// will be decompressed into memory.
+pub fn decompress_reader(
+    data: &RetrievedData,
+    name: &str,
+    opts: &DecompressionOptions,
+) -> Result<Box<dyn Read + Send>, anyhow::Error> {
+    let mut reader = data.reader()?;
+    let mut magic = Vec::with_capacity(MAGIC_LEN as usize);
+    reader.by_ref().take(MAGIC_LEN).read_to_end(&mut magic)?;
+
+    let detector = Detector {
+        file_name: Some(name),
+        ..Default::default()
+    };
+    let compression = detector.detect(&magic).map_err(|err| anyhow!("{err}"))?;
+    if matches!(compression, Compression::None) {
+        return Ok(Box::new(Cursor::new(magic).chain(reader)));
+    }
+
+    let mut data = magic;
+    reader.read_to_end(&mut data)?;
+    let data = compression.decompress_with(data.into(), opts)?;
+    Ok(Box::new(Cursor::new(data)))
+}
 
 /// Decompress a stream, or fail if no encoder was configured.
 ///
@@ -31,6 +63,20 @@ pub fn decompress(data: Bytes, name: &str) -> Result<Bytes, anyhow::Error> {
     decompress_opt(&data, name).unwrap_or_else(|| Ok(data))
 }
 
+/// Decompress a stream, using the provided options, or fail if no encoder was configured.
+pub fn decompress_with(
+    data: Bytes,
+    name: &str,
+    opts: &DecompressionOptions,
+) -> Result<Bytes, anyhow::Error> {
+    let detector = Detector {
+        file_name: Some(name),
+        ..Default::default()
+    };
+    let compression = detector.detect(&data).map_err(|err| anyhow!("{err}"))?;
+    Ok(compression.decompress_with(data, opts)?)
+}
+
 /// Decompress bz2 using `bzip2-rs` (pure Rust version)
 #[cfg(all(feature = "bzip2-rs", not(feature = "bzip2")))]
 #[deprecated(since = "0.9.3", note = "Use Compression::decompress instead")]
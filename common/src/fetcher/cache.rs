@@ -0,0 +1,172 @@
+use crate::utils::hex::Hex;
+use reqwest::{Response, ResponseBuilderExt, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Recording or replaying HTTP responses.
+///
+/// Each response is stored in the directory, as a JSON file containing the status and headers,
+/// and a file containing the body. Both files are named after the SHA-256 digest of the URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Send requests, and record all responses into the directory.
+    Record(PathBuf),
+    /// Don't send any requests, but serve the responses recorded in the directory.
+    Replay(PathBuf),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CacheError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid recorded response: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid recorded response: {0}")]
+    Http(#[from] http::Error),
+    #[error("No recorded response for: {0}")]
+    Missing(Url),
+}
+
+/// The recorded information of a response, besides the body.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    /// The requested URL
+    url: Url,
+    /// The final URL, after following redirects
+    response_url: Url,
+    status: u16,
+    headers: Vec<(String, String)>,
+}
+
+impl CacheMode {
+    fn paths(dir: &Path, url: &Url) -> (PathBuf, PathBuf) {
+        let name = Hex(&Sha256::digest(url.as_str())).to_lower();
+        (
+            dir.join(format!("{name}.json")),
+            dir.join(format!("{name}.body")),
+        )
+    }
+
+    /// Record a response, returning a response carrying the same content.
+    pub(crate) async fn record(
+        dir: &Path,
+        url: &Url,
+        response: Response,
+    ) -> Result<Response, RecordError> {
+        let entry = Entry {
+            url: url.clone(),
+            response_url: response.url().clone(),
+            status: response.status().as_u16(),
+            headers: response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect(),
+        };
+        let body = response.bytes().await?;
+
+        let (meta, data) = Self::paths(dir, url);
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(CacheError::from)?;
+        tokio::fs::write(&data, &body)
+            .await
+            .map_err(CacheError::from)?;
+        tokio::fs::write(
+            &meta,
+            serde_json::to_vec_pretty(&entry).map_err(CacheError::from)?,
+        )
+        .await
+        .map_err(CacheError::from)?;
+
+        log::debug!("Recorded {url}: {}", meta.display());
+
+        Ok(entry.into_response(body.to_vec())?)
+    }
+
+    /// Replay a recorded response.
+    pub(crate) async fn replay(dir: &Path, url: &Url) -> Result<Response, CacheError> {
+        let (meta, data) = Self::paths(dir, url);
+
+        let entry = match tokio::fs::read(&meta).await {
+            Ok(entry) => entry,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Err(CacheError::Missing(url.clone()));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let entry: Entry = serde_json::from_slice(&entry)?;
+        let body = tokio::fs::read(&data).await?;
+
+        log::debug!("Replaying {url}: {}", meta.display());
+
+        entry.into_response(body)
+    }
+}
+
+impl Entry {
+    fn into_response(self, body: Vec<u8>) -> Result<Response, CacheError> {
+        let mut response = http::Response::builder()
+            .status(StatusCode::from_u16(self.status).map_err(http::Error::from)?)
+            .url(self.response_url);
+
+        for (name, value) in self.headers {
+            response = response.header(name, value);
+        }
+
+        Ok(response.body(body)?.into())
+    }
+}
+
+/// Error recording a response
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum RecordError {
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn replay() {
+        let dir = tempfile::tempdir().expect("must create");
+        let dir = dir.path();
+        let url = Url::parse("https://example.com/foo.json").expect("must parse");
+
+        let recorded = http::Response::builder()
+            .status(404)
+            .header("ETag", "\"foo\"")
+            .url(url.clone())
+            .body("not found")
+            .expect("must build");
+        CacheMode::record(dir, &url, recorded.into())
+            .await
+            .expect("must record");
+
+        let response = CacheMode::replay(dir, &url).await.expect("must replay");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.url(), &url);
+        assert_eq!(
+            response.headers().get("etag").and_then(|v| v.to_str().ok()),
+            Some("\"foo\"")
+        );
+        assert_eq!(response.text().await.expect("must read"), "not found");
+
+        let other = Url::parse("https://example.com/bar.json").expect("must parse");
+        assert!(matches!(
+            CacheMode::replay(dir, &other).await,
+            Err(CacheError::Missing(_))
+        ));
+    }
+}
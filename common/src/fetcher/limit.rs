@@ -0,0 +1,185 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use time::{OffsetDateTime, format_description::well_known::Rfc2822};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+use url::Url;
+
+/// Limits requests, per host.
+///
+/// The state is shared by all clones of a [`super::Fetcher`].
+#[derive(Debug, Default)]
+pub(crate) struct Limiter {
+    /// The minimum time between two requests to the same host
+    interval: Option<Duration>,
+    /// The maximum number of requests in flight to the same host
+    max_in_flight: Option<usize>,
+
+    hosts: Mutex<HashMap<String, Arc<HostState>>>,
+}
+
+#[derive(Debug)]
+struct HostState {
+    in_flight: Option<Arc<Semaphore>>,
+    /// The earliest time the next request may be sent
+    next: tokio::sync::Mutex<Instant>,
+}
+
+/// The requested rate limit can't be used.
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid rate limit, must be a positive number of requests per second: {0}")]
+pub(crate) struct InvalidRateLimit(f64);
+
+/// A permit to send a request, must be held until the response is processed.
+#[derive(Debug)]
+pub(crate) struct Permit {
+    _in_flight: Option<OwnedSemaphorePermit>,
+}
+
+impl Limiter {
+    pub fn new(
+        requests_per_second: Option<f64>,
+        max_in_flight: Option<usize>,
+    ) -> Result<Self, InvalidRateLimit> {
+        let interval = requests_per_second
+            .map(|rps| match rps > 0.0 {
+                true => Duration::try_from_secs_f64(1.0 / rps).map_err(|_| InvalidRateLimit(rps)),
+                false => Err(InvalidRateLimit(rps)),
+            })
+            .transpose()?;
+
+        Ok(Self {
+            interval,
+            max_in_flight: max_in_flight.filter(|max| *max > 0),
+            hosts: Default::default(),
+        })
+    }
+
+    fn host(&self, url: &Url) -> Arc<HostState> {
+        let key = format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        );
+
+        let mut hosts = self.hosts.lock().unwrap_or_else(|err| err.into_inner());
+        hosts
+            .entry(key)
+            .or_insert_with(|| {
+                Arc::new(HostState {
+                    in_flight: self.max_in_flight.map(|max| Arc::new(Semaphore::new(max))),
+                    next: tokio::sync::Mutex::new(Instant::now()),
+                })
+            })
+            .clone()
+    }
+
+    /// Wait until a request to the host of the URL may be sent.
+    pub async fn acquire(&self, url: &Url) -> Permit {
+        let host = self.host(url);
+
+        let in_flight = match &host.in_flight {
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+
+        // holding the lock while sleeping queues up all other requests to this host
+        let mut next = host.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            log::debug!("Delaying request to {}: {:?}", url, *next - now);
+            tokio::time::sleep_until(*next).await;
+        }
+        if let Some(interval) = self.interval {
+            *next = Instant::now() + interval;
+        }
+
+        Permit {
+            _in_flight: in_flight,
+        }
+    }
+
+    /// Don't send any requests to the host of the URL for the provided duration.
+    pub async fn back_off(&self, url: &Url, duration: Duration) {
+        log::info!("Backing off from {}: {duration:?}", url);
+
+        let host = self.host(url);
+        let mut next = host.next.lock().await;
+        *next = (*next).max(Instant::now() + duration);
+    }
+}
+
+/// Parse the value of a `Retry-After` header, which is either a number of seconds, or an HTTP
+/// date.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = OffsetDateTime::parse(value, &Rfc2822).ok()?;
+    let delay = date - OffsetDateTime::now_utc();
+    Some(delay.try_into().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn invalid_rate_limit() {
+        for rps in [0.0, -1.0, f64::NAN, f64::MIN_POSITIVE / 4.0] {
+            assert!(Limiter::new(Some(rps), None).is_err(), "{rps}");
+        }
+        assert!(Limiter::new(Some(f64::MAX), None).is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn interval() {
+        let limiter = Limiter::new(Some(2.0), None).expect("must be valid");
+        let url = Url::parse("https://example.com/foo").expect("must parse");
+
+        let start = Instant::now();
+        for _ in 0..5 {
+            limiter.acquire(&url).await;
+        }
+
+        // the first request is sent right away
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+
+        // other hosts are not affected
+        let start = Instant::now();
+        limiter
+            .acquire(&Url::parse("https://example.org/foo").expect("must parse"))
+            .await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn back_off() {
+        let limiter = Limiter::new(None, Some(1)).expect("must be valid");
+        let url = Url::parse("https://example.com/foo").expect("must parse");
+
+        limiter.back_off(&url, Duration::from_secs(10)).await;
+
+        let start = Instant::now();
+        limiter.acquire(&url).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(10));
+    }
+}
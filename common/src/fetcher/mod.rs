@@ -1,16 +1,32 @@
 //! Fetching remote resources
 
+mod cache;
 mod data;
+mod limit;
+mod stats;
 use backon::{ExponentialBuilder, Retryable};
+pub use cache::{CacheError, CacheMode};
 pub use data::*;
+pub use stats::*;
 
-use reqwest::{Client, ClientBuilder, IntoUrl, Method, Response};
+use crate::{client::ClientOptions, retrieve::RetrievalMetadata};
+use cache::RecordError;
+use limit::{Limiter, Permit, parse_retry_after};
+use reqwest::{Client, IntoUrl, Method, RequestBuilder, Response, StatusCode, header};
+use std::error::Error as _;
 use std::fmt::Debug;
 use std::future::Future;
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use time::{OffsetDateTime, UtcOffset, format_description::well_known::Rfc2822};
 use url::Url;
 
+/// The maximum delay between retries, if none was configured.
+///
+/// This matches the default of the exponential backoff.
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(60);
+
 /// Fetch data using HTTP.
 ///
 /// This is some functionality sitting on top an HTTP client, allowing for additional options like
@@ -19,6 +35,11 @@ use url::Url;
 pub struct Fetcher {
     client: Client,
     retries: usize,
+    max_delay: Option<Duration>,
+    jitter: bool,
+    limiter: Arc<Limiter>,
+    stats: Arc<Mutex<FetcherStats>>,
+    cache: Option<CacheMode>,
 }
 
 /// Error when retrieving
@@ -26,6 +47,96 @@ pub struct Fetcher {
 pub enum Error {
     #[error("Request error: {0}")]
     Request(#[from] reqwest::Error),
+    #[error("Cache error: {0}")]
+    Cache(#[from] CacheError),
+}
+
+impl Error {
+    /// Check if the error is transient, and the request might succeed when being re-tried.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Request(err) => is_transient(err),
+            Self::Cache(_) => false,
+        }
+    }
+}
+
+fn is_transient(err: &reqwest::Error) -> bool {
+    if let Some(status) = err.status() {
+        return status.is_server_error()
+            || matches!(
+                status,
+                StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS
+            );
+    }
+
+    if err.is_builder() || err.is_redirect() || err.is_decode() {
+        return false;
+    }
+
+    if err.is_connect() {
+        // connection issues are caused by I/O errors, TLS issues (like an untrusted certificate)
+        // are not
+        let mut source = err.source();
+        while let Some(err) = source {
+            if err.is::<std::io::Error>() {
+                return true;
+            }
+            source = err.source();
+        }
+        return false;
+    }
+
+    // timeouts, or the connection broke while transferring the body
+    true
+}
+
+/// Error of a single attempt of fetching
+#[derive(Debug, thiserror::Error)]
+enum FetchOnceError {
+    #[error("{error}")]
+    Transient {
+        error: Error,
+        retry_after: Option<Duration>,
+    },
+    #[error(transparent)]
+    Permanent(Error),
+}
+
+impl FetchOnceError {
+    fn new(error: reqwest::Error, retry_after: Option<Duration>) -> Self {
+        match is_transient(&error) {
+            true => Self::Transient {
+                error: error.into(),
+                retry_after,
+            },
+            false => Self::Permanent(error.into()),
+        }
+    }
+}
+
+impl From<CacheError> for FetchOnceError {
+    fn from(value: CacheError) -> Self {
+        Self::Permanent(value.into())
+    }
+}
+
+impl From<RecordError> for FetchOnceError {
+    fn from(value: RecordError) -> Self {
+        match value {
+            RecordError::Request(err) => Self::new(err, None),
+            RecordError::Cache(err) => err.into(),
+        }
+    }
+}
+
+impl From<FetchOnceError> for Error {
+    fn from(value: FetchOnceError) -> Self {
+        match value {
+            FetchOnceError::Transient { error, .. } => error,
+            FetchOnceError::Permanent(error) => error,
+        }
+    }
 }
 
 /// Options for the [`Fetcher`]
@@ -34,6 +145,17 @@ pub enum Error {
 pub struct FetcherOptions {
     pub timeout: Duration,
     pub retries: usize,
+    /// Maximum delay between retries
+    pub max_delay: Option<Duration>,
+    /// Randomize the delay between retries
+    pub jitter: bool,
+    /// Maximum number of requests per second, per host
+    pub requests_per_second: Option<f64>,
+    /// Maximum number of requests in flight, per host
+    pub max_in_flight: Option<usize>,
+    pub client: ClientOptions,
+    /// Record or replay responses
+    pub cache: Option<CacheMode>,
 }
 
 impl FetcherOptions {
@@ -53,6 +175,42 @@ impl FetcherOptions {
         self.retries = retries;
         self
     }
+
+    /// Set the maximum delay between retries.
+    pub fn max_delay(mut self, max_delay: impl Into<Option<Duration>>) -> Self {
+        self.max_delay = max_delay.into();
+        self
+    }
+
+    /// Enable or disable randomizing the delay between retries.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the maximum number of requests per second, per host.
+    pub fn requests_per_second(mut self, requests_per_second: impl Into<Option<f64>>) -> Self {
+        self.requests_per_second = requests_per_second.into();
+        self
+    }
+
+    /// Set the maximum number of requests in flight, per host.
+    pub fn max_in_flight(mut self, max_in_flight: impl Into<Option<usize>>) -> Self {
+        self.max_in_flight = max_in_flight.into();
+        self
+    }
+
+    /// Set the options of the HTTP client.
+    pub fn client(mut self, client: ClientOptions) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set the record or replay mode.
+    pub fn cache(mut self, cache: impl Into<Option<CacheMode>>) -> Self {
+        self.cache = cache.into();
+        self
+    }
 }
 
 impl Default for FetcherOptions {
@@ -60,32 +218,56 @@ impl Default for FetcherOptions {
         Self {
             timeout: Duration::from_secs(30),
             retries: 5,
+            max_delay: None,
+            jitter: false,
+            requests_per_second: None,
+            max_in_flight: None,
+            client: Default::default(),
+            cache: None,
         }
     }
 }
 
 impl From<Client> for Fetcher {
     fn from(client: Client) -> Self {
-        Self::with_client(client, FetcherOptions::default())
+        Self::with_client(client, FetcherOptions::default(), Limiter::default())
     }
 }
 
 impl Fetcher {
     /// Create a new downloader from options
     pub async fn new(options: FetcherOptions) -> anyhow::Result<Self> {
-        let client = ClientBuilder::new().timeout(options.timeout);
+        let client = options.client.builder()?.timeout(options.timeout);
+        let limiter = Limiter::new(options.requests_per_second, options.max_in_flight)?;
 
-        Ok(Self::with_client(client.build()?, options))
+        Ok(Self::with_client(client.build()?, options, limiter))
     }
 
     /// Create a fetcher providing an existing client.
-    fn with_client(client: Client, options: FetcherOptions) -> Self {
+    fn with_client(client: Client, options: FetcherOptions, limiter: Limiter) -> Self {
         Self {
             client,
             retries: options.retries,
+            max_delay: options.max_delay,
+            jitter: options.jitter,
+            limiter: Arc::new(limiter),
+            stats: Default::default(),
+            cache: options.cache,
         }
     }
 
+    /// Get the statistics of all requests made so far, by this fetcher and all of its clones.
+    pub fn stats(&self) -> FetcherStats {
+        self.stats
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    fn update_stats(&self, f: impl FnOnce(&mut FetcherStats)) {
+        f(&mut self.stats.lock().unwrap_or_else(|err| err.into_inner()));
+    }
+
     async fn new_request(
         &self,
         method: Method,
@@ -109,33 +291,233 @@ impl Fetcher {
         // if the URL building fails, there is no need to re-try, abort now.
         let url = url.into_url()?;
 
-        let retries = self.retries;
-        let backoff = ExponentialBuilder::default();
+        self.retry(&url, || self.fetch_once(url.clone(), &processor))
+            .await
+    }
 
-        (|| async {
-            match self.fetch_once(url.clone(), &processor).await {
-                Ok(result) => Ok(result),
-                Err(err) => {
-                    log::info!("Failed to retrieve: {err}");
-                    Err(err)
-                }
-            }
+    /// Run a request, re-trying it in case of transient errors.
+    async fn retry<T, F, Fut>(&self, url: &Url, f: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, FetchOnceError>>,
+    {
+        let mut backoff = ExponentialBuilder::default().with_max_times(self.retries);
+        if let Some(max_delay) = self.max_delay {
+            backoff = backoff.with_max_delay(max_delay);
+        }
+        if self.jitter {
+            backoff = backoff.with_jitter();
+        }
+
+        let mut retries = 0;
+        let result = f
+            .retry(backoff)
+            .when(|err| matches!(err, FetchOnceError::Transient { .. }))
+            .adjust(|err, delay| match err {
+                // honor the server's request to back off
+                FetchOnceError::Transient {
+                    retry_after: Some(retry_after),
+                    ..
+                } => delay.map(|delay| delay.max(*retry_after)),
+                _ => delay,
+            })
+            .notify(|err, delay| {
+                retries += 1;
+                log::info!(
+                    "Failed to retrieve {url}, retrying ({retries}/{max}) after {delay:?}: {err}",
+                    max = self.retries
+                );
+                self.update_stats(|stats| stats.retry(url));
+            })
+            .await;
+
+        if let Err(err) = &result {
+            log::info!("Failed to retrieve {url} (retries: {retries}): {err}");
+            self.update_stats(|stats| stats.failures += 1);
+        }
+
+        Ok(result?)
+    }
+
+    async fn fetch_once<D: DataProcessor>(
+        &self,
+        url: Url,
+        processor: &D,
+    ) -> Result<D::Type, FetchOnceError> {
+        let _permit = self.acquire(&url).await;
+        self.update_stats(|stats| stats.requests += 1);
+
+        let request = self
+            .new_request(Method::GET, url.clone())
+            .await
+            .map_err(|err| FetchOnceError::new(err, None))?;
+        let response = self.send(&url, request).await?;
+        let retry_after = self.check_retry_after(&url, &response).await;
+
+        processor
+            .process(response)
+            .await
+            .map_err(|err| FetchOnceError::new(err, retry_after))
+    }
+
+    /// Get the size of a resource, using a HEAD request.
+    ///
+    /// Returns [`None`] if the server doesn't report the size. As recorded responses don't cover
+    /// HEAD requests, this is also the case when replaying responses.
+    pub async fn content_length(&self, url: impl IntoUrl) -> Result<Option<u64>, Error> {
+        let url = url.into_url()?;
+
+        if let Some(CacheMode::Replay(_)) = &self.cache {
+            return Ok(None);
+        }
+
+        self.retry(&url, || self.content_length_once(url.clone()))
+            .await
+    }
+
+    async fn content_length_once(&self, url: Url) -> Result<Option<u64>, FetchOnceError> {
+        let _permit = self.acquire(&url).await;
+        self.update_stats(|stats| stats.requests += 1);
+
+        let response = self
+            .new_request(Method::HEAD, url.clone())
+            .await
+            .map_err(|err| FetchOnceError::new(err, None))?
+            .send()
+            .await
+            .map_err(|err| FetchOnceError::new(err, None))?;
+        let retry_after = self.check_retry_after(&url, &response).await;
+
+        let response = response
+            .error_for_status()
+            .map_err(|err| FetchOnceError::new(err, retry_after))?;
+
+        // the body of a HEAD response is empty, so the header must be used
+        Ok(response
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok()))
+    }
+
+    /// fetch data, using a conditional GET request, processing the response data.
+    ///
+    /// The request will carry the `If-None-Match` and `If-Modified-Since` headers, based on
+    /// the metadata of a previous retrieval. If the server reports the resource as not modified,
+    /// [`None`] is returned.
+    pub async fn fetch_processed_conditional<D: DataProcessor>(
+        &self,
+        url: impl IntoUrl,
+        processor: D,
+        previous: &RetrievalMetadata,
+    ) -> Result<Option<D::Type>, Error> {
+        let url = url.into_url()?;
+
+        self.retry(&url, || {
+            self.fetch_once_conditional(url.clone(), &processor, previous)
         })
-        .retry(&backoff.with_max_times(retries))
         .await
     }
 
-    async fn fetch_once<D: DataProcessor>(
+    async fn fetch_once_conditional<D: DataProcessor>(
         &self,
         url: Url,
         processor: &D,
-    ) -> Result<D::Type, Error> {
-        let response = self.new_request(Method::GET, url).await?.send().await?;
+        previous: &RetrievalMetadata,
+    ) -> Result<Option<D::Type>, FetchOnceError> {
+        if self.cache.is_some() {
+            // recorded responses must carry the full content, and be independent of local state
+            return self.fetch_once(url, processor).await.map(Some);
+        }
+
+        let _permit = self.acquire(&url).await;
+        self.update_stats(|stats| stats.requests += 1);
+
+        let mut request = self
+            .new_request(Method::GET, url.clone())
+            .await
+            .map_err(|err| FetchOnceError::new(err, None))?;
+
+        if let Some(etag) = &previous.etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(date) = previous.last_modification.and_then(http_date) {
+            request = request.header(header::IF_MODIFIED_SINCE, date);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|err| FetchOnceError::new(err, None))?;
+        let retry_after = self.check_retry_after(&url, &response).await;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            log::debug!("Not modified: {}", response.url());
+            return Ok(None);
+        }
+
+        processor
+            .process(response)
+            .await
+            .map(Some)
+            .map_err(|err| FetchOnceError::new(err, retry_after))
+    }
+
+    /// Acquire a permit for sending a request, unless responses are being replayed.
+    async fn acquire(&self, url: &Url) -> Option<Permit> {
+        match &self.cache {
+            Some(CacheMode::Replay(_)) => None,
+            _ => Some(self.limiter.acquire(url).await),
+        }
+    }
+
+    /// Send a request, recording or replaying its response.
+    async fn send(&self, url: &Url, request: RequestBuilder) -> Result<Response, FetchOnceError> {
+        match &self.cache {
+            None => request
+                .send()
+                .await
+                .map_err(|err| FetchOnceError::new(err, None)),
+            Some(CacheMode::Record(dir)) => {
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|err| FetchOnceError::new(err, None))?;
+                Ok(CacheMode::record(dir, url, response).await?)
+            }
+            Some(CacheMode::Replay(dir)) => Ok(CacheMode::replay(dir, url).await?),
+        }
+    }
+
+    /// Back off from the host, if the server asks for it.
+    ///
+    /// The delay is capped at the maximum delay between retries.
+    async fn check_retry_after(&self, url: &Url, response: &Response) -> Option<Duration> {
+        if !matches!(
+            response.status(),
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        ) {
+            return None;
+        }
 
-        Ok(processor.process(response).await?)
+        let delay = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after)?
+            .min(self.max_delay.unwrap_or(DEFAULT_MAX_DELAY));
+
+        self.limiter.back_off(url, delay).await;
+        Some(delay)
     }
 }
 
+/// Format a timestamp as HTTP date, which is an RFC 2822 date in GMT.
+fn http_date(date: OffsetDateTime) -> Option<String> {
+    let date = date.to_offset(UtcOffset::UTC).format(&Rfc2822).ok()?;
+    Some(date.replace("+0000", "GMT"))
+}
+
 /// Processing data returned by a request.
 pub trait DataProcessor {
     type Type: Sized;
@@ -165,3 +547,61 @@ impl<D: Data> DataProcessor for TypedProcessor<D> {
         D::from_response(response).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format_http_date() {
+        let date = OffsetDateTime::from_unix_timestamp(784111777).expect("must be valid");
+        assert_eq!(
+            http_date(date).as_deref(),
+            Some("Sun, 06 Nov 1994 08:49:37 GMT")
+        );
+    }
+
+    #[tokio::test]
+    async fn permanent_error() {
+        let fetcher = Fetcher::new(FetcherOptions::new().retries(3))
+            .await
+            .expect("must create fetcher");
+
+        // an unsupported scheme fails when sending the request, which must not be re-tried
+        let result = fetcher.fetch::<String>("ftp://localhost/foo").await;
+        assert!(matches!(&result, Err(err) if !err.is_transient()));
+
+        let stats = fetcher.stats();
+        assert_eq!(stats.requests, 1);
+        assert_eq!(stats.retries, 0);
+        assert_eq!(stats.failures, 1);
+    }
+
+    #[tokio::test]
+    async fn content_length() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let addr = listener.local_addr().expect("must have address");
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("must accept");
+            let mut request = [0u8; 1024];
+            let len = stream.read(&mut request).expect("must read");
+            assert!(request[..len].starts_with(b"HEAD /doc.json "));
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1234\r\nConnection: close\r\n\r\n")
+                .expect("must write");
+        });
+
+        let fetcher = Fetcher::new(Default::default())
+            .await
+            .expect("must create fetcher");
+        let size = fetcher
+            .content_length(format!("http://{addr}/doc.json"))
+            .await
+            .expect("must request");
+
+        assert_eq!(size, Some(1234));
+        assert_eq!(fetcher.stats().requests, 1);
+    }
+}
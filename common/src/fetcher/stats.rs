@@ -0,0 +1,36 @@
+use std::collections::BTreeMap;
+use url::Url;
+
+/// Statistics of a [`super::Fetcher`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FetcherStats {
+    /// Number of requests sent, including retries
+    pub requests: usize,
+    /// Number of retries
+    pub retries: usize,
+    /// Number of requests which failed, after all retries
+    pub failures: usize,
+    /// Number of retries, per URL
+    pub retried: BTreeMap<Url, usize>,
+}
+
+impl FetcherStats {
+    pub(crate) fn retry(&mut self, url: &Url) {
+        self.retries += 1;
+        *self.retried.entry(url.clone()).or_default() += 1;
+    }
+
+    /// Log the statistics.
+    pub fn log(&self) {
+        log::info!(
+            "Requests: {}, retries: {}, failures: {}",
+            self.requests,
+            self.retries,
+            self.failures
+        );
+
+        for (url, retries) in &self.retried {
+            log::info!("  {url}: {retries} retries");
+        }
+    }
+}
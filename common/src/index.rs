@@ -0,0 +1,148 @@
+//! Entries based on the `index.txt` file.
+
+use crate::{
+    changes::ChangeSource,
+    fetcher::{self, Fetcher},
+};
+use std::collections::HashSet;
+use url::{ParseError, Url};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Fetch error: {0}")]
+    Fetcher(#[from] fetcher::Error),
+    #[error("URL error: {0}")]
+    Url(#[from] ParseError),
+}
+
+/// How to use the `index.txt` file of a directory based distribution.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DirectoryIndex {
+    /// Only use the `changes.csv` file.
+    Changes,
+    /// Use the `changes.csv` file, fall back to `index.txt` if it doesn't exist.
+    #[default]
+    Fallback,
+    /// Use the entries of both files, report entries which are missing in one of them.
+    Union,
+}
+
+/// State of an `index.txt` file.
+pub struct IndexSource {
+    /// The relative file names
+    pub entries: Vec<String>,
+}
+
+impl IndexSource {
+    /// Retrieve a file using a [`Fetcher`].
+    pub async fn retrieve(fetcher: &Fetcher, base_url: &Url) -> Result<Self, Error> {
+        let index = fetcher.fetch::<String>(base_url.join("index.txt")?).await?;
+
+        Ok(Self::parse(&index))
+    }
+
+    /// Retrieve a file using a [`Fetcher`], returning [`None`] if the file doesn't exist.
+    pub async fn retrieve_optional(
+        fetcher: &Fetcher,
+        base_url: &Url,
+    ) -> Result<Option<Self>, Error> {
+        Ok(fetcher
+            .fetch::<Option<String>>(base_url.join("index.txt")?)
+            .await?
+            .map(|index| Self::parse(&index)))
+    }
+
+    /// Parse the content of an `index.txt` file, one file name per line.
+    pub fn parse(index: &str) -> Self {
+        log::info!("Found 'index.txt', processing data");
+
+        let entries: Vec<_> = index
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(ToString::to_string)
+            .collect();
+
+        log::info!("Detected {} entries", entries.len());
+
+        Self { entries }
+    }
+}
+
+/// Entries which are only present in one of `index.txt` and `changes.csv`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IndexMismatch {
+    /// Entries only listed in the `index.txt`
+    pub only_index: Vec<String>,
+    /// Entries only listed in the `changes.csv`
+    pub only_changes: Vec<String>,
+}
+
+impl IndexMismatch {
+    /// Compare the entries of both files.
+    pub fn compare(index: &IndexSource, changes: &ChangeSource) -> Self {
+        let index_files: HashSet<_> = index.entries.iter().map(String::as_str).collect();
+        let changes_files: HashSet<_> = changes
+            .entries
+            .iter()
+            .map(|entry| entry.file.as_str())
+            .collect();
+
+        Self {
+            only_index: index
+                .entries
+                .iter()
+                .filter(|file| !changes_files.contains(file.as_str()))
+                .cloned()
+                .collect(),
+            only_changes: changes
+                .entries
+                .iter()
+                .filter(|entry| !index_files.contains(entry.file.as_str()))
+                .map(|entry| entry.file.clone())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.only_index.is_empty() && self.only_changes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::changes::ChangeEntry;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn parse() {
+        let index = IndexSource::parse("2023/a.json\n\n2023/b.json\r\n  2024/c.json  \n");
+        assert_eq!(
+            index.entries,
+            vec!["2023/a.json", "2023/b.json", "2024/c.json"]
+        );
+    }
+
+    #[test]
+    fn mismatch() {
+        let index = IndexSource::parse("2023/a.json\n2023/b.json\n");
+        let changes = ChangeSource {
+            entries: vec![
+                ChangeEntry {
+                    file: "2023/b.json".to_string(),
+                    timestamp: OffsetDateTime::UNIX_EPOCH,
+                },
+                ChangeEntry {
+                    file: "2023/c.json".to_string(),
+                    timestamp: OffsetDateTime::UNIX_EPOCH,
+                },
+            ],
+        };
+
+        let mismatch = IndexMismatch::compare(&index, &changes);
+        assert!(!mismatch.is_empty());
+        assert_eq!(mismatch.only_index, vec!["2023/a.json"]);
+        assert_eq!(mismatch.only_changes, vec!["2023/c.json"]);
+    }
+}
@@ -2,8 +2,11 @@
 #![forbid(unsafe_code)]
 
 pub mod changes;
+pub mod checkpoint;
+pub mod client;
 pub mod compression;
 pub mod fetcher;
+pub mod index;
 pub mod locale;
 pub mod progress;
 pub mod report;
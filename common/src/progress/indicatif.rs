@@ -25,6 +25,10 @@ impl super::ProgressBar for indicatif::ProgressBar {
         indicatif::ProgressBar::inc(self, work as u64)
     }
 
+    async fn add_work(&mut self, work: usize) {
+        indicatif::ProgressBar::inc_length(self, work as u64)
+    }
+
     async fn finish(self) {
         indicatif::ProgressBar::finish_and_clear(&self)
     }
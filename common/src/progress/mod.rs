@@ -19,6 +19,13 @@ pub trait ProgressBar {
 
     fn increment(&mut self, work: usize) -> impl Future<Output = ()>;
 
+    /// Add to the total amount of work, in case it isn't known upfront.
+    ///
+    /// By default, this does nothing.
+    fn add_work(&mut self, #[allow(unused_variables)] work: usize) -> impl Future<Output = ()> {
+        async {}
+    }
+
     fn finish(self) -> impl Future<Output = ()>;
 
     fn set_message(&mut self, msg: String) -> impl Future<Output = ()>;
@@ -80,6 +87,12 @@ impl<P: ProgressBar> ProgressBar for Option<P> {
         }
     }
 
+    async fn add_work(&mut self, work: usize) {
+        if let Some(bar) = self {
+            bar.add_work(work).await;
+        }
+    }
+
     async fn finish(self) {
         if let Some(bar) = self {
             bar.finish().await;
@@ -0,0 +1,273 @@
+use crate::compression::DecompressionOptions;
+use bytes::{BufMut, Bytes, BytesMut};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tempfile::TempPath;
+use tokio::io::AsyncWriteExt;
+
+/// Options for retrieving the content of documents.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RetrievalOptions {
+    /// The maximum size of a document. Zero means, unlimited.
+    ///
+    /// This is also used as the limit for decompressing a document.
+    pub max_size: usize,
+    /// Write documents to temporary files in this directory, instead of keeping them in memory.
+    ///
+    /// Storing the documents on the same filesystem later on doesn't require copying them.
+    pub spool_dir: Option<PathBuf>,
+}
+
+impl RetrievalOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn spool_dir(mut self, spool_dir: impl Into<Option<PathBuf>>) -> Self {
+        self.spool_dir = spool_dir.into();
+        self
+    }
+
+    /// Options for decompressing a document, sharing the size limit.
+    pub fn decompression(&self) -> DecompressionOptions {
+        DecompressionOptions::new().limit(self.max_size)
+    }
+}
+
+/// The content of a retrieved document, either in memory or spooled to a temporary file.
+#[derive(Clone, Debug)]
+pub enum RetrievedData {
+    Memory(Bytes),
+    File(Arc<SpooledFile>),
+}
+
+/// A temporary file, removed once it is no longer used.
+#[derive(Debug)]
+pub struct SpooledFile {
+    path: TempPath,
+    size: usize,
+}
+
+impl SpooledFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Default for RetrievedData {
+    fn default() -> Self {
+        Self::Memory(Bytes::new())
+    }
+}
+
+impl From<Bytes> for RetrievedData {
+    fn from(value: Bytes) -> Self {
+        Self::Memory(value)
+    }
+}
+
+impl From<Vec<u8>> for RetrievedData {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Memory(value.into())
+    }
+}
+
+impl RetrievedData {
+    /// The size of the document.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Memory(data) => data.len(),
+            Self::File(file) => file.size,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The path of the temporary file, if the document was spooled.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Memory(_) => None,
+            Self::File(file) => Some(file.path()),
+        }
+    }
+
+    /// Get the content, reading it from the temporary file if necessary.
+    pub async fn bytes(&self) -> io::Result<Bytes> {
+        match self {
+            Self::Memory(data) => Ok(data.clone()),
+            Self::File(file) => Ok(tokio::fs::read(file.path()).await?.into()),
+        }
+    }
+
+    /// Get the content, reading it from the temporary file if necessary, blocking the thread.
+    pub fn bytes_blocking(&self) -> io::Result<Bytes> {
+        match self {
+            Self::Memory(data) => Ok(data.clone()),
+            Self::File(file) => Ok(std::fs::read(file.path())?.into()),
+        }
+    }
+
+    /// Get a reader for the content, reading from the temporary file if necessary, blocking the
+    /// thread.
+    ///
+    /// Unlike [`Self::bytes_blocking`], this doesn't load a spooled document into memory.
+    pub fn reader(&self) -> io::Result<Box<dyn io::Read + Send>> {
+        match self {
+            Self::Memory(data) => Ok(Box::new(io::Cursor::new(data.clone()))),
+            Self::File(file) => Ok(Box::new(io::BufReader::new(std::fs::File::open(
+                file.path(),
+            )?))),
+        }
+    }
+
+    /// Store the content into a file, replacing an existing one.
+    ///
+    /// A spooled file will be linked to the target if possible, and only copied otherwise.
+    pub async fn store(&self, target: &Path) -> io::Result<()> {
+        match self {
+            Self::Memory(data) => tokio::fs::write(target, data).await,
+            Self::File(file) => {
+                match tokio::fs::remove_file(target).await {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                    _ => {}
+                }
+
+                if let Err(err) = tokio::fs::hard_link(file.path(), target).await {
+                    log::debug!("Unable to link spooled file, copying instead: {err}");
+                    tokio::fs::copy(file.path(), target).await?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Collecting the content of a document while it is being retrieved.
+pub struct RetrievingData {
+    target: Target,
+    size: usize,
+    max_size: usize,
+}
+
+enum Target {
+    Memory(BytesMut),
+    File(tokio::fs::File, TempPath),
+}
+
+impl RetrievingData {
+    pub fn new(options: &RetrievalOptions) -> io::Result<Self> {
+        let target = match &options.spool_dir {
+            None => Target::Memory(BytesMut::new()),
+            Some(dir) => {
+                let mut builder = tempfile::Builder::new();
+                builder.prefix(".walker-");
+                // the file might get linked to the output, so it needs regular permissions
+                #[cfg(unix)]
+                builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o644));
+
+                let (file, path) = builder.tempfile_in(dir)?.into_parts();
+                Target::File(tokio::fs::File::from_std(file), path)
+            }
+        };
+
+        Ok(Self {
+            target,
+            size: 0,
+            max_size: options.max_size,
+        })
+    }
+
+    /// Append a chunk of data.
+    pub async fn write(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.size += chunk.len();
+        if self.max_size > 0 && self.size > self.max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::FileTooLarge,
+                format!(
+                    "document exceeds the maximum size of {} bytes",
+                    self.max_size
+                ),
+            ));
+        }
+
+        match &mut self.target {
+            Target::Memory(data) => {
+                data.put(chunk);
+                Ok(())
+            }
+            Target::File(file, _) => file.write_all(chunk).await,
+        }
+    }
+
+    /// Complete the retrieval.
+    pub async fn finish(self) -> io::Result<RetrievedData> {
+        match self.target {
+            Target::Memory(data) => Ok(RetrievedData::Memory(data.freeze())),
+            Target::File(mut file, path) => {
+                file.flush().await?;
+                Ok(RetrievedData::File(Arc::new(SpooledFile {
+                    path,
+                    size: self.size,
+                })))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn spool() {
+        let dir = tempfile::tempdir().expect("must create");
+        let options = RetrievalOptions::new()
+            .max_size(8)
+            .spool_dir(dir.path().to_path_buf());
+
+        let mut data = RetrievingData::new(&options).expect("must create");
+        data.write(b"1234").await.expect("must write");
+        data.write(b"5678").await.expect("must write");
+        let data = data.finish().await.expect("must finish");
+
+        assert_eq!(data.len(), 8);
+        assert_eq!(data.bytes().await.expect("must read"), &b"12345678"[..]);
+
+        let mut content = vec![];
+        io::Read::read_to_end(&mut data.reader().expect("must open"), &mut content)
+            .expect("must read");
+        assert_eq!(content, b"12345678");
+
+        let target = dir.path().join("stored");
+        data.store(&target).await.expect("must store");
+        assert_eq!(std::fs::read(&target).expect("must read"), b"12345678");
+
+        // the temporary file must be removed, the stored one must remain
+        let spooled = data.path().expect("must be spooled").to_path_buf();
+        drop(data);
+        assert!(!spooled.exists());
+        assert!(target.exists());
+    }
+
+    #[tokio::test]
+    async fn max_size() {
+        let options = RetrievalOptions::new().max_size(4);
+
+        let mut data = RetrievingData::new(&options).expect("must create");
+        data.write(b"1234").await.expect("must write");
+        let err = data.write(b"5").await.expect_err("must fail");
+        assert_eq!(err.kind(), io::ErrorKind::FileTooLarge);
+    }
+}
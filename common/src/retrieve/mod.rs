@@ -1,6 +1,8 @@
 //! Retrieving remote content
 
+mod data;
 mod error;
+pub use data::*;
 pub use error::*;
 
 use crate::utils::{hex::Hex, url::Urlify};
@@ -11,8 +13,8 @@ use std::{
 };
 use time::OffsetDateTime;
 
-pub trait RetrievedDocument: Urlify + Debug {
-    type Discovered: Urlify + Debug;
+pub trait RetrievedDocument: Urlify + Debug + Send + Sync {
+    type Discovered: Urlify + Debug + Send + Sync;
 }
 
 /// The retrieved digest
@@ -84,7 +86,7 @@ where
 }
 
 /// Metadata of the retrieval process.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct RetrievalMetadata {
     /// Last known modification time
     pub last_modification: Option<OffsetDateTime>,
@@ -213,10 +213,10 @@ impl Source {
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct S3 {
-    region: String,
-    credentials: Option<(String, String)>,
-    bucket: String,
-    key: Option<String>,
+    pub(crate) region: String,
+    pub(crate) credentials: Option<(String, String)>,
+    pub(crate) bucket: String,
+    pub(crate) key: Option<String>,
 }
 
 impl TryFrom<&str> for S3 {
@@ -263,7 +263,8 @@ impl S3 {
 
         let mut shared_config = aws_config::defaults(BehaviorVersion::v2025_01_17())
             .region(region_provider)
-            .app_name(AppName::new(USER_AGENT)?);
+            // the app name must not contain a slash
+            .app_name(AppName::new(USER_AGENT.replace('/', "_"))?);
 
         if let Some((key_id, access_key)) = &self.credentials {
             let credentials = Credentials::new(key_id, access_key, None, None, "config");
@@ -272,7 +273,13 @@ impl S3 {
 
         let shared_config = shared_config.load().await;
 
-        Ok(Client::new(&shared_config))
+        // custom endpoints (e.g. MinIO, using `AWS_ENDPOINT_URL`) commonly don't support
+        // virtual-hosted style requests
+        let config = aws_sdk_s3::config::Builder::from(&shared_config)
+            .force_path_style(shared_config.endpoint_url().is_some())
+            .build();
+
+        Ok(Client::from_conf(config))
     }
 }
 
@@ -6,11 +6,10 @@ mod error;
 pub use error::*;
 
 use crate::{
-    USER_AGENT,
+    client::ClientOptions,
     sender::provider::{TokenInjector, TokenProvider},
 };
-use anyhow::Context;
-use reqwest::{IntoUrl, Method, RequestBuilder, header};
+use reqwest::{IntoUrl, Method, RequestBuilder};
 use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
 
 #[derive(Clone)]
@@ -26,8 +25,11 @@ pub struct HttpSender {
 pub struct HttpSenderOptions {
     pub connect_timeout: Option<Duration>,
     pub timeout: Option<Duration>,
+    /// Additional root certificates, in addition to the ones of the client options
     pub additional_root_certificates: Vec<PathBuf>,
+    /// Allow using TLS in an insecure mode, also if the client options don't
     pub tls_insecure: bool,
+    pub client: ClientOptions,
     pub query_parameters: HashMap<String, String>,
 }
 
@@ -46,6 +48,11 @@ impl HttpSenderOptions {
         self
     }
 
+    pub fn client(mut self, client: ClientOptions) -> Self {
+        self.client = client;
+        self
+    }
+
     pub fn query_parameters<I>(mut self, query_parameters: I) -> Self
     where
         I: IntoIterator<Item = (String, String)>,
@@ -111,10 +118,13 @@ impl HttpSender {
     where
         P: TokenProvider + 'static,
     {
-        let mut headers = header::HeaderMap::new();
-        headers.insert("User-Agent", header::HeaderValue::from_static(USER_AGENT));
+        let mut client_options = options.client;
+        client_options
+            .additional_root_certificates
+            .extend(options.additional_root_certificates);
+        client_options.tls_insecure |= options.tls_insecure;
 
-        let mut client = reqwest::ClientBuilder::new().default_headers(headers);
+        let mut client = client_options.builder()?;
 
         if let Some(connect_timeout) = options.connect_timeout {
             client = client.connect_timeout(connect_timeout);
@@ -124,20 +134,6 @@ impl HttpSender {
             client = client.timeout(timeout);
         }
 
-        for cert in options.additional_root_certificates {
-            let cert = std::fs::read(&cert)
-                .with_context(|| format!("Reading certificate: {}", cert.display()))?;
-            let cert = reqwest::tls::Certificate::from_pem(&cert)?;
-            client = client.add_root_certificate(cert);
-        }
-
-        if options.tls_insecure {
-            log::warn!("Disabling TLS validation");
-            client = client
-                .danger_accept_invalid_hostnames(true)
-                .danger_accept_invalid_certs(true);
-        }
-
         Ok(Self {
             client: client.build()?,
             provider: Arc::new(provider),
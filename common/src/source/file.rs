@@ -41,6 +41,20 @@ pub async fn read_sig_and_digests(
         read_optional(format!("{}.sha512", path.display())),
     )?;
 
+    let (sha256, sha512) = digests(data, sha256, sha512);
+
+    Ok((signature, sha256, sha512))
+}
+
+/// Create the digests of the data, from the content of the digest files
+pub fn digests(
+    data: &Bytes,
+    sha256: Option<String>,
+    sha512: Option<String>,
+) -> (
+    Option<RetrievedDigest<Sha256>>,
+    Option<RetrievedDigest<Sha512>>,
+) {
     let sha256 = sha256
         // take the first "word" from the line
         .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
@@ -65,5 +79,5 @@ pub async fn read_sig_and_digests(
             }
         });
 
-    Ok((signature, sha256, sha512))
+    (sha256, sha512)
 }
@@ -4,8 +4,9 @@ use crate::retrieve::RetrievedDocument;
 use std::fmt::{Debug, Display};
 
 pub mod file;
+pub mod s3;
 
 pub trait Source {
-    type Error: Display + Debug;
+    type Error: Display + Debug + Send + Sync + 'static;
     type Retrieved: RetrievedDocument;
 }
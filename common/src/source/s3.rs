@@ -0,0 +1,110 @@
+use crate::{
+    retrieve::{RetrievalMetadata, RetrievedDigest},
+    source::file::digests,
+    store::{ObjectHead, S3Storage, StoredObject},
+};
+use anyhow::anyhow;
+use bytes::Bytes;
+use futures_util::{StreamExt, TryStreamExt, stream, try_join};
+use sha2::{Sha256, Sha512};
+use url::Url;
+
+/// A document read from an S3 compatible bucket, along with its signature and digests.
+pub struct S3Document {
+    pub data: Bytes,
+    pub signature: Option<String>,
+    pub sha256: Option<RetrievedDigest<Sha256>>,
+    pub sha512: Option<RetrievedDigest<Sha512>>,
+    pub metadata: RetrievalMetadata,
+}
+
+/// Read a document stored by [`S3Storage::store_document`].
+///
+/// If the ETag, or otherwise the modification timestamp, of the local copy matches the stored
+/// document, it is considered unchanged and [`None`] is returned.
+pub async fn read_document(
+    storage: &S3Storage,
+    local: Option<&RetrievalMetadata>,
+) -> anyhow::Result<Option<S3Document>> {
+    if let Some(local) = local {
+        let stored = storage
+            .stored_metadata()
+            .await?
+            .ok_or_else(|| anyhow!("Object not found: {storage}"))?;
+        let unchanged = match (&local.etag, &stored.etag) {
+            (Some(local), Some(stored)) => local == stored,
+            _ => local.last_modification >= stored.last_modification,
+        };
+        if unchanged {
+            return Ok(None);
+        }
+    }
+
+    let ((data, metadata), signature, sha256, sha512) = try_join!(
+        async {
+            storage
+                .read()
+                .await?
+                .ok_or_else(|| anyhow!("Object not found: {storage}"))
+        },
+        read_text(storage.sidecar("asc")),
+        read_text(storage.sidecar("sha256")),
+        read_text(storage.sidecar("sha512")),
+    )?;
+
+    let (sha256, sha512) = digests(&data, sha256, sha512);
+
+    Ok(Some(S3Document {
+        data,
+        signature,
+        sha256,
+        sha512,
+        metadata,
+    }))
+}
+
+async fn read_text(storage: S3Storage) -> anyhow::Result<Option<String>> {
+    Ok(match storage.read_optional().await? {
+        Some(data) => Some(String::from_utf8(data.into())?),
+        None => None,
+    })
+}
+
+/// The number of objects to look up at the same time.
+const HEAD_CONCURRENCY: usize = 16;
+
+/// Look up the metadata stored with each object, like its modification timestamp.
+///
+/// Objects which got removed in the meantime are skipped.
+pub async fn head_objects(
+    objects: impl IntoIterator<Item = StoredObject>,
+) -> anyhow::Result<Vec<(StoredObject, ObjectHead)>> {
+    stream::iter(objects)
+        .map(async |object| {
+            let head = object.storage.head().await?;
+            Ok(head.map(|head| (object, head)))
+        })
+        .buffered(HEAD_CONCURRENCY)
+        .try_filter_map(async |entry| Ok(entry))
+        .try_collect()
+        .await
+}
+
+/// Scan for keys (`<fingerprint>.txt`) stored below a key, returning their fingerprints and URLs.
+pub async fn scan_keys(storage: &S3Storage) -> anyhow::Result<Vec<(String, Url)>> {
+    Ok(storage
+        .list()
+        .await?
+        .into_iter()
+        .filter_map(|object| {
+            let fingerprint = object
+                .storage
+                .key()
+                .rsplit('/')
+                .next()?
+                .strip_suffix(".txt")?
+                .to_string();
+            Some((fingerprint, object.storage.url()))
+        })
+        .collect())
+}
@@ -0,0 +1,626 @@
+//! Storing documents
+
+mod s3;
+
+pub use s3::*;
+
+use crate::retrieve::{RetrievalMetadata, RetrievedData, RetrievedDigest};
+use anyhow::Context;
+use bytes::Bytes;
+use sha2::{Sha256, Sha512};
+use std::fmt::{Display, Formatter};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+use tempfile::TempPath;
+use time::OffsetDateTime;
+use tokio::fs;
+
+#[cfg(target_os = "macos")]
+pub const ATTR_ETAG: &str = "etag";
+#[cfg(target_os = "linux")]
+pub const ATTR_ETAG: &str = "user.etag";
+
+/// The name of the extended attribute holding an additional attribute of a document.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn attribute_name(name: &str) -> String {
+    match cfg!(target_os = "linux") {
+        true => format!("user.{name}"),
+        false => name.to_string(),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("{0:#}")]
+    Io(anyhow::Error),
+    #[error("Failed to construct filename from URL: {0}")]
+    Filename(String),
+    #[error("Serialize key error: {0:#}")]
+    SerializeKey(anyhow::Error),
+    #[error("{0:#}")]
+    S3(anyhow::Error),
+}
+
+/// A location to store documents at, on the local filesystem or in an S3 compatible bucket.
+///
+/// This can be parsed from a string, which is an S3 URL (`s3://…`, see [`S3Storage`]), or a
+/// path.
+#[derive(Clone, Debug)]
+pub enum Storage {
+    File(PathBuf),
+    S3(S3Storage),
+}
+
+impl FromStr for Storage {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value.starts_with("s3://") {
+            true => Self::S3(S3Storage::try_from(value)?),
+            false => Self::File(value.into()),
+        })
+    }
+}
+
+impl From<PathBuf> for Storage {
+    fn from(value: PathBuf) -> Self {
+        Self::File(value)
+    }
+}
+
+impl From<&Path> for Storage {
+    fn from(value: &Path) -> Self {
+        Self::File(value.to_path_buf())
+    }
+}
+
+impl From<S3Storage> for Storage {
+    fn from(value: S3Storage) -> Self {
+        Self::S3(value)
+    }
+}
+
+impl Display for Storage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::S3(s3) => write!(f, "{s3}"),
+        }
+    }
+}
+
+impl Storage {
+    /// A location below this one. The name may contain multiple segments, separated by `/`.
+    pub fn join(&self, name: &str) -> Self {
+        match self {
+            Self::File(path) => Self::File(path.join(name)),
+            Self::S3(s3) => Self::S3(s3.join(name)),
+        }
+    }
+
+    /// The path on the local filesystem, if this is one.
+    pub fn as_path(&self) -> Option<&Path> {
+        match self {
+            Self::File(path) => Some(path),
+            Self::S3(_) => None,
+        }
+    }
+
+    /// Store a document at this location, see [`store_document`] and
+    /// [`S3Storage::store_document`].
+    pub async fn store_document(&self, document: Document<'_>) -> Result<(), StoreError> {
+        match self {
+            Self::File(path) => store_document(path, document).await,
+            Self::S3(s3) => s3.store_document(document).await,
+        }
+    }
+
+    /// Store some data at this location, as it is.
+    pub async fn write(&self, data: impl Into<Bytes>) -> Result<(), StoreError> {
+        match self {
+            Self::File(path) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .await
+                        .with_context(|| {
+                            format!("Failed to create parent directory: {}", parent.display())
+                        })
+                        .map_err(StoreError::Io)?;
+                }
+                fs::write(path, data.into())
+                    .await
+                    .with_context(|| format!("Failed to write file: {}", path.display()))
+                    .map_err(StoreError::Io)
+            }
+            Self::S3(s3) => s3.write(data).await,
+        }
+    }
+
+    /// Set the modification timestamp of the document stored at this location.
+    pub async fn set_document_timestamp(&self, mtime: SystemTime) -> Result<(), StoreError> {
+        match self {
+            Self::File(path) => set_document_timestamp(path, mtime),
+            Self::S3(s3) => s3.set_document_timestamp(mtime).await,
+        }
+    }
+
+    /// Remove staging files left behind by interrupted runs, see [`remove_staged_files`].
+    ///
+    /// Objects in S3 are written in a single request, so there are no staging files to remove.
+    pub fn remove_staged_files(&self) -> Result<usize, StoreError> {
+        match self {
+            Self::File(path) => remove_staged_files(path),
+            Self::S3(_) => Ok(0),
+        }
+    }
+
+    /// Read the retrieval metadata of the document stored at this location, if there is one.
+    pub async fn stored_metadata(&self) -> Result<Option<RetrievalMetadata>, StoreError> {
+        match self {
+            Self::File(path) => stored_metadata(path)
+                .await
+                .with_context(|| format!("Failed to read file metadata: {}", path.display()))
+                .map_err(StoreError::Io),
+            Self::S3(s3) => s3.stored_metadata().await,
+        }
+    }
+}
+
+pub struct Document<'a> {
+    /// The data to store
+    pub data: &'a RetrievedData,
+    /// An optional SHA256 digest
+    pub sha256: &'a Option<RetrievedDigest<Sha256>>,
+    /// An optional SHA512 digest
+    pub sha512: &'a Option<RetrievedDigest<Sha512>>,
+    /// An optional signature
+    pub signature: &'a Option<String>,
+
+    /// Last change date
+    pub changed: SystemTime,
+
+    /// Metadata from the retrieval process
+    pub metadata: &'a RetrievalMetadata,
+
+    /// Additional attributes, as pairs of name and value
+    ///
+    /// They are stored as extended attributes of the file, or as metadata of the S3 object. They
+    /// can be read using [`stored_attribute`] or [`ObjectHead::attribute`].
+    pub attributes: &'a [(&'a str, String)],
+
+    pub no_timestamps: bool,
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    pub no_xattrs: bool,
+}
+
+/// The suffix of staging files, which get renamed into place once they are complete.
+const STAGING_SUFFIX: &str = ".tmp";
+
+/// Store a document, along with its digests and signature.
+///
+/// All files are written to staging files first, and then renamed into place. The previous
+/// document is removed before its sidecar files are replaced, and the new document is renamed
+/// into place last. So if storing gets interrupted, there either is the complete previous entry,
+/// or no document at all, which then gets fetched again.
+///
+/// Files are synced before being renamed, and the directory is synced afterwards, so that the
+/// renamed files survive a crash too.
+pub async fn store_document(file: &Path, document: Document<'_>) -> Result<(), StoreError> {
+    log::debug!("Writing {}", file.display());
+
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create parent directory: {}", parent.display()))
+            .map_err(StoreError::Io)?;
+    }
+
+    let staged = staging_file(file)?;
+
+    document
+        .data
+        .store(&staged)
+        .await
+        .with_context(|| format!("Failed to write advisory: {}", file.display()))
+        .map_err(StoreError::Io)?;
+
+    if !document.no_timestamps {
+        // We use the retrieval metadata timestamp as file timestamp. If that's not available, then
+        // we use the change entry timestamp.
+        let mtime = document
+            .metadata
+            .last_modification
+            .map(SystemTime::from)
+            .unwrap_or_else(|| document.changed);
+        set_document_timestamp(&staged, mtime)?;
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    if !document.no_xattrs {
+        if let Some(etag) = &document.metadata.etag {
+            xattr::set(&staged, ATTR_ETAG, etag.as_bytes())
+                .with_context(|| format!("Failed to store {}: {}", ATTR_ETAG, file.display()))
+                .map_err(StoreError::Io)?;
+        }
+        for (name, value) in document.attributes {
+            xattr::set(&staged, attribute_name(name), value.as_bytes())
+                .with_context(|| format!("Failed to store {name}: {}", file.display()))
+                .map_err(StoreError::Io)?;
+        }
+    }
+
+    sync_file(&staged)
+        .await
+        .with_context(|| format!("Failed to sync advisory: {}", file.display()))
+        .map_err(StoreError::Io)?;
+
+    remove_optional(file)
+        .await
+        .with_context(|| format!("Failed to remove previous advisory: {}", file.display()))
+        .map_err(StoreError::Io)?;
+
+    store_sidecar(
+        file,
+        "sha256",
+        document.sha256.as_ref().map(|sha256| &sha256.expected),
+    )
+    .await
+    .context("Failed to write checksum")
+    .map_err(StoreError::Io)?;
+    store_sidecar(
+        file,
+        "sha512",
+        document.sha512.as_ref().map(|sha512| &sha512.expected),
+    )
+    .await
+    .context("Failed to write checksum")
+    .map_err(StoreError::Io)?;
+    store_sidecar(file, "asc", document.signature.as_ref())
+        .await
+        .context("Failed to write signature")
+        .map_err(StoreError::Io)?;
+
+    staged
+        .persist(file)
+        .with_context(|| format!("Failed to commit advisory: {}", file.display()))
+        .map_err(StoreError::Io)?;
+
+    // the sidecar files are in the same directory, so this covers their renames too
+    if let Some(parent) = file.parent() {
+        sync_dir(parent)
+            .with_context(|| format!("Failed to sync directory: {}", parent.display()))
+            .map_err(StoreError::Io)?;
+    }
+
+    Ok(())
+}
+
+/// Create a staging file for a file, in the same directory.
+///
+/// The staging file is removed when being dropped, unless it gets persisted.
+fn staging_file(file: &Path) -> Result<TempPath, StoreError> {
+    let (Some(dir), Some(name)) = (file.parent(), file.file_name()) else {
+        return Err(StoreError::Filename(file.display().to_string()));
+    };
+
+    let mut prefix = std::ffi::OsString::from(".");
+    prefix.push(name);
+    prefix.push(".");
+
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(&prefix).suffix(STAGING_SUFFIX);
+    // staging files get renamed into place, so they need regular permissions
+    #[cfg(unix)]
+    builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o644));
+
+    Ok(builder
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to create staging file for: {}", file.display()))
+        .map_err(StoreError::Io)?
+        .into_temp_path())
+}
+
+/// Store a sidecar file of a document, or remove a stale one if there is no content.
+async fn store_sidecar(
+    file: &Path,
+    extension: &str,
+    content: Option<&String>,
+) -> anyhow::Result<()> {
+    let target = PathBuf::from(format!("{}.{extension}", file.display()));
+
+    let Some(content) = content else {
+        return remove_optional(&target)
+            .await
+            .with_context(|| format!("Failed to remove stale file: {}", target.display()));
+    };
+
+    let staged = staging_file(&target)?;
+    fs::write(&staged, content)
+        .await
+        .with_context(|| format!("Failed to write file: {}", target.display()))?;
+    sync_file(&staged)
+        .await
+        .with_context(|| format!("Failed to sync file: {}", target.display()))?;
+    staged
+        .persist(&target)
+        .with_context(|| format!("Failed to commit file: {}", target.display()))?;
+
+    Ok(())
+}
+
+/// Flush the content and metadata of a file to the disk.
+async fn sync_file(file: &Path) -> std::io::Result<()> {
+    fs::File::open(file).await?.sync_all().await
+}
+
+/// Flush a directory to the disk, persisting renames of files inside it.
+///
+/// Directories can only be synced this way on Unix-like systems.
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    std::fs::File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Remove a file, if it exists.
+async fn remove_optional(file: &Path) -> std::io::Result<()> {
+    match fs::remove_file(file).await {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Get the file a staging file was created for.
+///
+/// A staging file left behind means that storing that file was interrupted. If the file itself
+/// is a document, there's no document, or the complete previous one.
+pub fn staged_file(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let name = name.strip_prefix('.')?.strip_suffix(STAGING_SUFFIX)?;
+    // strip the random part
+    let (name, _) = name.rsplit_once('.')?;
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(path.with_file_name(name))
+}
+
+/// Remove staging files left behind by interrupted runs, returning the number of removed files.
+///
+/// This walks the directory recursively, and must not run while documents are being stored into
+/// it, as that would remove their staging files too.
+pub fn remove_staged_files(dir: &Path) -> Result<usize, StoreError> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry
+            .with_context(|| format!("Failed to walk directory: {}", dir.display()))
+            .map_err(StoreError::Io)?;
+        if !entry.file_type().is_file() || staged_file(entry.path()).is_none() {
+            continue;
+        }
+
+        log::debug!("Removing staging file: {}", entry.path().display());
+        std::fs::remove_file(entry.path())
+            .with_context(|| format!("Failed to remove staging file: {}", entry.path().display()))
+            .map_err(StoreError::Io)?;
+        removed += 1;
+    }
+
+    Ok(removed)
+}
+
+/// Set the modification timestamp of a stored document.
+pub fn set_document_timestamp(file: &Path, mtime: SystemTime) -> Result<(), StoreError> {
+    filetime::set_file_mtime(file, mtime.into())
+        .with_context(|| {
+            format!(
+                "Failed to set last modification timestamp: {}",
+                file.display()
+            )
+        })
+        .map_err(StoreError::Io)
+}
+
+/// Read the retrieval metadata of a stored document.
+///
+/// This is the modification timestamp of the file and, if available, the ETag stored by
+/// [`store_document`]. If the file doesn't exist, [`None`] is returned.
+pub async fn stored_metadata(file: &Path) -> Result<Option<RetrievalMetadata>, std::io::Error> {
+    let metadata = match fs::metadata(file).await {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    let last_modification = metadata.modified().ok().map(OffsetDateTime::from);
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let etag = xattr::get(file, ATTR_ETAG)
+        .transpose()
+        .and_then(|r| r.ok())
+        .and_then(|s| String::from_utf8(s).ok());
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let etag = None;
+
+    Ok(Some(RetrievalMetadata {
+        last_modification,
+        etag,
+    }))
+}
+
+/// Read an additional attribute of a stored document, see [`Document::attributes`].
+///
+/// If the file or the attribute doesn't exist, or extended attributes are not supported,
+/// [`None`] is returned.
+pub fn stored_attribute(file: &Path, name: &str) -> Option<String> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    return xattr::get(file, attribute_name(name))
+        .ok()
+        .flatten()
+        .and_then(|value| String::from_utf8(value).ok());
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    return None;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[tokio::test]
+    async fn store_atomically() {
+        let dir = tempfile::tempdir().expect("must create");
+        let file = dir.path().join("2023").join("a.json");
+
+        let metadata = RetrievalMetadata::default();
+        let document = |data, signature| Document {
+            data,
+            sha256: &None,
+            sha512: &None,
+            signature,
+            changed: SystemTime::UNIX_EPOCH,
+            metadata: &metadata,
+            attributes: &[],
+            no_timestamps: false,
+            #[cfg(any(target_os = "linux", target_os = "macos"))]
+            no_xattrs: true,
+        };
+
+        let data = RetrievedData::from(Bytes::from_static(b"{}"));
+        let signature = Some("signature".to_string());
+        store_document(&file, document(&data, &signature))
+            .await
+            .expect("must store");
+        assert_eq!(std::fs::read_to_string(&file).expect("must read"), "{}");
+        assert!(dir.path().join("2023/a.json.asc").is_file());
+
+        // storing again without a signature removes the stale one
+        let data = RetrievedData::from(Bytes::from_static(b"{ }"));
+        store_document(&file, document(&data, &None))
+            .await
+            .expect("must store");
+        assert_eq!(std::fs::read_to_string(&file).expect("must read"), "{ }");
+        assert!(!dir.path().join("2023/a.json.asc").exists());
+        assert_eq!(
+            std::fs::metadata(&file)
+                .and_then(|metadata| metadata.modified())
+                .expect("must read"),
+            SystemTime::UNIX_EPOCH
+        );
+
+        // no staging files are left behind
+        let files = std::fs::read_dir(dir.path().join("2023"))
+            .expect("must read")
+            .map(|entry| entry.expect("must read").file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec!["a.json"]);
+    }
+
+    #[test]
+    fn storage() {
+        let storage = Storage::from_str("out").expect("must parse");
+        assert_eq!(
+            storage.join("metadata/keys").as_path(),
+            Some(Path::new("out/metadata/keys"))
+        );
+
+        let storage = Storage::from_str("s3://us-east-1/bucket/mirror").expect("must parse");
+        assert!(storage.as_path().is_none());
+        assert_eq!(
+            storage.join("metadata/keys").to_string(),
+            "s3://us-east-1/bucket/mirror/metadata/keys"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn attributes() {
+        let dir = tempfile::tempdir().expect("must create");
+        let file = dir.path().join("a.json");
+
+        let data = RetrievedData::from(Bytes::from_static(b"{}"));
+        let metadata = RetrievalMetadata {
+            last_modification: None,
+            etag: Some("\"foo\"".to_string()),
+        };
+        store_document(
+            &file,
+            Document {
+                data: &data,
+                sha256: &None,
+                sha512: &None,
+                signature: &None,
+                changed: SystemTime::UNIX_EPOCH,
+                metadata: &metadata,
+                attributes: &[("categories", "Product A".to_string())],
+                no_timestamps: false,
+                no_xattrs: false,
+            },
+        )
+        .await
+        .expect("must store");
+
+        assert_eq!(
+            stored_attribute(&file, "categories").as_deref(),
+            Some("Product A")
+        );
+        assert_eq!(stored_attribute(&file, "other"), None);
+        assert_eq!(
+            stored_metadata(&file)
+                .await
+                .expect("must read")
+                .and_then(|metadata| metadata.etag),
+            metadata.etag
+        );
+    }
+
+    #[test]
+    fn staged() {
+        let dir = Path::new("/out/2023");
+        assert_eq!(
+            staged_file(&dir.join(".a.json.x1Yz2.tmp")),
+            Some(dir.join("a.json"))
+        );
+        assert_eq!(
+            staged_file(&dir.join(".a.json.asc.x1Yz2.tmp")),
+            Some(dir.join("a.json.asc"))
+        );
+        assert_eq!(staged_file(&dir.join("a.json")), None);
+        assert_eq!(staged_file(&dir.join(".x1Yz2.tmp")), None);
+    }
+
+    #[test]
+    fn remove_staged() {
+        let dir = tempfile::tempdir().expect("must create");
+        let sub = dir.path().join("2023");
+        std::fs::create_dir_all(&sub).expect("must create");
+        for name in ["a.json", ".a.json.x1Yz2.tmp", ".a.json.asc.x1Yz2.tmp"] {
+            std::fs::write(sub.join(name), "{}").expect("must write");
+        }
+        std::fs::write(dir.path().join(".b.json.x1Yz2.tmp"), "{}").expect("must write");
+
+        assert_eq!(remove_staged_files(dir.path()).expect("must remove"), 3);
+
+        let files = std::fs::read_dir(&sub)
+            .expect("must read")
+            .map(|entry| entry.expect("must read").file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec!["a.json"]);
+        assert!(!dir.path().join(".b.json.x1Yz2.tmp").exists());
+
+        // a missing directory has nothing to remove
+        assert_eq!(
+            remove_staged_files(&dir.path().join("missing")).expect("must remove"),
+            0
+        );
+    }
+}
@@ -0,0 +1,520 @@
+//! Storing documents in an S3 compatible bucket
+
+use super::{Document, StoreError};
+use crate::{
+    retrieve::{RetrievalMetadata, RetrievedData},
+    scoop::S3,
+};
+use anyhow::Context;
+use aws_sdk_s3::{Client, primitives::ByteStream, types::MetadataDirective};
+use bytes::Bytes;
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    sync::Arc,
+    time::SystemTime,
+};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tokio::sync::OnceCell;
+use url::Url;
+
+/// The object metadata entry holding the ETag reported when retrieving the document.
+pub const META_ETAG: &str = "etag";
+/// The object metadata entry holding the modification timestamp of the document (RFC 3339).
+pub const META_LAST_MODIFIED: &str = "last-modified";
+
+/// The characters to encode in the values of additional attributes.
+const ATTRIBUTE_ENCODE_SET: &percent_encoding::AsciiSet = &percent_encoding::CONTROLS.add(b'%');
+
+/// A key in an S3 compatible bucket.
+///
+/// The key is used as a prefix for keys joined to it. The client is created when first being
+/// used, and shared with all keys joined to this one.
+#[derive(Clone, Debug)]
+pub struct S3Storage {
+    location: S3,
+    client: Arc<OnceCell<Client>>,
+}
+
+/// An object listed by [`S3Storage::list`].
+#[derive(Clone, Debug)]
+pub struct StoredObject {
+    pub storage: S3Storage,
+    /// The time the object was stored
+    pub modified: SystemTime,
+    pub size: Option<u64>,
+}
+
+/// The metadata of a stored object, see [`S3Storage::head`].
+#[derive(Clone, Debug)]
+pub struct ObjectHead {
+    /// The retrieval metadata stored with the object
+    pub metadata: RetrievalMetadata,
+    pub size: Option<u64>,
+    object_metadata: HashMap<String, String>,
+}
+
+impl ObjectHead {
+    /// Get an additional attribute of the document, see [`Document::attributes`].
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        self.object_metadata.get(name).map(|value| {
+            percent_encoding::percent_decode_str(value)
+                .decode_utf8_lossy()
+                .into_owned()
+        })
+    }
+
+    /// The modification timestamp of the document, as stored by [`S3Storage::store_document`].
+    pub fn modified(&self) -> SystemTime {
+        self.metadata
+            .last_modification
+            .map(SystemTime::from)
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    }
+}
+
+impl TryFrom<&str> for S3Storage {
+    type Error = anyhow::Error;
+
+    /// Parse an S3 URL: `s3://[<key id>:<access key>@]<region>/<bucket>[/<key>]`
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(Self::new(S3::try_from(value)?))
+    }
+}
+
+impl Display for S3Storage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.url())
+    }
+}
+
+impl S3Storage {
+    pub fn new(mut location: S3) -> Self {
+        location.key = location
+            .key
+            .map(|key| key.trim_matches('/').to_string())
+            .filter(|key| !key.is_empty());
+
+        Self {
+            location,
+            client: Default::default(),
+        }
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.location.bucket
+    }
+
+    pub fn key(&self) -> &str {
+        self.location.key.as_deref().unwrap_or_default()
+    }
+
+    /// A key below this one, separated by a `/`.
+    pub fn join(&self, name: &str) -> Self {
+        let name = name.trim_matches('/');
+        let key = match (self.key(), name) {
+            (key, "") => key.to_string(),
+            ("", name) => name.to_string(),
+            (key, name) => format!("{key}/{name}"),
+        };
+
+        Self {
+            location: S3 {
+                key: Some(key).filter(|key| !key.is_empty()),
+                ..self.location.clone()
+            },
+            client: self.client.clone(),
+        }
+    }
+
+    /// The key of a file stored next to a document, like its signature.
+    pub fn sidecar(&self, extension: &str) -> Self {
+        self.with_key(&format!("{}.{extension}", self.key()))
+    }
+
+    /// A key in the same bucket, not relative to this one.
+    fn with_key(&self, key: &str) -> Self {
+        Self {
+            location: S3 {
+                key: None,
+                ..self.location.clone()
+            },
+            client: self.client.clone(),
+        }
+        .join(key)
+    }
+
+    /// The URL of the key, without credentials: `s3://<region>/<bucket>/<key>`.
+    ///
+    /// Each segment of the key is percent-encoded.
+    pub fn url(&self) -> Url {
+        let mut url = Url::parse("s3://localhost/").expect("static URL must parse");
+        let _ = url.set_host(Some(&self.location.region));
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.clear().push(self.bucket());
+            if !self.key().is_empty() {
+                segments.extend(self.key().split('/'));
+            }
+        }
+        url
+    }
+
+    /// The URL of the key, as a directory, ending with a `/`.
+    pub fn directory_url(&self) -> Url {
+        let mut url = self.url();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.push("");
+        }
+        url
+    }
+
+    /// The key a URL (created by [`S3Storage::url`]) points to, if it is in the same bucket.
+    pub fn from_url(&self, url: &Url) -> Option<Self> {
+        if url.scheme() != "s3" || url.host_str() != Some(self.location.region.as_str()) {
+            return None;
+        }
+
+        let mut segments = url.path_segments()?.map(|segment| {
+            percent_encoding::percent_decode_str(segment)
+                .decode_utf8_lossy()
+                .to_string()
+        });
+        if segments.next()? != self.bucket() {
+            return None;
+        }
+
+        Some(self.with_key(&segments.collect::<Vec<_>>().join("/")))
+    }
+
+    async fn client(&self) -> Result<&Client, StoreError> {
+        self.client
+            .get_or_try_init(|| self.location.client())
+            .await
+            .context("Failed to create S3 client")
+            .map_err(StoreError::S3)
+    }
+
+    /// Store a document, along with its digests and signature.
+    ///
+    /// Each file is stored as an object, named like the files on the filesystem. The ETag and
+    /// modification timestamp of the document are stored in its object metadata. Like for the
+    /// filesystem, the document is removed first, and stored last.
+    pub async fn store_document(&self, document: Document<'_>) -> Result<(), StoreError> {
+        log::debug!("Storing {self}");
+
+        self.delete().await?;
+
+        for (extension, content) in [
+            (
+                "sha256",
+                document.sha256.as_ref().map(|sha256| &sha256.expected),
+            ),
+            (
+                "sha512",
+                document.sha512.as_ref().map(|sha512| &sha512.expected),
+            ),
+            ("asc", document.signature.as_ref()),
+        ] {
+            let sidecar = self.sidecar(extension);
+            match content {
+                Some(content) => sidecar.write(content.clone().into_bytes()).await?,
+                None => sidecar.delete().await?,
+            }
+        }
+
+        let mut metadata = HashMap::new();
+        if let Some(etag) = &document.metadata.etag {
+            metadata.insert(META_ETAG.to_string(), etag.clone());
+        }
+        if !document.no_timestamps {
+            let mtime = document
+                .metadata
+                .last_modification
+                .unwrap_or_else(|| document.changed.into());
+            metadata.insert(META_LAST_MODIFIED.to_string(), format_timestamp(mtime)?);
+        }
+        for (name, value) in document.attributes {
+            // object metadata is limited to ASCII
+            let value = percent_encoding::utf8_percent_encode(value, ATTRIBUTE_ENCODE_SET);
+            metadata.insert(name.to_string(), value.to_string());
+        }
+
+        let body = match document.data {
+            RetrievedData::Memory(data) => ByteStream::from(data.clone()),
+            RetrievedData::File(file) => ByteStream::from_path(file.path())
+                .await
+                .with_context(|| format!("Failed to read spooled file for: {self}"))
+                .map_err(StoreError::S3)?,
+        };
+
+        self.client()
+            .await?
+            .put_object()
+            .bucket(self.bucket())
+            .key(self.key())
+            .set_metadata(Some(metadata))
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to store object: {self}"))
+            .map_err(StoreError::S3)?;
+
+        Ok(())
+    }
+
+    /// Store an object.
+    pub async fn write(&self, data: impl Into<Bytes>) -> Result<(), StoreError> {
+        self.client()
+            .await?
+            .put_object()
+            .bucket(self.bucket())
+            .key(self.key())
+            .body(ByteStream::from(data.into()))
+            .send()
+            .await
+            .with_context(|| format!("Failed to store object: {self}"))
+            .map_err(StoreError::S3)?;
+
+        Ok(())
+    }
+
+    /// Delete an object, if it exists.
+    pub async fn delete(&self) -> Result<(), StoreError> {
+        self.client()
+            .await?
+            .delete_object()
+            .bucket(self.bucket())
+            .key(self.key())
+            .send()
+            .await
+            .with_context(|| format!("Failed to delete object: {self}"))
+            .map_err(StoreError::S3)?;
+
+        Ok(())
+    }
+
+    /// Set the modification timestamp of a stored document.
+    ///
+    /// As object metadata cannot be modified, the object gets copied onto itself.
+    pub async fn set_document_timestamp(&self, mtime: SystemTime) -> Result<(), StoreError> {
+        let client = self.client().await?;
+
+        let head = client
+            .head_object()
+            .bucket(self.bucket())
+            .key(self.key())
+            .send()
+            .await
+            .with_context(|| format!("Failed to get object metadata: {self}"))
+            .map_err(StoreError::S3)?;
+
+        let mut metadata = head.metadata().cloned().unwrap_or_default();
+        metadata.insert(
+            META_LAST_MODIFIED.to_string(),
+            format_timestamp(mtime.into())?,
+        );
+
+        client
+            .copy_object()
+            .bucket(self.bucket())
+            .key(self.key())
+            .copy_source(self.url().path().trim_start_matches('/'))
+            .metadata_directive(MetadataDirective::Replace)
+            .set_metadata(Some(metadata))
+            .send()
+            .await
+            .with_context(|| format!("Failed to update object metadata: {self}"))
+            .map_err(StoreError::S3)?;
+
+        Ok(())
+    }
+
+    /// Read the retrieval metadata of a stored document.
+    ///
+    /// If the object doesn't carry a modification timestamp, the time it was stored is used. If
+    /// the object doesn't exist, [`None`] is returned.
+    pub async fn stored_metadata(&self) -> Result<Option<RetrievalMetadata>, StoreError> {
+        Ok(self.head().await?.map(|head| head.metadata))
+    }
+
+    /// Get the metadata of an object, if it exists.
+    pub async fn head(&self) -> Result<Option<ObjectHead>, StoreError> {
+        let head = match self
+            .client()
+            .await?
+            .head_object()
+            .bucket(self.bucket())
+            .key(self.key())
+            .send()
+            .await
+        {
+            Ok(head) => head,
+            Err(err) if err.as_service_error().is_some_and(|err| err.is_not_found()) => {
+                return Ok(None);
+            }
+            Err(err) => {
+                return Err(StoreError::S3(
+                    anyhow::Error::from(err)
+                        .context(format!("Failed to get object metadata: {self}")),
+                ));
+            }
+        };
+
+        Ok(Some(ObjectHead {
+            metadata: retrieval_metadata(head.metadata(), head.last_modified()),
+            size: head
+                .content_length()
+                .and_then(|size| u64::try_from(size).ok()),
+            object_metadata: head.metadata().cloned().unwrap_or_default(),
+        }))
+    }
+
+    /// Read an object, along with the retrieval metadata, if it exists.
+    pub async fn read(&self) -> Result<Option<(Bytes, RetrievalMetadata)>, StoreError> {
+        let object = match self
+            .client()
+            .await?
+            .get_object()
+            .bucket(self.bucket())
+            .key(self.key())
+            .send()
+            .await
+        {
+            Ok(object) => object,
+            Err(err)
+                if err
+                    .as_service_error()
+                    .is_some_and(|err| err.is_no_such_key()) =>
+            {
+                return Ok(None);
+            }
+            Err(err) => {
+                return Err(StoreError::S3(
+                    anyhow::Error::from(err).context(format!("Failed to get object: {self}")),
+                ));
+            }
+        };
+
+        let metadata = retrieval_metadata(object.metadata(), object.last_modified());
+        let data = object
+            .body
+            .collect()
+            .await
+            .with_context(|| format!("Failed to read object: {self}"))
+            .map_err(StoreError::S3)?
+            .into_bytes();
+
+        Ok(Some((data, metadata)))
+    }
+
+    /// Read an object, if it exists.
+    pub async fn read_optional(&self) -> Result<Option<Bytes>, StoreError> {
+        Ok(self.read().await?.map(|(data, _)| data))
+    }
+
+    /// Get the size of an object, if it exists.
+    pub async fn size(&self) -> Result<Option<u64>, StoreError> {
+        Ok(self.head().await?.and_then(|head| head.size))
+    }
+
+    /// List all objects below this key.
+    pub async fn list(&self) -> Result<Vec<StoredObject>, StoreError> {
+        let prefix = match self.key() {
+            "" => String::new(),
+            key => format!("{key}/"),
+        };
+
+        let mut pages = self
+            .client()
+            .await?
+            .list_objects_v2()
+            .bucket(self.bucket())
+            .prefix(prefix)
+            .into_paginator()
+            .send();
+
+        let mut result = vec![];
+        while let Some(page) = pages.next().await {
+            let page = page
+                .with_context(|| format!("Failed to list objects: {self}"))
+                .map_err(StoreError::S3)?;
+
+            for object in page.contents() {
+                let Some(key) = object.key() else {
+                    continue;
+                };
+
+                result.push(StoredObject {
+                    storage: self.with_key(key),
+                    modified: object
+                        .last_modified()
+                        .and_then(|modified| SystemTime::try_from(*modified).ok())
+                        .unwrap_or(SystemTime::UNIX_EPOCH),
+                    size: object.size().and_then(|size| u64::try_from(size).ok()),
+                });
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+fn format_timestamp(timestamp: OffsetDateTime) -> Result<String, StoreError> {
+    timestamp
+        .format(&Rfc3339)
+        .context("Failed to format timestamp")
+        .map_err(StoreError::S3)
+}
+
+fn retrieval_metadata(
+    metadata: Option<&HashMap<String, String>>,
+    stored: Option<&aws_sdk_s3::primitives::DateTime>,
+) -> RetrievalMetadata {
+    let last_modification = metadata
+        .and_then(|metadata| metadata.get(META_LAST_MODIFIED))
+        .and_then(|timestamp| OffsetDateTime::parse(timestamp, &Rfc3339).ok())
+        .or_else(|| {
+            stored
+                .and_then(|stored| SystemTime::try_from(*stored).ok())
+                .map(OffsetDateTime::from)
+        });
+
+    RetrievalMetadata {
+        last_modification,
+        etag: metadata.and_then(|metadata| metadata.get(META_ETAG).cloned()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        let storage = S3Storage::try_from("s3://foo:bar@us-east-1/b1/mirror/").expect("must parse");
+        assert_eq!(storage.key(), "mirror");
+
+        let document = storage
+            .join("https%3A%2F%2Fexample%2Ecom%2F")
+            .join("2023/a.json");
+        assert_eq!(
+            document.key(),
+            "mirror/https%3A%2F%2Fexample%2Ecom%2F/2023/a.json"
+        );
+
+        // no credentials, and the key can be recovered
+        let url = document.url();
+        assert_eq!(
+            url.as_str(),
+            "s3://us-east-1/b1/mirror/https%253A%252F%252Fexample%252Ecom%252F/2023/a.json"
+        );
+        assert_eq!(
+            storage.from_url(&url).map(|key| key.key().to_string()),
+            Some(document.key().to_string())
+        );
+
+        let other = S3Storage::try_from("s3://us-east-1/b2").expect("must parse");
+        assert_eq!(other.key(), "");
+        assert!(storage.from_url(&other.url()).is_none());
+    }
+}
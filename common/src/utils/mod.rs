@@ -1,6 +1,7 @@
 //! Common utilities
 pub mod hex;
 pub mod measure;
+pub mod stream;
 pub mod url;
 
 pub(crate) mod pem;
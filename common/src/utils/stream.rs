@@ -0,0 +1,47 @@
+//! Stream utilities
+
+use futures_util::{FutureExt, Stream, StreamExt, stream};
+use std::future::{Future, ready};
+use tokio::sync::mpsc;
+
+/// Turn a walk, reporting its outcomes through a channel, into a stream.
+///
+/// The walk is only driven while the stream is being polled, and waits for the stream to be
+/// polled if the channel is full. If the walk fails, its error is yielded as an item. The stream
+/// ends once the walk is complete and all of its outcomes were yielded.
+pub fn walk_stream<T, E, F, Fut>(buffer: usize, f: F) -> impl Stream<Item = Result<T, E>>
+where
+    F: FnOnce(mpsc::Sender<T>) -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+{
+    let (tx, rx) = mpsc::channel(buffer.max(1));
+
+    let outcomes = stream::unfold(rx, async |mut rx| {
+        rx.recv().await.map(|item| (Ok(item), rx))
+    });
+    let walk = f(tx)
+        .into_stream()
+        .filter_map(|result| ready(result.err().map(Err)));
+
+    stream::select(outcomes, walk)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn outcomes_and_error() {
+        let items: Vec<Result<usize, &str>> = walk_stream(1, async |tx| {
+            for i in 0..3 {
+                tx.send(i).await.map_err(|_| "closed")?;
+            }
+            Err("failed")
+        })
+        .collect()
+        .await;
+
+        assert_eq!(items.iter().filter(|item| item.is_ok()).count(), 3);
+        assert_eq!(items.iter().filter(|item| item.is_err()).count(), 1);
+    }
+}
@@ -1,4 +1,5 @@
 //! OpenPGP validation
+use crate::retrieve::RetrievedData;
 use crate::utils::openpgp::PublicKey;
 use crate::validate::ValidationOptions;
 use anyhow::bail;
@@ -8,7 +9,10 @@ use sequoia_openpgp::{
     packet::{Signature, key::PublicParts},
     parse::{
         Parse,
-        stream::{DetachedVerifierBuilder, MessageLayer, MessageStructure, VerificationHelper},
+        stream::{
+            DetachedVerifier, DetachedVerifierBuilder, MessageLayer, MessageStructure,
+            VerificationHelper,
+        },
     },
     policy::{HashAlgoSecurity, Policy, StandardPolicy},
     types::{AEADAlgorithm, SymmetricAlgorithm},
@@ -91,6 +95,35 @@ pub fn validate_signature(
     signature: &str,
     data: impl AsRef<[u8]>,
 ) -> Result<(), anyhow::Error> {
+    verify(options, keys, signature, |verifier| {
+        verifier.verify_bytes(data)
+    })
+}
+
+/// Validate the signature of retrieved data, reading spooled data from its file.
+pub fn validate_signature_data(
+    options: &ValidationOptions,
+    keys: &[PublicKey],
+    signature: &str,
+    data: &RetrievedData,
+) -> Result<(), anyhow::Error> {
+    match data {
+        RetrievedData::Memory(data) => validate_signature(options, keys, signature, data),
+        RetrievedData::File(file) => verify(options, keys, signature, |verifier| {
+            verifier.verify_file(file.path())
+        }),
+    }
+}
+
+fn verify<F>(
+    options: &ValidationOptions,
+    keys: &[PublicKey],
+    signature: &str,
+    f: F,
+) -> Result<(), anyhow::Error>
+where
+    F: FnOnce(&mut DetachedVerifier<'_, Helper<'_>>) -> sequoia_openpgp::Result<()>,
+{
     // TODO: we could move this into the context and re-use
     let policy = match options.validation_date {
         Some(time) => StandardPolicy::at(time),
@@ -103,7 +136,7 @@ pub fn validate_signature(
         Helper { keys },
     )?;
 
-    verifier.verify_bytes(data)?;
+    f(&mut verifier)?;
 
     Ok(())
 }
@@ -0,0 +1,113 @@
+use bytes::Bytes;
+use std::time::{Duration, SystemTime};
+use time::OffsetDateTime;
+use walker_common::{
+    retrieve::{RetrievalMetadata, RetrievedData},
+    store::{Document, S3Storage},
+};
+
+/// Test storing, listing and reading documents using an S3 compatible service.
+///
+/// This requires a bucket, provided using `WALKER_TEST_S3` (like
+/// `s3://minioadmin:minioadmin@us-east-1/test`), and `AWS_ENDPOINT_URL` pointing to the service
+/// (like a local MinIO instance). The test only uses keys below a prefix unique to the test run.
+#[ignore]
+#[tokio::test]
+async fn store_and_read() {
+    let bucket = std::env::var("WALKER_TEST_S3").expect("requires WALKER_TEST_S3");
+    let base = S3Storage::try_from(bucket.as_str())
+        .expect("must parse")
+        .join(&format!("walker-test-{}", std::process::id()));
+    let document = base.join("2023/a.json");
+
+    let data = RetrievedData::from(Bytes::from_static(b"{}"));
+    let metadata = RetrievalMetadata {
+        last_modification: Some(
+            OffsetDateTime::from_unix_timestamp(1_672_531_200).expect("must be valid"),
+        ),
+        etag: Some("\"foo\"".to_string()),
+    };
+
+    document
+        .store_document(Document {
+            data: &data,
+            sha256: &None,
+            sha512: &None,
+            signature: &Some("signature".to_string()),
+            changed: SystemTime::UNIX_EPOCH,
+            metadata: &metadata,
+            attributes: &[("categories", "Product Ä".to_string())],
+            no_timestamps: false,
+            #[cfg(any(target_os = "linux", target_os = "macos"))]
+            no_xattrs: false,
+        })
+        .await
+        .expect("must store");
+
+    // read back
+
+    let (stored, stored_metadata) = document
+        .read()
+        .await
+        .expect("must read")
+        .expect("must exist");
+    assert_eq!(stored, Bytes::from_static(b"{}"));
+    assert_eq!(stored_metadata, metadata);
+    assert_eq!(
+        document
+            .sidecar("asc")
+            .read_optional()
+            .await
+            .expect("must read"),
+        Some(Bytes::from_static(b"signature"))
+    );
+    assert!(
+        document
+            .sidecar("sha256")
+            .read_optional()
+            .await
+            .expect("must read")
+            .is_none()
+    );
+
+    // list
+
+    let mut keys = base
+        .list()
+        .await
+        .expect("must list")
+        .into_iter()
+        .map(|object| object.storage.key().to_string())
+        .collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec![
+            format!("{}/2023/a.json", base.key()),
+            format!("{}/2023/a.json.asc", base.key()),
+        ]
+    );
+
+    // update the timestamp, keeping the ETag
+
+    let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    document
+        .set_document_timestamp(modified)
+        .await
+        .expect("must update");
+    let head = document
+        .head()
+        .await
+        .expect("must get metadata")
+        .expect("must exist");
+    assert_eq!(head.modified(), modified);
+    assert_eq!(head.metadata.etag, metadata.etag);
+    assert_eq!(head.size, Some(2));
+    assert_eq!(head.attribute("categories").as_deref(), Some("Product Ä"));
+
+    // clean up
+
+    document.sidecar("asc").delete().await.expect("must delete");
+    document.delete().await.expect("must delete");
+    assert!(document.head().await.expect("must get metadata").is_none());
+}
@@ -0,0 +1,95 @@
+use csaf_walker::report::provider::{
+    Outcome, ProviderChecker, Requirement, render_provider_to_html,
+};
+use std::path::PathBuf;
+use walker_common::{
+    cli::{CommandDefaults, client::ClientArguments, validation::ValidationArguments},
+    validate::ValidationOptions,
+};
+
+/// Check a provider against the requirements of the CSAF standard.
+#[derive(clap::Args, Debug)]
+pub struct CheckProvider {
+    #[command(flatten)]
+    client: ClientArguments,
+
+    #[command(flatten)]
+    validation: ValidationArguments,
+
+    /// The provider to check, either a base domain, or the full URL to the provider metadata file
+    source: String,
+
+    /// Path of an HTML output file
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+impl CommandDefaults for CheckProvider {
+    fn progress(&self) -> bool {
+        false
+    }
+}
+
+impl CheckProvider {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let options: ValidationOptions = self.validation.into();
+        let fetcher = self.client.new_fetcher().await?;
+
+        let report = ProviderChecker::new(fetcher)
+            .with_options(options)
+            .check(&self.source)
+            .await?;
+
+        for requirement in Requirement::ALL {
+            let outcome = report.outcome(requirement);
+            let required = match requirement.is_required(report.role) {
+                true => " (required)",
+                false => "",
+            };
+            println!(
+                "{outcome} {section:<7} {requirement}{required}",
+                section = requirement.section()
+            );
+
+            if outcome == Outcome::Fail {
+                for message in report
+                    .requirements
+                    .get(&requirement)
+                    .iter()
+                    .flat_map(|state| &state.failures)
+                {
+                    println!("     - {message}");
+                }
+            }
+        }
+
+        if !report.document_errors.is_empty() {
+            println!();
+            println!("Documents which could not be retrieved:");
+            for message in &report.document_errors {
+                println!("     - {message}");
+            }
+        }
+
+        println!();
+        println!("Role: {:?}", report.role);
+        println!(
+            "Conforms: {}",
+            match report.conforms() {
+                true => "yes",
+                false => "no",
+            }
+        );
+
+        if let Some(output) = &self.output {
+            let mut out = std::fs::File::create(output)?;
+            render_provider_to_html(&mut out, &report)?;
+        }
+
+        if !report.conforms() {
+            anyhow::bail!("Provider does not conform to the requirements of its role");
+        }
+
+        Ok(())
+    }
+}
@@ -2,7 +2,11 @@ use crate::{
     cmd::{DiscoverArguments, FilterArguments},
     common::filter,
 };
-use csaf_walker::{discover::DiscoveredAdvisory, source::new_source, walker::Walker};
+use csaf_walker::{
+    discover::{DiscoverConfig, DiscoveredAdvisory},
+    source::new_source,
+    walker::Walker,
+};
 use std::convert::Infallible;
 use walker_common::{
     cli::{CommandDefaults, client::ClientArguments},
@@ -29,8 +33,8 @@ impl CommandDefaults for Discover {
 }
 
 impl Discover {
-    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
-        Walker::new(new_source(self.discover, self.client).await?)
+    pub async fn run<P: Progress + Clone + Send + Sync>(self, progress: P) -> anyhow::Result<()> {
+        Walker::new(new_source(DiscoverConfig::try_from(self.discover)?, self.client).await?)
             .with_progress(progress.clone())
             .walk(filter(
                 self.filter,
@@ -1,16 +1,20 @@
 use crate::{
-    cmd::{DiscoverArguments, FilterArguments, SkipArguments, StoreArguments},
-    common::walk_visitor,
+    cmd::{
+        DiscoverArguments, FilterArguments, PlanArguments, PruneArguments, SkipArguments,
+        StoreArguments,
+    },
+    common::{WalkOptions, walk_visitor},
 };
 use csaf_walker::{
-    discover::DiscoverConfig,
     retrieve::RetrievingVisitor,
-    visitors::{skip::SkipExistingVisitor, store::StoreVisitor},
+    visitors::{
+        checkpoint::CheckpointVisitor, plan::PlanningVisitor, skip::SkipExistingVisitor,
+        store::StoreVisitor,
+    },
 };
 use walker_common::{
     cli::{CommandDefaults, client::ClientArguments, runner::RunnerArguments},
     progress::Progress,
-    since::Since,
 };
 
 /// Like sync, but doesn't validate.
@@ -33,44 +37,59 @@ pub struct Download {
 
     #[command(flatten)]
     store: StoreArguments,
+
+    #[command(flatten)]
+    prune: PruneArguments,
+
+    #[command(flatten)]
+    plan: PlanArguments,
 }
 
 impl CommandDefaults for Download {}
 
 impl Download {
-    pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
         let store: StoreVisitor = self.store.try_into()?;
         let base = store.base.clone();
 
-        let since = Since::new(
-            self.skip.since,
-            self.skip.since_file,
-            self.skip
-                .since_file_offset
-                .map(|d| d.into())
-                .unwrap_or_default(),
-        )?;
-
         walk_visitor(
             progress,
             self.client,
-            DiscoverConfig::from(self.discover).with_since(since.since),
+            self.discover,
             self.filter,
             self.runner,
-            async |source| {
-                let visitor = RetrievingVisitor::new(source.clone(), store);
+            WalkOptions {
+                skip: Some(&self.skip),
+                prune: self.prune.pruner(&base)?,
+                plan: Some(&self.plan),
+            },
+            async |provider, source| {
+                let mut store = store.clone();
+                store.base = provider.create_dir(&base)?;
+                let output = store.base.clone();
+
+                // when planning, retrieval is replaced
+                let visitor = PlanningVisitor {
+                    visitor: RetrievingVisitor::new(
+                        source.clone(),
+                        CheckpointVisitor {
+                            visitor: store,
+                            checkpoint: provider.checkpoint.clone(),
+                        },
+                    ),
+                    source,
+                    plan: provider.plan.clone(),
+                };
 
                 Ok(SkipExistingVisitor {
                     visitor,
-                    output: base,
-                    since: since.since,
+                    output,
+                    since: provider.since,
                 })
             },
         )
         .await?;
 
-        since.store()?;
-
         Ok(())
     }
 }
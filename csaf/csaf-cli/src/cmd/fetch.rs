@@ -1,11 +1,13 @@
 use crate::{
     cmd::{DiscoverArguments, FilterArguments, SkipArguments},
-    common::walk_visitor,
+    common::{WalkOptions, walk_visitor},
 };
 use colored_json::write_colored_json;
 use csaf_walker::{
-    discover::DiscoverConfig, retrieve::RetrievingVisitor, validation::ValidatedAdvisory,
-    validation::ValidationVisitor,
+    retrieve::RetrievingVisitor,
+    source::DispatchSource,
+    validation::{ValidatedAdvisory, ValidationError, ValidationVisitor},
+    visitors::checkpoint::CheckpointVisitor,
 };
 use jsonpath_rust::JsonPath;
 use serde_json::Value;
@@ -16,7 +18,6 @@ use walker_common::{
         validation::ValidationArguments,
     },
     progress::Progress,
-    since::Since,
     validate::ValidationOptions,
 };
 
@@ -69,8 +70,8 @@ fn write(pretty: bool, value: &Value) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn show(output: &str, pretty: bool, doc: ValidatedAdvisory) -> anyhow::Result<()> {
-    let doc: Value = serde_json::from_slice(&doc.data)?;
+async fn show(output: &str, pretty: bool, doc: ValidatedAdvisory) -> anyhow::Result<()> {
+    let doc: Value = serde_json::from_slice(&doc.data.bytes().await?)?;
 
     if output == "json" {
         write(pretty, &doc)?;
@@ -85,39 +86,40 @@ fn show(output: &str, pretty: bool, doc: ValidatedAdvisory) -> anyhow::Result<()
 }
 
 impl Fetch {
-    pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
 
-        let since = Since::new(
-            self.skip.since,
-            self.skip.since_file,
-            self.skip
-                .since_file_offset
-                .map(|d| d.into())
-                .unwrap_or_default(),
-        )?;
+        let (output, pretty) = (self.output, self.pretty);
+        let show = move |doc: Result<ValidatedAdvisory, ValidationError<DispatchSource>>| {
+            let output = output.clone();
+            async move {
+                show(&output, pretty, doc?).await?;
 
-        let show = async |doc| {
-            show(&self.output, self.pretty, doc?)?;
-
-            Ok::<_, anyhow::Error>(())
+                Ok::<_, anyhow::Error>(())
+            }
         };
 
         walk_visitor(
             progress,
             self.client,
-            DiscoverConfig::from(self.discover).with_since(since.since),
+            self.discover,
             self.filter,
             self.runner,
-            async |source| {
-                let validation = ValidationVisitor::new(show).with_options(options);
+            WalkOptions {
+                skip: Some(&self.skip),
+                ..Default::default()
+            },
+            async |provider, source| {
+                let validation = ValidationVisitor::new(CheckpointVisitor {
+                    visitor: show.clone(),
+                    checkpoint: provider.checkpoint.clone(),
+                })
+                .with_options(options.clone());
                 Ok(RetrievingVisitor::new(source.clone(), validation))
             },
         )
         .await?;
 
-        since.store()?;
-
         Ok(())
     }
 }
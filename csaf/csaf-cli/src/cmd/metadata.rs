@@ -66,13 +66,19 @@ impl Metadata {
             DiscoverConfig {
                 since: None,
                 source: self.source,
+                metadata_verification: Default::default(),
+                retrieval: Default::default(),
+                feed_page_limit: None,
+                directory_index: Default::default(),
+                mirrors: Default::default(),
             },
             self.client,
         )
         .await?;
 
-        let metadata = source.load_metadata().await?;
-        Self::show_metadata(&metadata)?;
+        for metadata in source.load_providers().await? {
+            Self::show_metadata(&metadata)?;
+        }
 
         Ok(())
     }
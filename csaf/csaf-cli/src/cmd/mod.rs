@@ -1,8 +1,20 @@
-use anyhow::Context;
-use csaf_walker::visitors::{filter::FilterConfig, store::StoreVisitor};
+use anyhow::{Context, bail};
+use csaf_walker::{
+    discover::Deduplication,
+    metadata::{MetadataSignatureMode, MirrorPolicy},
+    model::metadata::TlpLabel,
+    visitors::{
+        filter::FilterConfig,
+        prune::{PruneMode, Pruner},
+        store::StoreVisitor,
+    },
+    walker::ErrorPolicy,
+};
 use flexible_time::timestamp::StartTimestamp;
 use std::path::PathBuf;
+use walker_common::{cli::retrieval::RetrievalArguments, index::DirectoryIndex, store::Storage};
 
+pub mod check_provider;
 pub mod discover;
 pub mod download;
 pub mod fetch;
@@ -17,10 +29,149 @@ pub mod sync;
 #[derive(Debug, clap::Parser)]
 #[command(next_help_heading = "Discovery")]
 pub struct DiscoverArguments {
-    /// Source to scan from.
+    /// Sources to scan from.
     ///
-    /// CSAF trusted provider base domain (e.g. `redhat.com`), the full URL to the provider metadata file, or a local `file:` source.
-    pub source: String,
+    /// CSAF trusted provider base domain (e.g. `redhat.com`), the full URL to the provider metadata file, the full URL to
+    /// an `aggregator.json` file, or a local `file:` source. Using `@<file>`, sources will be read from a file, one per
+    /// line, ignoring empty lines and lines starting with `#`. When walking more than one source, each provider will use
+    /// its own subdirectory for output and state files.
+    #[arg(required = true)]
+    pub source: Vec<String>,
+
+    /// How to handle the OpenPGP signature of the provider metadata
+    #[arg(long, value_enum, default_value_t = MetadataSignature::Off)]
+    pub metadata_signature: MetadataSignature,
+
+    /// Pinned OpenPGP keys, used to verify the signature of the provider metadata
+    #[arg(long)]
+    pub metadata_key: Vec<PathBuf>,
+
+    /// Trust the keys stored by a previous run in this directory to verify the signature of the
+    /// provider metadata
+    #[arg(long)]
+    pub metadata_keys_from: Option<PathBuf>,
+
+    /// How to handle a distribution failing to load its index
+    #[arg(long, value_enum, default_value_t = OnDistributionError::Fail)]
+    pub on_distribution_error: OnDistributionError,
+
+    /// Remove advisories discovered more than once (e.g. through a directory and a ROLIE feed) before retrieving them,
+    /// keeping the one with the best metadata
+    #[arg(long, value_enum, default_value_t = Deduplicate::None)]
+    pub deduplicate: Deduplicate,
+
+    /// The maximum number of pages to retrieve for a single ROLIE feed, defaults to 1000. A feed exceeding the limit
+    /// fails to load.
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub feed_page_limit: Option<usize>,
+
+    /// Which index files of a directory distribution to use for discovering advisories
+    #[arg(long, value_enum, default_value_t = IndexFiles::Fallback)]
+    pub directory_index: IndexFiles,
+
+    /// How to handle the mirrors of the providers listed by an aggregator
+    #[arg(long, value_enum, default_value_t = Mirrors::Fallback)]
+    pub mirrors: Mirrors,
+
+    #[command(flatten)]
+    pub retrieval: RetrievalArguments,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum MetadataSignature {
+    /// don't check the signature
+    Off,
+    /// warn if the signature can't be verified
+    Warn,
+    /// fail if the signature can't be verified
+    Require,
+}
+
+impl From<MetadataSignature> for MetadataSignatureMode {
+    fn from(value: MetadataSignature) -> Self {
+        match value {
+            MetadataSignature::Off => Self::Off,
+            MetadataSignature::Warn => Self::Warn,
+            MetadataSignature::Require => Self::Require,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OnDistributionError {
+    /// abort processing the provider
+    Fail,
+    /// skip the distribution, and continue with the next one
+    Continue,
+}
+
+impl From<OnDistributionError> for ErrorPolicy {
+    fn from(value: OnDistributionError) -> Self {
+        match value {
+            OnDistributionError::Fail => Self::FailFast,
+            OnDistributionError::Continue => Self::Continue,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum IndexFiles {
+    /// only use `changes.csv`, fail if it is missing
+    Changes,
+    /// use `changes.csv`, falling back to `index.txt` if it is missing
+    Fallback,
+    /// use the entries of both `changes.csv` and `index.txt`, warning about differences
+    Union,
+}
+
+impl From<IndexFiles> for DirectoryIndex {
+    fn from(value: IndexFiles) -> Self {
+        match value {
+            IndexFiles::Changes => Self::Changes,
+            IndexFiles::Fallback => Self::Fallback,
+            IndexFiles::Union => Self::Union,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Mirrors {
+    /// only use the provider's own metadata
+    Ignore,
+    /// use the provider's own metadata, falling back to its mirrors if that fails
+    Fallback,
+    /// use the provider's own metadata and all of its mirrors
+    All,
+}
+
+impl From<Mirrors> for MirrorPolicy {
+    fn from(value: Mirrors) -> Self {
+        match value {
+            Mirrors::Ignore => Self::Ignore,
+            Mirrors::Fallback => Self::Fallback,
+            Mirrors::All => Self::All,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Deduplicate {
+    /// keep all advisories
+    None,
+    /// advisories with the same URL
+    Url,
+    /// advisories with the same filename
+    Filename,
+}
+
+impl From<Deduplicate> for Deduplication {
+    fn from(value: Deduplicate) -> Self {
+        match value {
+            Deduplicate::None => Self::None,
+            Deduplicate::Url => Self::Url,
+            Deduplicate::Filename => Self::Filename,
+        }
+    }
 }
 
 #[derive(Debug, clap::Parser)]
@@ -37,6 +188,15 @@ pub struct FilterArguments {
     #[arg(long)]
     /// Ignore all non-matching prefixes
     pub only_prefix: Vec<String>,
+
+    #[arg(long)]
+    /// Ignore all advisories without a matching ROLIE category term
+    pub only_category: Vec<String>,
+
+    #[arg(long, value_delimiter = ',')]
+    /// Only process distributions with one of the TLP labels (e.g. `WHITE,GREEN`). Directory
+    /// distributions don't carry a label and are considered `UNLABELED`.
+    pub tlp: Vec<TlpLabel>,
 }
 
 impl From<FilterArguments> for FilterConfig {
@@ -45,6 +205,8 @@ impl From<FilterArguments> for FilterConfig {
             .ignored_distributions(filter.ignore_distribution)
             .ignored_prefixes(filter.ignore_prefix)
             .only_prefixes(filter.only_prefix)
+            .only_categories(filter.only_category)
+            .only_tlp_labels(filter.tlp)
     }
 }
 
@@ -60,9 +222,9 @@ pub struct StoreArguments {
     #[arg(long)]
     pub no_timestamps: bool,
 
-    /// Output path, defaults to the local directory.
+    /// Output path, defaults to the local directory. Using `s3://[<key id>:<access key>@]<region>/<bucket>[/<prefix>]`, documents are stored in an S3 compatible bucket.
     #[arg(short, long)]
-    pub data: Option<PathBuf>,
+    pub data: Option<Storage>,
 }
 
 impl TryFrom<StoreArguments> for StoreVisitor {
@@ -71,7 +233,9 @@ impl TryFrom<StoreArguments> for StoreVisitor {
     fn try_from(value: StoreArguments) -> Result<Self, Self::Error> {
         let base = match value.data {
             Some(base) => base,
-            None => std::env::current_dir().context("Get current working directory")?,
+            None => std::env::current_dir()
+                .context("Get current working directory")?
+                .into(),
         };
 
         let result = Self::new(base).no_timestamps(value.no_timestamps);
@@ -97,6 +261,64 @@ pub struct SkipArguments {
     /// A delta to add to the value loaded from the since-state file.
     #[arg(long)]
     pub since_file_offset: Option<humantime::Duration>,
+
+    /// A file to record completed documents in, allowing to resume an interrupted run. It will be removed at the end of a successful run.
+    #[arg(long)]
+    pub checkpoint: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Parser)]
+#[command(next_help_heading = "Pruning")]
+pub struct PruneArguments {
+    /// Remove stored documents (and their signature and digest files), which are no longer listed by their distribution.
+    ///
+    /// This conflicts with `--deduplicate`, as removed duplicates would be considered no longer listed.
+    #[arg(long, conflicts_with = "deduplicate")]
+    pub prune: bool,
+
+    /// Move pruned documents into this directory, instead of removing them.
+    #[arg(long, requires = "prune")]
+    pub prune_quarantine: Option<PathBuf>,
+
+    /// Only report the documents which would be pruned.
+    #[arg(long, requires = "prune")]
+    pub prune_dry_run: bool,
+}
+
+impl PruneArguments {
+    /// Create a pruner for the stored documents, if pruning is enabled.
+    ///
+    /// Pruning is only supported for documents stored in a directory.
+    pub fn pruner(&self, base: &Storage) -> anyhow::Result<Option<Pruner>> {
+        if !self.prune {
+            return Ok(None);
+        }
+
+        let Some(base) = base.as_path() else {
+            bail!("Pruning is only supported when storing documents in a directory");
+        };
+
+        Ok(Some(
+            Pruner::new(base)
+                .mode(match &self.prune_quarantine {
+                    Some(quarantine) => PruneMode::Quarantine(quarantine.clone()),
+                    None => PruneMode::Remove,
+                })
+                .dry_run(self.prune_dry_run),
+        ))
+    }
+}
+
+#[derive(Debug, clap::Parser)]
+#[command(next_help_heading = "Planning")]
+pub struct PlanArguments {
+    /// Only discover documents, and report which would be fetched or skipped (and why), without retrieving or storing anything. The size of documents is requested using HEAD requests.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Write the plan of the dry run as JSON into this file, instead of printing it.
+    #[arg(long, requires = "dry_run")]
+    pub dry_run_output: Option<PathBuf>,
 }
 
 #[derive(Debug, clap::Parser)]
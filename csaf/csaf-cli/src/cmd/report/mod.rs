@@ -4,7 +4,10 @@ use crate::{
 };
 use csaf_walker::{
     discover::AsDiscovered,
-    report::{DocumentKey, Duplicates, ReportRenderOption, ReportResult, render_to_html},
+    report::{
+        DocumentKey, Duplicates, ReportRenderOption, ReportResult, render_providers_to_html,
+        render_to_html,
+    },
     retrieve::RetrievingVisitor,
     source::DispatchSource,
     validation::{ValidatedAdvisory, ValidationError, ValidationVisitor},
@@ -13,6 +16,7 @@ use csaf_walker::{
         check::{CheckError, init_verifying_visitor},
     },
     visitors::duplicates::DetectDuplicatesVisitor,
+    walker::DistributionFailures,
 };
 use reqwest::Url;
 use std::{
@@ -82,144 +86,200 @@ pub struct RenderOptions {
     statistics_file: Option<PathBuf>,
 }
 
+/// The state of a report for a single provider
+#[derive(Default)]
+struct ProviderReport {
+    name: Option<String>,
+    total: Arc<AtomicUsize>,
+    duplicates: Arc<Mutex<Duplicates>>,
+    failures: DistributionFailures,
+    errors: Arc<Mutex<BTreeMap<DocumentKey, String>>>,
+    warnings: Arc<Mutex<BTreeMap<DocumentKey, Vec<CheckError>>>>,
+}
+
 impl Report {
-    pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
-
-        let total = Arc::new(AtomicUsize::default());
-        let duplicates: Arc<Mutex<Duplicates>> = Default::default();
-        let errors: Arc<Mutex<BTreeMap<DocumentKey, String>>> = Default::default();
-        let warnings: Arc<Mutex<BTreeMap<DocumentKey, Vec<CheckError>>>> = Default::default();
-
-        {
-            let total = total.clone();
-            let duplicates = duplicates.clone();
-            let errors = errors.clone();
-            let warnings = warnings.clone();
-
-            let visitor = move |advisory: Result<
-                VerifiedAdvisory<ValidatedAdvisory, &'static str>,
-                VerificationError<ValidationError<DispatchSource>, ValidatedAdvisory>,
-            >| {
-                (*total).fetch_add(1, Ordering::Release);
-
-                let errors = errors.clone();
-                let warnings = warnings.clone();
-
-                async move {
-                    let adv = match advisory {
-                        Ok(adv) => adv,
-                        Err(err) => {
-                            let name = match err.as_discovered().relative_base_and_url() {
-                                Some((base, relative)) => DocumentKey {
-                                    distribution_url: base.clone(),
-                                    url: relative,
-                                },
-                                None => DocumentKey {
-                                    distribution_url: err.url().clone(),
-                                    url: Default::default(),
-                                },
-                            };
-
-                            errors.lock().await.insert(name, err.to_string());
-                            return Ok::<_, anyhow::Error>(());
+        let reports: Mutex<Vec<ProviderReport>> = Default::default();
+
+        walk_visitor(
+            progress,
+            self.client,
+            self.discover,
+            self.filter,
+            self.runner,
+            Default::default(),
+            async |provider, source| {
+                let report = ProviderReport {
+                    name: provider.name.clone(),
+                    failures: provider.failures.clone(),
+                    ..Default::default()
+                };
+
+                let total = report.total.clone();
+                let duplicates = report.duplicates.clone();
+                let errors = report.errors.clone();
+                let warnings = report.warnings.clone();
+
+                reports.lock().await.push(report);
+
+                let visitor = move |advisory: Result<
+                    VerifiedAdvisory<ValidatedAdvisory, &'static str>,
+                    VerificationError<ValidationError<DispatchSource>, ValidatedAdvisory>,
+                >| {
+                    (*total).fetch_add(1, Ordering::Release);
+
+                    let errors = errors.clone();
+                    let warnings = warnings.clone();
+
+                    async move {
+                        let adv = match advisory {
+                            Ok(adv) => adv,
+                            Err(err) => {
+                                let name = match err.as_discovered().relative_base_and_url() {
+                                    Some((base, relative)) => DocumentKey {
+                                        distribution_url: base.clone(),
+                                        url: relative,
+                                    },
+                                    None => DocumentKey {
+                                        distribution_url: err.url().clone(),
+                                        url: Default::default(),
+                                    },
+                                };
+
+                                errors.lock().await.insert(name, err.to_string());
+                                return Ok::<_, anyhow::Error>(());
+                            }
+                        };
+
+                        if !adv.failures.is_empty() {
+                            let name = DocumentKey::for_document(&adv);
+                            warnings
+                                .lock()
+                                .await
+                                .entry(name)
+                                .or_default()
+                                .extend(adv.failures.into_values().flatten());
                         }
-                    };
-
-                    if !adv.failures.is_empty() {
-                        let name = DocumentKey::for_document(&adv);
-                        warnings
-                            .lock()
-                            .await
-                            .entry(name)
-                            .or_default()
-                            .extend(adv.failures.into_values().flatten());
+
+                        Ok::<_, anyhow::Error>(())
                     }
+                };
+
+                // content checks
+
+                let visitor = VerifyingVisitor::with_checks(visitor, init_verifying_visitor());
+                #[cfg(feature = "csaf-validator-lib")]
+                let visitor = {
+                    if let Some(profile) = self.verification.profile.into() {
+                        let timeout = self.verification.timeout.map(|timeout| timeout.into());
+                        visitor.add(
+                            "csaf_validator_lib",
+                            csaf_walker::verification::check::csaf_validator_lib::CsafValidatorLib::new(
+                                profile,
+                            ).timeout(timeout).ignore(self.verification.skip.clone()),
+                        )
+                    } else {
+                        visitor
+                    }
+                };
+
+                // validation (can we work with this document?)
+
+                let visitor = ValidationVisitor::new(visitor).with_options(options.clone());
+                let visitor = RetrievingVisitor::new(source.clone(), visitor);
+
+                Ok(DetectDuplicatesVisitor {
+                    duplicates,
+                    visitor,
+                })
+            },
+        )
+        .await?;
+
+        let mut reports = reports.into_inner();
+        reports.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut results = Vec::with_capacity(reports.len());
+        let mut statistics = Statistics {
+            total: 0,
+            errors: 0,
+            total_errors: 0,
+            warnings: 0,
+            total_warnings: 0,
+        };
+
+        for report in &reports {
+            let total = report.total.load(Ordering::Acquire);
+            let duplicates = report.duplicates.lock().await;
+            let distribution_errors = report.failures.get();
+            let errors = report.errors.lock().await;
+            let warnings = report.warnings.lock().await;
+
+            statistics.total += total;
+            statistics.errors += errors.len();
+            statistics.total_errors += errors.len();
+            statistics.warnings += warnings.len();
+            statistics.total_warnings += warnings.values().map(|v| v.len()).sum::<usize>();
+
+            results.push((
+                report.name.as_deref(),
+                total,
+                duplicates,
+                distribution_errors,
+                errors,
+                warnings,
+            ));
+        }
 
-                    Ok::<_, anyhow::Error>(())
-                }
-            };
-
-            // content checks
-
-            let visitor = VerifyingVisitor::with_checks(visitor, init_verifying_visitor());
-            #[cfg(feature = "csaf-validator-lib")]
-            let visitor = {
-                if let Some(profile) = self.verification.profile.into() {
-                    let timeout = self.verification.timeout.map(|timeout| timeout.into());
-                    visitor.add(
-                        "csaf_validator_lib",
-                        csaf_walker::verification::check::csaf_validator_lib::CsafValidatorLib::new(
-                            profile,
-                        ).timeout(timeout).ignore(self.verification.skip),
+        let results: Vec<_> = results
+            .iter()
+            .map(
+                |(name, total, duplicates, distribution_errors, errors, warnings)| {
+                    (
+                        *name,
+                        ReportResult {
+                            total: *total,
+                            duplicates,
+                            distribution_errors,
+                            errors,
+                            warnings,
+                        },
                     )
-                } else {
-                    visitor
-                }
-            };
-
-            // validation (can we work with this document?)
-
-            let visitor = ValidationVisitor::new(visitor).with_options(options);
-
-            walk_visitor(
-                progress,
-                self.client,
-                self.discover,
-                self.filter,
-                self.runner,
-                async move |source| {
-                    let visitor = RetrievingVisitor::new(source.clone(), visitor);
-
-                    Ok(DetectDuplicatesVisitor {
-                        duplicates,
-                        visitor,
-                    })
                 },
             )
-            .await?;
-        }
+            .collect();
 
-        let total = (*total).load(Ordering::Acquire);
-        let errors = errors.lock().await;
-        let warnings = warnings.lock().await;
-
-        Self::render(
-            &self.render,
-            &ReportResult {
-                total,
-                duplicates: &*duplicates.lock().await,
-                errors: &errors,
-                warnings: &warnings,
-            },
-        )?;
+        Self::render(&self.render, &results)?;
 
-        report::record_now(
-            self.render.statistics_file.as_deref(),
-            Statistics {
-                total,
-                errors: errors.len(),
-                total_errors: errors.len(),
-                warnings: warnings.len(),
-                total_warnings: warnings.iter().map(|(_, v)| v.len()).sum(),
-            },
-        )?;
+        report::record_now(self.render.statistics_file.as_deref(), statistics)?;
 
         Ok(())
     }
 
-    fn render(render: &RenderOptions, report: &ReportResult) -> anyhow::Result<()> {
+    fn render(
+        render: &RenderOptions,
+        reports: &[(Option<&str>, ReportResult)],
+    ) -> anyhow::Result<()> {
         let mut out = std::fs::File::create(&render.output)?;
 
-        render_to_html(
-            &mut out,
-            report,
-            ReportRenderOption {
-                output: &render.output,
-                base_url: &render.base_url,
-                source_url: &render.source_url,
-            },
-        )?;
+        let options = ReportRenderOption {
+            output: &render.output,
+            base_url: &render.base_url,
+            source_url: &render.source_url,
+        };
+
+        match reports {
+            // a single source
+            [(None, report)] => render_to_html(&mut out, report, options)?,
+            reports => {
+                let reports: Vec<_> = reports
+                    .iter()
+                    .map(|(name, report)| (name.unwrap_or_default(), report.clone()))
+                    .collect();
+                render_providers_to_html(&mut out, &reports, options)?
+            }
+        }
 
         Ok(())
     }
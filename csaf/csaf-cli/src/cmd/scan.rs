@@ -37,7 +37,10 @@ pub struct Scan {
 impl CommandDefaults for Scan {}
 
 impl Scan {
-    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone + Send + Sync + 'static>(
+        self,
+        progress: P,
+    ) -> anyhow::Result<()> {
         walk_standard(
             progress.clone(),
             self.client,
@@ -45,33 +48,36 @@ impl Scan {
             self.discover,
             self.filter,
             self.validation,
-            async |advisory: Result<ValidatedAdvisory, ValidationError<DispatchSource>>| {
-                match advisory {
-                    Ok(adv) => {
-                        progress.println(&format!("Advisory: {}", adv.url));
-                        log::debug!("  Metadata: {:?}", adv.sha256);
-                        log::debug!("    SHA256: {:?}", adv.sha256);
-                        log::debug!("    SHA512: {:?}", adv.sha512);
-                        match serde_json::from_slice::<Csaf>(&adv.data) {
-                            Ok(csaf) => {
-                                progress.println(&format!(
-                                    "  {} ({}): {}",
-                                    csaf.document.tracking.id,
-                                    csaf.document.tracking.initial_release_date,
-                                    csaf.document.title
-                                ));
-                            }
-                            Err(err) => {
-                                eprintln!("  Format error: {err}");
+            move |advisory: Result<ValidatedAdvisory, ValidationError<DispatchSource>>| {
+                let progress = progress.clone();
+                async move {
+                    match advisory {
+                        Ok(adv) => {
+                            progress.println(&format!("Advisory: {}", adv.url));
+                            log::debug!("  Metadata: {:?}", adv.sha256);
+                            log::debug!("    SHA256: {:?}", adv.sha256);
+                            log::debug!("    SHA512: {:?}", adv.sha512);
+                            match serde_json::from_slice::<Csaf>(&adv.data.bytes().await?) {
+                                Ok(csaf) => {
+                                    progress.println(&format!(
+                                        "  {} ({}): {}",
+                                        csaf.document.tracking.id,
+                                        csaf.document.tracking.initial_release_date,
+                                        csaf.document.title
+                                    ));
+                                }
+                                Err(err) => {
+                                    eprintln!("  Format error: {err}");
+                                }
                             }
                         }
+                        Err(err) => {
+                            eprintln!("Advisory(ERR): {err}");
+                        }
                     }
-                    Err(err) => {
-                        eprintln!("Advisory(ERR): {err}");
-                    }
-                }
 
-                Ok::<_, anyhow::Error>(())
+                    Ok::<_, anyhow::Error>(())
+                }
             },
         )
         .await?;
@@ -1,10 +1,11 @@
 use crate::{
     cmd::{DiscoverArguments, FilterArguments, SkipArguments},
-    common::walk_visitor,
+    common::{WalkOptions, walk_visitor},
 };
 use csaf_walker::{
-    discover::DiscoverConfig, retrieve::RetrievingVisitor, validation::ValidationVisitor,
-    visitors::skip::SkipFailedVisitor,
+    retrieve::RetrievingVisitor,
+    validation::ValidationVisitor,
+    visitors::{checkpoint::CheckpointVisitor, skip::SkipFailedVisitor},
 };
 use walker_common::{
     cli::{
@@ -12,7 +13,6 @@ use walker_common::{
         validation::ValidationArguments,
     },
     progress::Progress,
-    since::Since,
     validate::ValidationOptions,
 };
 use walker_extras::visitors::{SendArguments, SendVisitor};
@@ -49,41 +49,38 @@ pub struct Send {
 impl CommandDefaults for Send {}
 
 impl Send {
-    pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
         let send: SendVisitor = self.send.into_visitor().await?;
 
-        let since = Since::new(
-            self.skip.since,
-            self.skip.since_file,
-            self.skip
-                .since_file_offset
-                .map(|d| d.into())
-                .unwrap_or_default(),
-        )?;
-
         walk_visitor(
             progress,
             self.client,
-            DiscoverConfig::from(self.discover).with_since(since.since),
+            self.discover,
             self.filter,
             self.runner,
-            async move |source| {
+            WalkOptions {
+                skip: Some(&self.skip),
+                ..Default::default()
+            },
+            async |provider, source| {
                 Ok({
                     RetrievingVisitor::new(source.clone(), {
+                        // skipped documents must not be recorded as completed
                         ValidationVisitor::new(SkipFailedVisitor {
                             skip_failures: self.skip_failures,
-                            visitor: send,
+                            visitor: CheckpointVisitor {
+                                visitor: send.clone(),
+                                checkpoint: provider.checkpoint.clone(),
+                            },
                         })
-                        .with_options(options)
+                        .with_options(options.clone())
                     })
                 })
             },
         )
         .await?;
 
-        since.store()?;
-
         Ok(())
     }
 }
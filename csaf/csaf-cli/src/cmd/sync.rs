@@ -1,12 +1,17 @@
 use crate::{
-    cmd::{DiscoverArguments, FilterArguments, SkipArguments, StoreArguments},
-    common::walk_visitor,
+    cmd::{
+        DiscoverArguments, FilterArguments, PlanArguments, PruneArguments, SkipArguments,
+        StoreArguments,
+    },
+    common::{WalkOptions, walk_visitor},
 };
 use csaf_walker::{
-    discover::DiscoverConfig,
     retrieve::RetrievingVisitor,
     validation::ValidationVisitor,
-    visitors::{skip::SkipExistingVisitor, store::StoreVisitor},
+    visitors::{
+        checkpoint::CheckpointVisitor, plan::PlanningVisitor, skip::SkipExistingVisitor,
+        store::StoreVisitor,
+    },
 };
 use walker_common::{
     cli::{
@@ -14,7 +19,6 @@ use walker_common::{
         validation::ValidationArguments,
     },
     progress::Progress,
-    since::Since,
     validate::ValidationOptions,
 };
 
@@ -41,50 +45,61 @@ pub struct Sync {
 
     #[command(flatten)]
     store: StoreArguments,
+
+    #[command(flatten)]
+    prune: PruneArguments,
+
+    #[command(flatten)]
+    plan: PlanArguments,
 }
 
 impl CommandDefaults for Sync {}
 
 impl Sync {
-    pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
         let store: StoreVisitor = self.store.try_into()?;
         let base = store.base.clone();
 
-        let since = Since::new(
-            self.skip.since,
-            self.skip.since_file,
-            self.skip
-                .since_file_offset
-                .map(|d| d.into())
-                .unwrap_or_default(),
-        )?;
-
         walk_visitor(
             progress,
             self.client,
-            DiscoverConfig::from(self.discover).with_since(since.since),
+            self.discover,
             self.filter,
             self.runner,
-            async move |source| {
-                let visitor = {
-                    RetrievingVisitor::new(
-                        source,
-                        ValidationVisitor::new(store).with_options(options),
-                    )
+            WalkOptions {
+                skip: Some(&self.skip),
+                prune: self.prune.pruner(&base)?,
+                plan: Some(&self.plan),
+            },
+            async |provider, source| {
+                let mut store = store.clone();
+                store.base = provider.create_dir(&base)?;
+                let output = store.base.clone();
+
+                // when planning, retrieval is replaced
+                let visitor = PlanningVisitor {
+                    visitor: RetrievingVisitor::new(
+                        source.clone(),
+                        ValidationVisitor::new(CheckpointVisitor {
+                            visitor: store,
+                            checkpoint: provider.checkpoint.clone(),
+                        })
+                        .with_options(options.clone()),
+                    ),
+                    source,
+                    plan: provider.plan.clone(),
                 };
 
                 Ok(SkipExistingVisitor {
                     visitor,
-                    output: base,
-                    since: since.since,
+                    output,
+                    since: provider.since,
                 })
             },
         )
         .await?;
 
-        since.store()?;
-
         Ok(())
     }
 }
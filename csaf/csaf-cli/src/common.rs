@@ -1,16 +1,38 @@
-use crate::cmd::DiscoverArguments;
+use crate::cmd::{Deduplicate, DiscoverArguments, PlanArguments, SkipArguments};
+use anyhow::{Context, bail};
 use csaf_walker::{
     discover::{DiscoverConfig, DiscoveredVisitor},
+    metadata::MetadataVerification,
+    model::metadata::ProviderMetadata,
     retrieve::RetrievingVisitor,
-    source::{DispatchSource, new_source},
+    source::{DispatchSource, HttpOptions, HttpSource, Source, SourceDescriptor},
     validation::{ValidatedVisitor, ValidationVisitor},
-    visitors::filter::{FilterConfig, FilteringVisitor},
-    walker::Walker,
+    visitors::{
+        checkpoint::CheckpointVisitor,
+        filter::{FilterConfig, FilteringVisitor},
+        limit::LimitingVisitor,
+        plan::{Plan, PlanReason, PlanSummary, PlannedAdvisory, PlanningStage},
+        prune::{Listing, ListingVisitor, Pruner},
+    },
+    walker::{DistributionFailures, Walker},
 };
-use std::future::Future;
+use futures::{StreamExt, stream};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::SystemTime,
+};
+use tokio::sync::Semaphore;
+use url::Url;
 use walker_common::{
+    checkpoint::Checkpoint,
     cli::{client::ClientArguments, runner::RunnerArguments, validation::ValidationArguments},
+    fetcher::Fetcher,
     progress::Progress,
+    since::Since,
+    store::Storage,
     validate::ValidationOptions,
 };
 
@@ -18,15 +40,16 @@ pub async fn walk_standard<V, P>(
     progress: P,
     client: ClientArguments,
     runner: RunnerArguments,
-    discover: impl Into<DiscoverConfig>,
+    discover: DiscoverArguments,
     filter: impl Into<FilterConfig>,
     validation: ValidationArguments,
     visitor: V,
 ) -> anyhow::Result<()>
 where
-    V: ValidatedVisitor<DispatchSource>,
+    V: ValidatedVisitor<DispatchSource> + Clone + 'static,
+    V::Context: 'static,
     V::Error: Send + Sync + 'static,
-    P: Progress,
+    P: Progress + Clone,
 {
     let options: ValidationOptions = validation.into();
 
@@ -36,23 +59,270 @@ where
         discover,
         filter,
         runner,
-        async move |source| {
+        Default::default(),
+        async |_, source| {
             Ok(RetrievingVisitor::new(
                 source.clone(),
-                ValidationVisitor::new(visitor).with_options(options),
+                ValidationVisitor::new(visitor.clone()).with_options(options.clone()),
             ))
         },
     )
     .await
 }
 
-impl From<DiscoverArguments> for DiscoverConfig {
-    fn from(value: DiscoverArguments) -> Self {
-        Self {
+impl TryFrom<DiscoverArguments> for DiscoverConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(value: DiscoverArguments) -> Result<Self, Self::Error> {
+        let mut sources = value.sources()?;
+        if sources.len() != 1 {
+            bail!("This command requires exactly one source");
+        }
+
+        Ok(value.config(&Provider {
+            source: sources.remove(0),
+            name: None,
             since: None,
-            source: value.source,
+            failures: Default::default(),
+            plan: None,
+            metadata: None,
+            checkpoint: None,
+        }))
+    }
+}
+
+impl DiscoverArguments {
+    /// Get all sources, reading the ones provided through a file.
+    pub fn sources(&self) -> anyhow::Result<Vec<String>> {
+        let mut sources = Vec::with_capacity(self.source.len());
+
+        for source in &self.source {
+            let Some(file) = source.strip_prefix('@') else {
+                sources.push(source.clone());
+                continue;
+            };
+
+            let content = std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read sources from file: {file}"))?;
+            sources.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(ToString::to_string),
+            );
+        }
+
+        if sources.is_empty() {
+            bail!("No source provided");
+        }
+
+        Ok(sources)
+    }
+
+    /// Create the discovery configuration for a provider.
+    pub fn config(&self, provider: &Provider) -> DiscoverConfig {
+        DiscoverConfig {
+            since: provider.since,
+            source: provider.source.clone(),
+            metadata_verification: MetadataVerification::new()
+                .mode(self.metadata_signature.into())
+                .keys(self.metadata_key.clone())
+                .previous(
+                    self.metadata_keys_from
+                        .as_ref()
+                        .map(|previous| provider.dir(previous)),
+                ),
+            retrieval: self.retrieval.clone().into(),
+            feed_page_limit: self.feed_page_limit,
+            directory_index: self.directory_index.into(),
+            mirrors: self.mirrors.into(),
         }
     }
+
+    /// Resolve the sources into the providers to walk.
+    ///
+    /// A single source is walked as it is. Otherwise, each source is resolved into the providers
+    /// it serves, named after the canonical URL of their metadata. Remote providers keep the
+    /// metadata loaded (and verified) here, so that it isn't loaded again for walking them.
+    pub async fn providers(&self, fetcher: &Fetcher) -> anyhow::Result<Vec<Provider>> {
+        let sources = self.sources()?;
+
+        if let [source] = sources.as_slice() {
+            return Ok(vec![Provider {
+                source: source.clone(),
+                name: None,
+                since: None,
+                failures: Default::default(),
+                plan: None,
+                metadata: None,
+                checkpoint: None,
+            }]);
+        }
+
+        let mut names = HashSet::new();
+        let mut result = Vec::with_capacity(sources.len());
+
+        for source in sources {
+            let descriptor = SourceDescriptor::from_str(&source)?;
+            let providers = descriptor
+                .clone()
+                .into_source_with_fetcher(
+                    self.config(&Provider {
+                        source: source.clone(),
+                        name: None,
+                        since: None,
+                        failures: Default::default(),
+                        plan: None,
+                        metadata: None,
+                        checkpoint: None,
+                    }),
+                    fetcher.clone(),
+                )?
+                .load_providers()
+                .await
+                .with_context(|| format!("Failed to load providers of source: {source}"))?;
+
+            for metadata in providers {
+                let name = provider_name(&metadata.canonical_url);
+                if !names.insert(name.clone()) {
+                    log::warn!("Skipping duplicate provider: {name} ({source})");
+                    continue;
+                }
+
+                log::info!("Provider: {name} ({})", metadata.canonical_url);
+
+                // only remote providers can be walked using their metadata, others need their source
+                let (source, metadata) = match descriptor {
+                    SourceDescriptor::Url(_)
+                    | SourceDescriptor::Aggregator(_)
+                    | SourceDescriptor::Lookup(_) => {
+                        (metadata.canonical_url.to_string(), Some(metadata))
+                    }
+                    SourceDescriptor::File(_)
+                    | SourceDescriptor::S3(_)
+                    | SourceDescriptor::Custom(_) => (source.clone(), None),
+                };
+
+                result.push(Provider {
+                    source,
+                    name: Some(name),
+                    since: None,
+                    failures: Default::default(),
+                    plan: None,
+                    metadata,
+                    checkpoint: None,
+                });
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl SkipArguments {
+    /// Load the since state of a provider.
+    pub fn since(&self, provider: &Provider) -> anyhow::Result<Since> {
+        Since::new(
+            self.since,
+            self.since_file.as_deref().map(|file| provider.file(file)),
+            self.since_file_offset.map(|d| d.into()).unwrap_or_default(),
+        )
+    }
+
+    /// Open the checkpoint of a provider.
+    pub fn checkpoint(&self, provider: &Provider) -> anyhow::Result<Option<Checkpoint>> {
+        let Some(file) = &self.checkpoint else {
+            return Ok(None);
+        };
+
+        let file = provider.file(file);
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Ok(Some(Checkpoint::open(file)?))
+    }
+}
+
+/// A provider to walk.
+#[derive(Clone, Debug)]
+pub struct Provider {
+    /// The source of the provider
+    pub source: String,
+    /// The name of the provider, derived from the canonical URL of its metadata.
+    ///
+    /// This is [`None`] when walking a single source, in which case no subdirectories are used.
+    pub name: Option<String>,
+    /// Only walk documents changed since this timestamp
+    pub since: Option<SystemTime>,
+    /// Distributions which failed to load
+    pub failures: DistributionFailures,
+    /// Record what would be done with the documents, instead of retrieving them
+    pub plan: Option<Plan>,
+    /// The metadata of the provider, if it was already loaded while resolving the sources
+    pub metadata: Option<ProviderMetadata>,
+    /// Record the documents completed successfully, see [`CheckpointVisitor`]
+    pub checkpoint: Option<Arc<Checkpoint>>,
+}
+
+impl Provider {
+    /// The directory of the provider, inside a base directory.
+    pub fn dir(&self, base: &Path) -> PathBuf {
+        match &self.name {
+            Some(name) => base.join(name),
+            None => base.to_path_buf(),
+        }
+    }
+
+    /// The location of the provider, inside a base location, creating the directory if necessary.
+    ///
+    /// The base directory itself is expected to exist. When only planning, nothing is created.
+    /// Buckets don't have directories, so nothing gets created either.
+    pub fn create_dir(&self, base: &Storage) -> std::io::Result<Storage> {
+        let storage = match &self.name {
+            Some(name) => base.join(name),
+            None => base.clone(),
+        };
+        if let (Some(dir), true, None) = (storage.as_path(), self.name.is_some(), &self.plan) {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(storage)
+    }
+
+    /// The location of a file of the provider, in a subdirectory next to the original location.
+    pub fn file(&self, file: &Path) -> PathBuf {
+        match (&self.name, file.file_name()) {
+            (Some(name), Some(file_name)) => file.with_file_name(name).join(file_name),
+            _ => file.to_path_buf(),
+        }
+    }
+}
+
+/// Derive the name of a provider from the canonical URL of its metadata.
+///
+/// This is the host name, followed by the path to the metadata, unless it's the well-known one.
+fn provider_name(url: &Url) -> String {
+    let mut name = url.host_str().unwrap_or_default().to_string();
+    if let Some(port) = url.port() {
+        name.push_str(&format!("_{port}"));
+    }
+
+    let path = url
+        .path()
+        .rsplit_once('/')
+        .map(|(dir, _)| dir)
+        .unwrap_or_default();
+    let path = path.strip_suffix("/.well-known/csaf").unwrap_or(path);
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        name.push('_');
+        name.push_str(segment);
+    }
+
+    name.replace(
+        |c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')),
+        "_",
+    )
 }
 
 /// Create a [`FilteringVisitor`] from a [`FilterConfig`].
@@ -66,53 +336,315 @@ where
     }
 }
 
-pub async fn walk_visitor<F, Fut, V, P>(
+/// Options for keeping local state in sync with the providers.
+#[derive(Default)]
+pub struct WalkOptions<'a> {
+    /// Load the since state and checkpoint of each provider
+    pub skip: Option<&'a SkipArguments>,
+    /// Prune stored documents, in the directory of each provider inside the base of the pruner
+    pub prune: Option<Pruner>,
+    /// Only plan what would be done, if requested
+    pub plan: Option<&'a PlanArguments>,
+}
+
+/// Walk all providers of the sources.
+///
+/// The visitor is created for each provider. The providers are walked concurrently, sharing the
+/// number of workers. If `skip` is present, the since state and checkpoint are loaded for each
+/// provider. The since state is stored, and the checkpoint removed, after the provider was walked
+/// successfully, without skipping any distributions. Pruning happens only in that case as well.
+///
+/// Documents completed by a previous run are skipped. Recording the documents completed by this
+/// run is up to the visitor, using a [`CheckpointVisitor`] with the checkpoint of the provider, as
+/// only the visitor knows if a document was processed successfully.
+///
+/// For a dry run, the documents are only recorded in a plan, which gets reported at the end. No
+/// state is stored, and pruning only reports the documents it would prune.
+///
+/// On SIGINT, SIGTERM, or once the maximum duration has passed, the documents currently being
+/// processed are completed, but no new ones are started.
+pub async fn walk_visitor<F, V, P>(
     progress: P,
     client: ClientArguments,
-    discover: impl Into<DiscoverConfig>,
+    discover: DiscoverArguments,
     filter: impl Into<FilterConfig>,
     runner: RunnerArguments,
+    options: WalkOptions<'_>,
     f: F,
 ) -> anyhow::Result<()>
 where
-    F: FnOnce(DispatchSource) -> Fut,
-    Fut: Future<Output = anyhow::Result<V>>,
-    V: DiscoveredVisitor,
+    F: AsyncFn(&Provider, DispatchSource) -> anyhow::Result<V>,
+    V: DiscoveredVisitor + 'static,
+    V::Context: 'static,
     V::Error: Send + Sync + 'static,
-    P: Progress,
+    P: Progress + Clone,
 {
-    let source = new_source(discover, client).await?;
+    if options.prune.is_some() && discover.deduplicate != Deduplicate::None {
+        // removed duplicates would be considered no longer listed, and get pruned
+        bail!("Pruning can't be combined with deduplication");
+    }
+
+    let fetcher = client.new_fetcher().await?;
+    let providers = discover.providers(&fetcher).await?;
+    let filter = filter.into();
+
+    let limit = Arc::new(Semaphore::new(match runner.workers {
+        0 => Semaphore::MAX_PERMITS,
+        n => n,
+    }));
+    let cancellation = runner.cancellation();
+    let WalkOptions { skip, prune, plan } = options;
+    let plan = plan
+        .filter(|plan| plan.dry_run)
+        .map(|plan| (plan, Plan::new()));
+
+    let walk = async |mut provider: Provider| {
+        provider.plan = plan.as_ref().map(|(_, plan)| plan.clone());
+        let since = skip.map(|skip| skip.since(&provider)).transpose()?;
+        provider.since = since.as_ref().and_then(|since| since.since);
+        provider.checkpoint = skip
+            // a dry run must not record any documents as completed
+            .filter(|_| provider.plan.is_none())
+            .map(|skip| skip.checkpoint(&provider))
+            .transpose()?
+            .flatten()
+            .map(Arc::new);
+
+        let pruner = prune.as_ref().map(|pruner| {
+            pruner
+                .clone()
+                .base(provider.dir(&pruner.base))
+                .dry_run(pruner.dry_run || provider.plan.is_some())
+        });
+        let listing = pruner.as_ref().map(|_| Listing::new());
+
+        let mut config = discover.config(&provider);
+        if pruner.is_some() {
+            // pruning requires the full index, unchanged documents are skipped later on
+            config.since = None;
+        }
+
+        let source: DispatchSource = match &provider.metadata {
+            Some(metadata) => HttpSource::new(
+                metadata.clone(),
+                fetcher.clone(),
+                HttpOptions::from(&config),
+            )
+            .into(),
+            None => SourceDescriptor::from_str(&provider.source)?
+                .into_source_with_fetcher(config, fetcher.clone())?,
+        };
+        let walker = Walker::new(source.clone())
+            .with_progress(progress.clone())
+            .with_cancellation(cancellation.clone())
+            .with_error_policy(discover.on_distribution_error.into())
+            .with_deduplication(discover.deduplicate.into())
+            .with_distribution_failures(provider.failures.clone());
+        let visitor = LimitingVisitor {
+            visitor: f(&provider, source.clone()).await?,
+            limit: limit.clone(),
+        };
+        // documents not reaching the retrieval are skipped by the visitor, as they are unchanged
+        let visitor = PlanningStage {
+            visitor,
+            plan: provider.plan.clone(),
+            reason: PlanReason::Unchanged,
+        };
+        // completed documents are recorded by the visitor, once processed successfully
+        let visitor = CheckpointVisitor {
+            visitor,
+            checkpoint: provider.checkpoint.clone(),
+        };
+
+        let result = walk_source(
+            walker,
+            filter.clone(),
+            &runner,
+            listing.clone(),
+            provider.plan.clone(),
+            visitor,
+        )
+        .await;
+        // skipped distributions need to be walked again
+        let complete = result.is_ok() && provider.failures.is_empty();
+
+        if let Some(checkpoint) = provider.checkpoint.take() {
+            match complete {
+                true => Arc::into_inner(checkpoint)
+                    .context("checkpoint is still in use")?
+                    .remove()?,
+                false => log::info!(
+                    "Completed documents are recorded in: {}",
+                    checkpoint.path().display()
+                ),
+            }
+        }
+        result?;
+
+        if let (Some(pruner), Some(listing), true) = (pruner, listing, complete) {
+            prune_provider(&pruner, &listing)?;
+        }
+
+        if let Some(since) = since {
+            if provider.plan.is_some() {
+                return Ok(());
+            }
+
+            if !complete {
+                log::warn!(
+                    "Failed to load {} distribution(s), not storing the since state",
+                    provider.failures.len()
+                );
+                return Ok(());
+            }
+
+            if let Some(parent) = since.since_file.as_deref().and_then(Path::parent) {
+                std::fs::create_dir_all(parent)?;
+            }
+            since.store()?;
+        }
+
+        Ok::<_, anyhow::Error>(())
+    };
 
-    walk_source(progress, source, filter, runner, f).await
+    let total = providers.len();
+    let concurrency = match runner.workers {
+        0 => total,
+        n => n.min(total),
+    };
+
+    let results: Vec<_> = stream::iter(providers)
+        .map(async |provider| (provider.name.clone(), walk(provider).await))
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    fetcher.stats().log();
+
+    if let Some((arguments, plan)) = plan {
+        report_plan(&plan, arguments.dry_run_output.as_deref())?;
+    }
+
+    let mut failed = 0;
+    for (name, result) in results {
+        match (name, result) {
+            (_, Ok(())) => {}
+            // a single source, report as it is
+            (None, Err(err)) => return Err(err),
+            (Some(name), Err(err)) => {
+                log::error!("Failed to walk provider {name}: {err:#}");
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("Failed to walk {failed} of {total} providers");
+    }
+
+    Ok(())
 }
 
-pub async fn walk_source<F, Fut, V, P>(
-    progress: P,
-    source: DispatchSource,
-    filter_config: impl Into<FilterConfig>,
-    runner: RunnerArguments,
-    f: F,
+/// Prune the documents of a provider, printing a report.
+fn prune_provider(pruner: &Pruner, listing: &Listing) -> anyhow::Result<()> {
+    let report = pruner.prune(listing)?;
+
+    let action = match pruner.dry_run {
+        true => "Would prune",
+        false => "Pruned",
+    };
+    for document in &report.documents {
+        println!("{action}: {}", document.display());
+    }
+    println!(
+        "{action} {} document(s) and {} signature/digest file(s) in: {}",
+        report.documents.len(),
+        report.sidecars,
+        pruner.base.display()
+    );
+
+    Ok(())
+}
+
+/// The plan of a dry run, as written to a file.
+#[derive(serde::Serialize)]
+struct PlanOutput {
+    summary: PlanSummary,
+    advisories: Vec<PlannedAdvisory>,
+}
+
+/// Report the plan of a dry run, printing it or writing it to a file as JSON.
+fn report_plan(plan: &Plan, output: Option<&Path>) -> anyhow::Result<()> {
+    let summary = plan.summary();
+
+    if let Some(output) = output {
+        let file = std::fs::File::create(output)
+            .with_context(|| format!("Failed to create plan file: {}", output.display()))?;
+        serde_json::to_writer_pretty(
+            std::io::BufWriter::new(file),
+            &PlanOutput {
+                summary,
+                advisories: plan.advisories(),
+            },
+        )?;
+        log::info!("Plan written to: {}", output.display());
+        return Ok(());
+    }
+
+    for advisory in plan.advisories() {
+        println!(
+            "{:<5} {:<9} {:>10} {}",
+            advisory.action,
+            advisory.reason,
+            advisory
+                .size
+                .map(|size| size.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            advisory.url
+        );
+    }
+    println!(
+        "Would fetch {} document(s) ({} bytes, {} of unknown size), and skip {} document(s)",
+        summary.fetch, summary.size, summary.unknown_size, summary.skip
+    );
+
+    Ok(())
+}
+
+async fn walk_source<V, P>(
+    walker: Walker<DispatchSource, P>,
+    filter_config: FilterConfig,
+    runner: &RunnerArguments,
+    listing: Option<Listing>,
+    plan: Option<Plan>,
+    visitor: V,
 ) -> anyhow::Result<()>
 where
-    F: FnOnce(DispatchSource) -> Fut,
-    Fut: Future<Output = anyhow::Result<V>>,
-    V: DiscoveredVisitor,
+    V: DiscoveredVisitor + 'static,
+    V::Context: 'static,
     V::Error: Send + Sync + 'static,
     P: Progress,
 {
-    let visitor = f(source.clone()).await?;
-    let walker = Walker::new(source).with_progress(progress);
+    // don't even discover filtered distributions
+    let distribution_filter = filter_config.clone();
+    let walker = walker.with_distribution_filter(move |distribution| {
+        distribution_filter.accepts_distribution(distribution)
+    });
+
+    // record all listed documents, including filtered ones
+    let visitor = ListingVisitor {
+        visitor: PlanningStage {
+            visitor: filter(filter_config, visitor),
+            plan,
+            reason: PlanReason::Filtered,
+        },
+        listing,
+    };
 
     match runner.workers {
-        1 => {
-            walker.walk(filter(filter_config, visitor)).await?;
-        }
-        n => {
-            walker
-                .walk_parallel(n, filter(filter_config, visitor))
-                .await?;
-        }
-    }
+        1 => walker.walk(visitor).await?,
+        n => walker.walk_parallel_spawned(n, visitor).await?,
+    };
 
     Ok(())
 }
@@ -120,8 +652,18 @@ where
 #[cfg(test)]
 mod test {
 
-    use csaf_walker::source::SourceDescriptor;
-    use std::str::FromStr;
+    use super::*;
+    use clap::Parser;
+    use csaf_walker::{
+        discover::{DiscoveredAdvisory, DistributionContext},
+        retrieve::RetrievedAdvisory,
+        source::{CustomSource, DynSource, register_scheme},
+    };
+    use futures::future::{BoxFuture, FutureExt};
+    use walker_common::{
+        utils::openpgp::PublicKey,
+        validate::source::{Key, KeySourceError},
+    };
 
     #[tokio::test]
     async fn test_file_relative() {
@@ -175,10 +717,171 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_aggregator() {
+        let source = SourceDescriptor::from_str(
+            "https://base.domain/.well-known/csaf-aggregator/aggregator.json",
+        );
+        println!("Result: {source:?}");
+        assert!(
+            matches!(source, Ok(SourceDescriptor::Aggregator(url)) if url.as_str() == "https://base.domain/.well-known/csaf-aggregator/aggregator.json")
+        );
+    }
+
     #[tokio::test]
     async fn test_gopher() {
         let source = SourceDescriptor::from_str("gopher://base.domain");
         println!("Result: {source:?}");
         assert!(source.is_err());
     }
+
+    #[test]
+    fn test_provider_name() {
+        let name = |url: &str| provider_name(&Url::parse(url).expect("must parse"));
+
+        assert_eq!(
+            name("https://www.redhat.com/.well-known/csaf/provider-metadata.json"),
+            "www.redhat.com"
+        );
+        assert_eq!(
+            name("https://example.com:8443/security/csaf/provider-metadata.json"),
+            "example.com_8443_security_csaf"
+        );
+    }
+
+    #[test]
+    fn test_provider_paths() {
+        let provider = Provider {
+            source: "redhat.com".to_string(),
+            name: Some("www.redhat.com".to_string()),
+            since: None,
+            failures: Default::default(),
+            plan: None,
+            metadata: None,
+            checkpoint: None,
+        };
+
+        assert_eq!(
+            provider.dir(Path::new("out")),
+            Path::new("out/www.redhat.com")
+        );
+        assert_eq!(
+            provider.file(Path::new("state/since.json")),
+            Path::new("state/www.redhat.com/since.json")
+        );
+
+        let provider = Provider {
+            name: None,
+            ..provider
+        };
+
+        assert_eq!(provider.dir(Path::new("out")), Path::new("out"));
+        assert_eq!(
+            provider.file(Path::new("state/since.json")),
+            Path::new("state/since.json")
+        );
+    }
+
+    /// A custom source, serving a single provider named after the host of its URL.
+    #[derive(Debug)]
+    struct MockSource(Url);
+
+    impl DynSource for MockSource {
+        fn load_metadata(&self) -> BoxFuture<'_, anyhow::Result<ProviderMetadata>> {
+            let host = self.0.host_str().unwrap_or_default().to_string();
+            async move {
+                Ok(serde_json::from_value(serde_json::json!({
+                    "canonical_url": format!("https://{host}/provider-metadata.json"),
+                    "distributions": [],
+                    "last_updated": "2024-01-01T00:00:00Z",
+                    "metadata_version": "2.0",
+                    "publisher": {
+                        "category": "vendor",
+                        "contact_details": "",
+                        "name": "Example",
+                        "namespace": "https://example.com",
+                    },
+                }))?)
+            }
+            .boxed()
+        }
+
+        fn load_index(
+            &self,
+            _context: DistributionContext,
+        ) -> BoxFuture<'_, anyhow::Result<Vec<DiscoveredAdvisory>>> {
+            async { Ok(vec![]) }.boxed()
+        }
+
+        fn load_advisory(
+            &self,
+            _advisory: DiscoveredAdvisory,
+        ) -> BoxFuture<'_, anyhow::Result<Option<RetrievedAdvisory>>> {
+            async { Ok(None) }.boxed()
+        }
+
+        fn load_public_key<'a>(
+            &'a self,
+            _key: Key<'a>,
+        ) -> BoxFuture<'a, Result<PublicKey, KeySourceError<anyhow::Error>>> {
+            async { Err(KeySourceError::Source(anyhow::anyhow!("no keys"))) }.boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_providers() {
+        register_scheme("mock-providers", |url, _, _| {
+            Ok(CustomSource::new(MockSource(url)))
+        })
+        .expect("must register");
+
+        let discover = DiscoverArguments::parse_from([
+            "test",
+            "mock-providers://a.example.com",
+            "mock-providers://b.example.com",
+        ]);
+        let fetcher = Fetcher::new(Default::default())
+            .await
+            .expect("must create fetcher");
+        let providers = discover.providers(&fetcher).await.expect("must resolve");
+
+        // custom sources must not be replaced by walking their metadata using HTTP
+        let providers = providers
+            .into_iter()
+            .map(|provider| (provider.name, provider.source, provider.metadata.is_some()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            providers,
+            vec![
+                (
+                    Some("a.example.com".to_string()),
+                    "mock-providers://a.example.com".to_string(),
+                    false
+                ),
+                (
+                    Some("b.example.com".to_string()),
+                    "mock-providers://b.example.com".to_string(),
+                    false
+                ),
+            ]
+        );
+
+        // and can be turned into their source again
+        let source = SourceDescriptor::from_str(&providers[0].1)
+            .expect("must parse")
+            .into_source_with_fetcher(
+                discover.config(&Provider {
+                    source: providers[0].1.clone(),
+                    name: None,
+                    since: None,
+                    failures: Default::default(),
+                    plan: None,
+                    metadata: None,
+                    checkpoint: None,
+                }),
+                fetcher,
+            )
+            .expect("must create source");
+        assert!(matches!(source, DispatchSource::Custom(_)));
+    }
 }
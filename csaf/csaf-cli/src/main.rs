@@ -5,8 +5,9 @@ mod common;
 
 use clap::Parser;
 use cmd::{
-    discover::Discover, download::Download, fetch::Fetch, metadata::Metadata, parse::Parse,
-    report::Report, scan::Scan, scoop::Scoop, send::Send, sync::Sync,
+    check_provider::CheckProvider, discover::Discover, download::Download, fetch::Fetch,
+    metadata::Metadata, parse::Parse, report::Report, scan::Scan, scoop::Scoop, send::Send,
+    sync::Sync,
 };
 use std::{ops::Deref, process::ExitCode};
 use walker_common::{
@@ -35,6 +36,7 @@ enum Command {
     Report(Report),
     Send(Send),
     Metadata(Metadata),
+    CheckProvider(CheckProvider),
     Scoop(Scoop),
 }
 
@@ -52,13 +54,18 @@ impl Deref for Command {
             Self::Report(cmd) => cmd,
             Self::Send(cmd) => cmd,
             Self::Metadata(cmd) => cmd,
+            Self::CheckProvider(cmd) => cmd,
             Self::Scoop(cmd) => cmd,
         }
     }
 }
 
 impl Command {
-    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P>(self, progress: P) -> anyhow::Result<()>
+    where
+        // `Send` and `Sync` are commands
+        P: Progress + Clone + std::marker::Send + std::marker::Sync + 'static,
+    {
         match self {
             Self::Parse(cmd) => cmd.run(progress).await,
             Self::Download(cmd) => cmd.run(progress).await,
@@ -69,6 +76,7 @@ impl Command {
             Self::Report(cmd) => cmd.run(progress).await,
             Self::Send(cmd) => cmd.run(progress).await,
             Self::Metadata(cmd) => cmd.run().await,
+            Self::CheckProvider(cmd) => cmd.run().await,
             Self::Scoop(cmd) => cmd.run(progress).await,
         }
     }
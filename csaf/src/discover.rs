@@ -1,14 +1,25 @@
 //! Discovering
 
-use crate::model::metadata::ProviderMetadata;
+use crate::{
+    metadata::{MetadataVerification, MirrorPolicy},
+    model::metadata::{Feed, ProviderMetadata, TlpLabel},
+    rolie::Category,
+};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::fmt::Debug;
 use std::future::Future;
 use std::sync::Arc;
 use std::time::SystemTime;
 use url::Url;
-use walker_common::utils::url::Urlify;
+use walker_common::{
+    index::DirectoryIndex,
+    retrieve::{RetrievalMetadata, RetrievalOptions},
+    utils::url::Urlify,
+};
 
 /// Discovery configuration
+#[derive(Clone, Debug)]
 pub struct DiscoverConfig {
     /// The source to locate the provider metadata.
     ///
@@ -19,6 +30,21 @@ pub struct DiscoverConfig {
     /// Only report documents which have changed since the provided date. If a document has no
     /// change information, or this field is [`None`], it will always be reported.
     pub since: Option<SystemTime>,
+
+    /// How to verify the signature of the provider metadata.
+    pub metadata_verification: MetadataVerification,
+
+    /// How to retrieve the content of documents.
+    pub retrieval: RetrievalOptions,
+
+    /// The maximum number of pages to retrieve for a single ROLIE feed.
+    pub feed_page_limit: Option<usize>,
+
+    /// How to use the `index.txt` and `changes.csv` files of directory based distributions.
+    pub directory_index: DirectoryIndex,
+
+    /// How to handle the mirrors of the providers listed by an aggregator.
+    pub mirrors: MirrorPolicy,
 }
 
 impl DiscoverConfig {
@@ -26,6 +52,34 @@ impl DiscoverConfig {
         self.since = since.into();
         self
     }
+
+    pub fn with_metadata_verification(
+        mut self,
+        metadata_verification: MetadataVerification,
+    ) -> Self {
+        self.metadata_verification = metadata_verification;
+        self
+    }
+
+    pub fn with_retrieval(mut self, retrieval: RetrievalOptions) -> Self {
+        self.retrieval = retrieval;
+        self
+    }
+
+    pub fn with_feed_page_limit(mut self, feed_page_limit: impl Into<Option<usize>>) -> Self {
+        self.feed_page_limit = feed_page_limit.into();
+        self
+    }
+
+    pub fn with_directory_index(mut self, directory_index: DirectoryIndex) -> Self {
+        self.directory_index = directory_index;
+        self
+    }
+
+    pub fn with_mirrors(mut self, mirrors: MirrorPolicy) -> Self {
+        self.mirrors = mirrors;
+        self
+    }
 }
 
 impl From<&str> for DiscoverConfig {
@@ -33,6 +87,11 @@ impl From<&str> for DiscoverConfig {
         Self {
             since: None,
             source: value.to_string(),
+            metadata_verification: Default::default(),
+            retrieval: Default::default(),
+            feed_page_limit: None,
+            directory_index: Default::default(),
+            mirrors: Default::default(),
         }
     }
 }
@@ -40,7 +99,7 @@ impl From<&str> for DiscoverConfig {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DistributionContext {
     Directory(Url),
-    Feed(Url),
+    Feed(Feed),
 }
 
 impl DistributionContext {
@@ -48,7 +107,25 @@ impl DistributionContext {
     pub fn url(&self) -> &Url {
         match self {
             Self::Directory(url) => url,
-            Self::Feed(url) => url,
+            Self::Feed(feed) => &feed.url,
+        }
+    }
+
+    /// Get the TLP label of the distribution
+    ///
+    /// Directory distributions don't carry a label, and are considered [`TlpLabel::Unlabeled`].
+    pub fn tlp_label(&self) -> &TlpLabel {
+        match self {
+            Self::Directory(_) => &TlpLabel::Unlabeled,
+            Self::Feed(feed) => &feed.tlp_label,
+        }
+    }
+
+    /// Get the summary of the distribution, if it has one
+    pub fn summary(&self) -> Option<&str> {
+        match self {
+            Self::Directory(_) => None,
+            Self::Feed(feed) => feed.summary.as_deref(),
         }
     }
 }
@@ -65,10 +142,93 @@ pub struct DiscoveredAdvisory {
     pub signature: Option<Url>,
     /// The "last changed" date from the change information
     pub modified: SystemTime,
+    /// The ROLIE categories of the advisory, from its feed and entry
+    pub categories: Vec<Category>,
+    /// The URL of the ROLIE feed page the advisory was found on
+    pub page: Option<Url>,
+    /// The retrieval metadata of a local copy, if one is known
+    ///
+    /// This is used by sources to only retrieve the advisory if it was modified.
+    pub local: Option<RetrievalMetadata>,
+}
+
+impl DiscoveredAdvisory {
+    /// Rate the metadata of the advisory, a higher value being better.
+    ///
+    /// Advisories with digest and signature links are preferred over newer ones.
+    fn rating(&self) -> (usize, SystemTime) {
+        let links = usize::from(self.digest.is_some()) + usize::from(self.signature.is_some());
+        (links, self.modified)
+    }
+}
+
+/// How to detect advisories discovered more than once, e.g. through a directory distribution
+/// and a ROLIE feed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Deduplication {
+    /// Keep all advisories.
+    #[default]
+    None,
+    /// Advisories with the same URL, ignoring any fragment.
+    Url,
+    /// Advisories with the same filename.
+    Filename,
+}
+
+impl Deduplication {
+    fn key(&self, advisory: &DiscoveredAdvisory) -> Option<String> {
+        match self {
+            Self::None => None,
+            Self::Url => {
+                let mut url = advisory.url.clone();
+                url.set_fragment(None);
+                Some(url.into())
+            }
+            Self::Filename => advisory
+                .url
+                .path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .map(ToString::to_string),
+        }
+    }
+
+    /// Remove duplicates, keeping the advisory with the best metadata.
+    ///
+    /// The order of the advisories is kept, using the position of the first occurrence.
+    pub fn deduplicate(
+        &self,
+        advisories: impl IntoIterator<Item = DiscoveredAdvisory>,
+    ) -> Vec<DiscoveredAdvisory> {
+        let mut result = Vec::<DiscoveredAdvisory>::new();
+        let mut known = HashMap::new();
+
+        for advisory in advisories {
+            let Some(key) = self.key(&advisory) else {
+                result.push(advisory);
+                continue;
+            };
+
+            match known.entry(key) {
+                Entry::Vacant(entry) => {
+                    entry.insert(result.len());
+                    result.push(advisory);
+                }
+                Entry::Occupied(entry) => {
+                    let existing = &mut result[*entry.get()];
+                    log::debug!("Duplicate advisory: {} ({})", advisory.url, existing.url);
+                    if advisory.rating() > existing.rating() {
+                        *existing = advisory;
+                    }
+                }
+            }
+        }
+
+        result
+    }
 }
 
 /// Get a document as [`DiscoveredAdvisory`]
-pub trait AsDiscovered: Debug {
+pub trait AsDiscovered: Debug + Send + Sync {
     fn as_discovered(&self) -> &DiscoveredAdvisory;
 }
 
@@ -94,30 +254,32 @@ impl Urlify for DiscoveredAdvisory {
 #[derive(Debug)]
 pub struct DiscoveredContext<'c> {
     pub metadata: &'c ProviderMetadata,
+    /// The categories listed by the ROLIE category documents of the provider
+    pub categories: &'c [Category],
 }
 
 /// Visiting discovered advisories
-pub trait DiscoveredVisitor {
-    type Error: std::fmt::Display + Debug;
-    type Context;
+pub trait DiscoveredVisitor: Send + Sync {
+    type Error: std::fmt::Display + Debug + Send;
+    type Context: Send + Sync;
 
     fn visit_context(
         &self,
         context: &DiscoveredContext,
-    ) -> impl Future<Output = Result<Self::Context, Self::Error>>;
+    ) -> impl Future<Output = Result<Self::Context, Self::Error>> + Send;
 
     fn visit_advisory(
         &self,
         context: &Self::Context,
         advisory: DiscoveredAdvisory,
-    ) -> impl Future<Output = Result<(), Self::Error>>;
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
 }
 
 impl<F, E, Fut> DiscoveredVisitor for F
 where
-    F: Fn(DiscoveredAdvisory) -> Fut,
-    Fut: Future<Output = Result<(), E>>,
-    E: std::fmt::Display + Debug,
+    F: Fn(DiscoveredAdvisory) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), E>> + Send,
+    E: std::fmt::Display + Debug + Send,
 {
     type Error = E;
     type Context = ();
@@ -137,3 +299,67 @@ where
         self(advisory).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    fn advisory(distribution: &str, name: &str, modified: u64) -> DiscoveredAdvisory {
+        let context = Arc::new(DistributionContext::Directory(
+            Url::parse(distribution).expect("must parse"),
+        ));
+        DiscoveredAdvisory {
+            url: context.url().join(name).expect("must join"),
+            context,
+            digest: None,
+            signature: None,
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(modified),
+            categories: vec![],
+            page: None,
+            local: None,
+        }
+    }
+
+    #[test]
+    fn deduplicate() {
+        let signed = DiscoveredAdvisory {
+            signature: Some(
+                Url::parse("https://example.com/feed/2023/a.json.asc").expect("must parse"),
+            ),
+            ..advisory("https://example.com/feed/", "2023/a.json", 1)
+        };
+        let advisories = vec![
+            advisory("https://example.com/dir/", "2023/a.json", 2),
+            advisory("https://example.com/dir/", "2023/b.json", 1),
+            advisory("https://example.com/dir/", "2023/b.json#fragment", 2),
+            signed.clone(),
+        ];
+
+        let urls = |advisories: Vec<DiscoveredAdvisory>| {
+            advisories
+                .into_iter()
+                .map(|advisory| advisory.url.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(Deduplication::None.deduplicate(advisories.clone()).len(), 4);
+        // the newer one of the two
+        assert_eq!(
+            urls(Deduplication::Url.deduplicate(advisories.clone())),
+            [
+                "https://example.com/dir/2023/a.json",
+                "https://example.com/dir/2023/b.json#fragment",
+                "https://example.com/feed/2023/a.json",
+            ]
+        );
+        // the signed one, at the position of the first occurrence
+        assert_eq!(
+            urls(Deduplication::Filename.deduplicate(advisories)),
+            [
+                "https://example.com/feed/2023/a.json",
+                "https://example.com/dir/2023/b.json#fragment",
+            ]
+        );
+    }
+}
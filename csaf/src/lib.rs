@@ -61,6 +61,7 @@ pub mod report;
 pub mod retrieve;
 pub mod rolie;
 pub mod source;
+pub mod stream;
 pub mod validation;
 pub mod visitors;
 pub mod walker;
@@ -0,0 +1,146 @@
+use super::{Error, MetadataSource, MetadataVerification};
+use crate::model::{aggregator::Aggregator, metadata::ProviderMetadata};
+use async_trait::async_trait;
+use url::Url;
+use walker_common::fetcher::{Fetcher, Json};
+
+/// How to handle the mirrors of a provider, listed by an aggregator.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MirrorPolicy {
+    /// Only use the provider's own metadata, ignore the mirrors.
+    Ignore,
+    /// Use the provider's own metadata, try the mirrors in case that fails.
+    #[default]
+    Fallback,
+    /// Use the provider's own metadata and all of its mirrors.
+    All,
+}
+
+/// A metadata source, listing all providers of a CSAF aggregator or lister.
+///
+/// The source points to the `aggregator.json` file. Each listed provider or publisher will be
+/// loaded from its own `provider-metadata.json`, keeping its own keys and distributions.
+#[derive(Clone, Debug)]
+pub struct AggregatorRetriever {
+    pub url: Url,
+    pub mirrors: MirrorPolicy,
+    pub verification: MetadataVerification,
+}
+
+impl AggregatorRetriever {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            mirrors: Default::default(),
+            verification: Default::default(),
+        }
+    }
+
+    pub fn mirrors(mut self, mirrors: MirrorPolicy) -> Self {
+        self.mirrors = mirrors;
+        self
+    }
+
+    pub fn verification(mut self, verification: MetadataVerification) -> Self {
+        self.verification = verification;
+        self
+    }
+
+    /// Retrieve the `aggregator.json` document.
+    pub async fn load_aggregator(&self, fetcher: &Fetcher) -> Result<Aggregator, Error> {
+        Ok(fetcher
+            .fetch::<Json<Aggregator>>(self.url.clone())
+            .await?
+            .into_inner())
+    }
+
+    /// Load a single listed entry, following the mirror policy.
+    async fn load_entry(
+        &self,
+        fetcher: &Fetcher,
+        url: &Url,
+        mirrors: &[Url],
+    ) -> Vec<ProviderMetadata> {
+        let mut result = vec![];
+
+        match self.verification.fetch_metadata(fetcher, url.clone()).await {
+            Ok(metadata) => {
+                result.push(metadata);
+                if self.mirrors != MirrorPolicy::All {
+                    return result;
+                }
+            }
+            Err(err) => {
+                log::warn!("Failed to load provider metadata ({url}): {err}");
+                if self.mirrors == MirrorPolicy::Ignore {
+                    return result;
+                }
+            }
+        }
+
+        for mirror in mirrors {
+            match self
+                .verification
+                .fetch_metadata(fetcher, mirror.clone())
+                .await
+            {
+                Ok(metadata) => {
+                    result.push(metadata);
+                    if self.mirrors == MirrorPolicy::Fallback {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    log::warn!("Failed to load mirrored provider metadata ({mirror}): {err}");
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[async_trait]
+impl MetadataSource for AggregatorRetriever {
+    /// Load the metadata of the single provider listed by the aggregator.
+    ///
+    /// Fails if the aggregator doesn't list exactly one provider or publisher, in which case
+    /// [`MetadataSource::load_providers`] must be used. Using [`MirrorPolicy::All`], only the
+    /// first metadata which could be loaded is returned.
+    async fn load_metadata(&self, fetcher: &Fetcher) -> Result<ProviderMetadata, Error> {
+        let aggregator = self.load_aggregator(fetcher).await?;
+
+        let mut entries = aggregator.entries();
+        let (metadata, mirrors) = match (entries.next(), entries.next()) {
+            (Some(entry), None) => entry,
+            (None, _) => return Err(Error::NotFound),
+            (Some(_), Some(_)) => return Err(Error::MultipleProviders),
+        };
+
+        self.load_entry(fetcher, &metadata.url, mirrors)
+            .await
+            .into_iter()
+            .next()
+            .ok_or(Error::NotFound)
+    }
+
+    async fn load_providers(&self, fetcher: &Fetcher) -> Result<Vec<ProviderMetadata>, Error> {
+        let aggregator = self.load_aggregator(fetcher).await?;
+
+        log::info!(
+            "Aggregator '{}' lists {} provider(s) and {} publisher(s)",
+            aggregator.aggregator.name,
+            aggregator.csaf_providers.len(),
+            aggregator.csaf_publishers.len()
+        );
+
+        let mut result = vec![];
+
+        for (metadata, mirrors) in aggregator.entries() {
+            log::debug!("Loading listed provider: {}", metadata.publisher.name);
+            result.extend(self.load_entry(fetcher, &metadata.url, mirrors).await);
+        }
+
+        Ok(result)
+    }
+}
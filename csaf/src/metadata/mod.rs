@@ -1,10 +1,16 @@
+mod aggregator;
+mod signature;
+
+pub use aggregator::*;
+pub use signature::*;
+
 use crate::model::metadata::ProviderMetadata;
 use async_trait::async_trait;
 use hickory_resolver::Resolver;
 use sectxtlib::SecurityTxt;
-use std::fmt::Debug;
+use std::{fmt::Debug, path::PathBuf};
 use url::Url;
-use walker_common::fetcher::{self, Fetcher, Json};
+use walker_common::fetcher::{self, Fetcher};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -16,31 +22,60 @@ pub enum Error {
     NotFound,
     #[error("DNS request failed: {0}")]
     Dns(#[from] hickory_resolver::ResolveError),
+    #[error("source lists multiple providers")]
+    MultipleProviders,
+    #[error("failed to parse metadata: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid URL: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("failed to load key ({0}): {1}")]
+    Key(PathBuf, anyhow::Error),
+    #[error("no trusted keys to verify the metadata signature")]
+    NoTrustedKeys,
+    #[error("missing metadata signature: {0}")]
+    MissingSignature(Url),
+    #[error("invalid metadata signature: {0}")]
+    Signature(anyhow::Error),
 }
 
-#[async_trait(?Send)]
-pub trait MetadataSource: Debug {
+#[async_trait]
+pub trait MetadataSource: Debug + Send + Sync {
     async fn load_metadata(&self, fetcher: &Fetcher) -> Result<ProviderMetadata, Error>;
+
+    /// Load the metadata of all providers reachable through this source.
+    ///
+    /// By default, this is the single provider from [`MetadataSource::load_metadata`]. Sources
+    /// like an aggregator may list more than one.
+    async fn load_providers(&self, fetcher: &Fetcher) -> Result<Vec<ProviderMetadata>, Error> {
+        Ok(vec![self.load_metadata(fetcher).await?])
+    }
 }
 
-#[async_trait(?Send)]
+#[async_trait]
 impl MetadataSource for Url {
     async fn load_metadata(&self, fetcher: &Fetcher) -> Result<ProviderMetadata, Error> {
-        Ok(fetcher
-            .fetch::<Json<ProviderMetadata>>(self.clone())
-            .await?
-            .into_inner())
+        MetadataVerification::default()
+            .fetch_metadata(fetcher, self.clone())
+            .await
     }
 }
 
-#[async_trait(?Send)]
+/// Metadata which was already loaded, e.g. while resolving the providers of an aggregator.
+#[async_trait]
+impl MetadataSource for ProviderMetadata {
+    async fn load_metadata(&self, _fetcher: &Fetcher) -> Result<ProviderMetadata, Error> {
+        Ok(self.clone())
+    }
+}
+
+#[async_trait]
 impl MetadataSource for &str {
     async fn load_metadata(&self, fetcher: &Fetcher) -> Result<ProviderMetadata, Error> {
         MetadataRetriever::new(*self).load_metadata(fetcher).await
     }
 }
 
-#[async_trait(?Send)]
+#[async_trait]
 impl MetadataSource for String {
     async fn load_metadata(&self, fetcher: &Fetcher) -> Result<ProviderMetadata, Error> {
         MetadataRetriever::new(self).load_metadata(fetcher).await
@@ -51,15 +86,22 @@ impl MetadataSource for String {
 #[derive(Clone, Debug)]
 pub struct MetadataRetriever {
     pub base_url: String,
+    pub verification: MetadataVerification,
 }
 
 impl MetadataRetriever {
     pub fn new(base_url: impl Into<String>) -> Self {
         Self {
             base_url: base_url.into(),
+            verification: Default::default(),
         }
     }
 
+    pub fn verification(mut self, verification: MetadataVerification) -> Self {
+        self.verification = verification;
+        self
+    }
+
     /// Fetch a security.txt and extract all CSAF entries.
     ///
     /// In order for a CSAF entry to be considered, it needs to have a scheme of `https` and parse
@@ -99,12 +141,7 @@ impl MetadataRetriever {
             return Ok(None);
         };
 
-        Ok(Some(
-            fetcher
-                .fetch::<Json<ProviderMetadata>>(url)
-                .await?
-                .into_inner(),
-        ))
+        Ok(Some(self.verification.fetch_metadata(fetcher, url).await?))
     }
 
     /// Retrieve provider metadata through the full well-known URL.
@@ -121,10 +158,9 @@ impl MetadataRetriever {
 
         log::debug!("Trying to retrieve by well-known approach: {url}");
 
-        Ok(fetcher
-            .fetch::<Option<Json<ProviderMetadata>>>(url)
-            .await?
-            .map(|metadata| metadata.into_inner()))
+        self.verification
+            .fetch_metadata_optional(fetcher, Url::parse(&url)?)
+            .await
     }
 
     /// Retrieve provider metadata through the DNS path of provided URL.
@@ -165,10 +201,9 @@ impl MetadataRetriever {
 
         let url = format!("https://{host}");
 
-        Ok(fetcher
-            .fetch::<Option<Json<ProviderMetadata>>>(url)
-            .await?
-            .map(|value| value.into_inner()))
+        self.verification
+            .fetch_metadata_optional(fetcher, Url::parse(&url)?)
+            .await
     }
 
     /// Retrieving provider metadata via the security text from the provided URL.
@@ -186,19 +221,14 @@ impl MetadataRetriever {
 
         if let Some(url) = Self::get_metadata_url_from_security_text(fetcher, url).await? {
             // if we fail with a 404, that's an error too, as the security.txt pointed to us towards it
-            Ok(Some(
-                fetcher
-                    .fetch::<Json<ProviderMetadata>>(url)
-                    .await?
-                    .into_inner(),
-            ))
+            Ok(Some(self.verification.fetch_metadata(fetcher, url).await?))
         } else {
             Ok(None)
         }
     }
 }
 
-#[async_trait(?Send)]
+#[async_trait]
 impl MetadataSource for MetadataRetriever {
     async fn load_metadata(&self, fetcher: &Fetcher) -> Result<ProviderMetadata, Error> {
         // try a full URL first
@@ -0,0 +1,242 @@
+use super::Error;
+use crate::{model::metadata::ProviderMetadata, visitors::store::DIR_METADATA};
+use bytes::Bytes;
+use std::{
+    collections::HashSet,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use url::Url;
+use walker_common::{
+    fetcher::Fetcher,
+    utils::openpgp::{PublicKey, validate_keys},
+    validate::{ValidationOptions, openpgp::validate_signature},
+};
+
+/// How to handle the OpenPGP signature of a `provider-metadata.json` file.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MetadataSignatureMode {
+    /// Don't check the signature.
+    #[default]
+    Off,
+    /// Check the signature, but only log a warning if it can't be verified.
+    Warn,
+    /// Fail loading the metadata if the signature can't be verified.
+    Require,
+}
+
+/// Verification of the `provider-metadata.json.asc` signature.
+///
+/// The signature is never verified using the keys listed in the metadata itself, as a tampered
+/// metadata file could simply list different keys. Only keys pinned by the user, or keys stored
+/// by a previous run, are trusted.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetadataVerification {
+    pub mode: MetadataSignatureMode,
+    /// Files containing pinned OpenPGP keys
+    pub keys: Vec<PathBuf>,
+    /// The storage base of a previous run, providing the keys stored by it
+    pub previous: Option<PathBuf>,
+    /// Time for policy checks
+    pub validation_date: Option<SystemTime>,
+}
+
+impl MetadataVerification {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(mut self, mode: MetadataSignatureMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn keys<I>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        self.keys = Vec::from_iter(keys);
+        self
+    }
+
+    pub fn add_key(mut self, key: impl Into<PathBuf>) -> Self {
+        self.keys.push(key.into());
+        self
+    }
+
+    pub fn previous(mut self, previous: impl Into<Option<PathBuf>>) -> Self {
+        self.previous = previous.into();
+        self
+    }
+
+    pub fn validation_date(mut self, validation_date: impl Into<Option<SystemTime>>) -> Self {
+        self.validation_date = validation_date.into();
+        self
+    }
+
+    /// Retrieve a provider metadata document, verifying its signature.
+    pub async fn fetch_metadata(
+        &self,
+        fetcher: &Fetcher,
+        url: Url,
+    ) -> Result<ProviderMetadata, Error> {
+        let data = fetcher.fetch::<Bytes>(url.clone()).await?;
+        self.verify(fetcher, &url, data).await
+    }
+
+    /// Retrieve a provider metadata document, verifying its signature.
+    ///
+    /// If the document cannot be found, [`None`] is returned.
+    pub async fn fetch_metadata_optional(
+        &self,
+        fetcher: &Fetcher,
+        url: Url,
+    ) -> Result<Option<ProviderMetadata>, Error> {
+        let Some(data) = fetcher.fetch::<Option<Bytes>>(url.clone()).await? else {
+            return Ok(None);
+        };
+        Ok(Some(self.verify(fetcher, &url, data).await?))
+    }
+
+    async fn verify(
+        &self,
+        fetcher: &Fetcher,
+        url: &Url,
+        data: Bytes,
+    ) -> Result<ProviderMetadata, Error> {
+        let metadata: ProviderMetadata = serde_json::from_slice(&data)?;
+
+        if self.mode == MetadataSignatureMode::Off {
+            return Ok(metadata);
+        }
+
+        match self.verify_signature(fetcher, url, &data, &metadata).await {
+            Ok(()) => {
+                log::info!("Verified signature of provider metadata: {url}");
+                Ok(metadata)
+            }
+            Err(err) if self.mode == MetadataSignatureMode::Warn => {
+                log::warn!("Unable to verify signature of provider metadata ({url}): {err}");
+                Ok(metadata)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn verify_signature(
+        &self,
+        fetcher: &Fetcher,
+        url: &Url,
+        data: &[u8],
+        metadata: &ProviderMetadata,
+    ) -> Result<(), Error> {
+        let keys = self.trusted_keys().await?;
+        if keys.is_empty() {
+            return Err(Error::NoTrustedKeys);
+        }
+
+        let signature_url = Url::parse(&format!("{url}.asc"))?;
+        let Some(signature) = fetcher
+            .fetch::<Option<String>>(signature_url.clone())
+            .await?
+        else {
+            return Err(Error::MissingSignature(signature_url));
+        };
+
+        let options = ValidationOptions::new().validation_date(self.validation_date);
+        validate_signature(&options, &keys, &signature, data).map_err(Error::Signature)?;
+
+        // the signature is fine, but the provider might have rotated its keys
+
+        let trusted: HashSet<_> = keys
+            .iter()
+            .flat_map(|key| &key.certs)
+            .map(|cert| cert.fingerprint().to_hex())
+            .collect();
+
+        for key in &metadata.public_openpgp_keys {
+            match &key.fingerprint {
+                Some(fingerprint) if !trusted.contains(&fingerprint.to_uppercase()) => {
+                    log::warn!("Provider metadata lists a new key: {fingerprint}");
+                }
+                Some(_) => {}
+                None => log::warn!(
+                    "Provider metadata lists a key without fingerprint: {}",
+                    key.url
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load all trusted keys, pinned ones and those from a previous run.
+    pub async fn trusted_keys(&self) -> Result<Vec<PublicKey>, Error> {
+        let mut result = vec![];
+
+        for path in &self.keys {
+            result.push(load_key(path).await?);
+        }
+
+        if let Some(previous) = &self.previous {
+            let dir = previous.join(DIR_METADATA).join("keys");
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    log::info!("No keys of a previous run found: {}", dir.display());
+                    return Ok(result);
+                }
+                Err(err) => return Err(Error::Key(dir, err.into())),
+            };
+
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|err| Error::Key(dir.clone(), err.into()))?
+            {
+                let path = entry.path();
+                if path.is_file() && path.extension().is_some_and(|ext| ext == "txt") {
+                    result.push(load_key(&path).await?);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+async fn load_key(path: &Path) -> Result<PublicKey, Error> {
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|err| Error::Key(path.to_path_buf(), err.into()))?;
+    validate_keys(data.into(), None).map_err(|err| Error::Key(path.to_path_buf(), err.into()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn missing_previous_run() {
+        let verification = MetadataVerification::new()
+            .mode(MetadataSignatureMode::Require)
+            .previous(PathBuf::from("this-should-not-exist"));
+
+        let keys = verification.trusted_keys().await.expect("must not fail");
+        assert!(keys.is_empty());
+    }
+
+    #[tokio::test]
+    async fn missing_pinned_key() {
+        let verification = MetadataVerification::new()
+            .mode(MetadataSignatureMode::Require)
+            .add_key("this-should-not-exist.txt");
+
+        let result = verification.trusted_keys().await;
+        assert!(
+            matches!(result, Err(Error::Key(path, _)) if path == Path::new("this-should-not-exist.txt"))
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use crate::model::metadata::Role;
+use chrono::{DateTime, Utc};
+use url::Url;
+
+/// The content of an `aggregator.json` file, as defined in section 7.1.21 of the CSAF standard.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Aggregator {
+    pub aggregator: AggregatorInformation,
+
+    pub aggregator_version: String,
+
+    pub canonical_url: Url,
+
+    #[serde(default)]
+    pub csaf_providers: Vec<ListedProvider>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub csaf_publishers: Vec<ListedPublisher>,
+
+    pub last_updated: DateTime<Utc>,
+}
+
+impl Aggregator {
+    /// Iterate over all listed entries, providers first, then publishers.
+    pub fn entries(&self) -> impl Iterator<Item = (&ListedMetadata, &[Url])> {
+        self.csaf_providers
+            .iter()
+            .map(|provider| (&provider.metadata, provider.mirrors.as_slice()))
+            .chain(
+                self.csaf_publishers
+                    .iter()
+                    .map(|publisher| (&publisher.metadata, publisher.mirrors.as_slice())),
+            )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct AggregatorInformation {
+    pub category: AggregatorCategory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact_details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuing_authority: Option<String>,
+    pub name: String,
+    pub namespace: Url,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregatorCategory {
+    Aggregator,
+    Lister,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ListedProvider {
+    pub metadata: ListedMetadata,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<Url>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ListedPublisher {
+    pub metadata: ListedMetadata,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<Url>,
+    pub update_interval: String,
+}
+
+/// The metadata of a provider or publisher, as listed by an aggregator.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ListedMetadata {
+    pub last_updated: DateTime<Utc>,
+    pub publisher: ListedPublisherInformation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    /// The URL of the `provider-metadata.json`
+    pub url: Url,
+}
+
+/// Publisher information, as listed by an aggregator.
+///
+/// Compared to [`crate::model::metadata::Publisher`], the contact details are optional.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ListedPublisherInformation {
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact_details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuing_authority: Option<String>,
+    pub name: String,
+    pub namespace: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_aggregator() {
+        let aggregator: Aggregator = serde_json::from_value(serde_json::json!({
+            "aggregator": {
+                "category": "lister",
+                "name": "Example Lister",
+                "namespace": "https://lister.example.com"
+            },
+            "aggregator_version": "2.0",
+            "canonical_url": "https://lister.example.com/.well-known/csaf-aggregator/aggregator.json",
+            "csaf_providers": [
+                {
+                    "metadata": {
+                        "last_updated": "2025-01-01T00:00:00Z",
+                        "publisher": {
+                            "category": "vendor",
+                            "name": "Vendor A",
+                            "namespace": "https://a.example.com"
+                        },
+                        "role": "csaf_trusted_provider",
+                        "url": "https://a.example.com/.well-known/csaf/provider-metadata.json"
+                    },
+                    "mirrors": [
+                        "https://lister.example.com/.well-known/csaf-aggregator/a/provider-metadata.json"
+                    ]
+                }
+            ],
+            "csaf_publishers": [
+                {
+                    "metadata": {
+                        "last_updated": "2025-01-01T00:00:00Z",
+                        "publisher": {
+                            "category": "vendor",
+                            "name": "Vendor B",
+                            "namespace": "https://b.example.com"
+                        },
+                        "url": "https://b.example.com/.well-known/csaf/provider-metadata.json"
+                    },
+                    "update_interval": "daily"
+                }
+            ],
+            "last_updated": "2025-01-02T00:00:00Z"
+        }))
+        .expect("example must parse");
+
+        assert_eq!(aggregator.aggregator.category, AggregatorCategory::Lister);
+
+        let entries: Vec<_> = aggregator.entries().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0.role, Some(Role::TrustedProvider));
+        assert_eq!(entries[0].1.len(), 1);
+        assert_eq!(entries[1].0.role, None);
+        assert!(entries[1].1.is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use std::str::FromStr;
 use url::Url;
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
@@ -27,7 +28,7 @@ pub struct Feed {
     pub url: Url,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum TlpLabel {
     Unlabeled,
@@ -37,6 +38,27 @@ pub enum TlpLabel {
     Red,
 }
 
+impl FromStr for TlpLabel {
+    type Err = String;
+
+    /// Parse a TLP label, ignoring the case and an optional `TLP:` prefix.
+    ///
+    /// TLP v2 `CLEAR` is considered the same as `WHITE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let label = s.trim().to_ascii_uppercase();
+        let label = label.strip_prefix("TLP:").unwrap_or(&label);
+
+        match label {
+            "UNLABELED" => Ok(Self::Unlabeled),
+            "WHITE" | "CLEAR" => Ok(Self::White),
+            "GREEN" => Ok(Self::Green),
+            "AMBER" => Ok(Self::Amber),
+            "RED" => Ok(Self::Red),
+            _ => Err(format!("unknown TLP label: {s}")),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct Key {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -1,3 +1,4 @@
 //! Data models
+pub mod aggregator;
 pub mod metadata;
 pub(crate) mod store;
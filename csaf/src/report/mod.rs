@@ -2,6 +2,8 @@
 
 mod render;
 
+pub mod provider;
+
 pub use render::*;
 
 use crate::discover::DiscoveredAdvisory;
@@ -14,6 +16,8 @@ use walker_common::utils::url::Urlify;
 pub struct ReportResult<'d> {
     pub total: usize,
     pub duplicates: &'d Duplicates,
+    /// Distributions which failed to load, by their URL
+    pub distribution_errors: &'d BTreeMap<Url, String>,
     pub errors: &'d BTreeMap<DocumentKey, String>,
     pub warnings: &'d BTreeMap<DocumentKey, Vec<Cow<'static, str>>>,
 }
@@ -0,0 +1,935 @@
+//! Checking a provider against the requirements of section 7 of the CSAF standard.
+
+use crate::{
+    discover::DiscoveredAdvisory,
+    metadata::{self, MetadataRetriever, MetadataSource},
+    model::metadata::{ProviderMetadata, Role, TlpLabel},
+    retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor, RetrievingVisitor},
+    rolie::{RolieCategories, RolieService, RolieSource},
+    source::{HttpOptions, HttpSource, HttpSourceError},
+    walker::Walker,
+};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+use tokio::sync::Mutex;
+use url::Url;
+use walker_common::{
+    changes::ChangeSource,
+    fetcher::{self, Fetcher},
+    index::{IndexMismatch, IndexSource},
+    report,
+    retrieve::RetrievalError,
+    utils::openpgp::PublicKey,
+    validate::{ValidationOptions, openpgp::validate_signature_data},
+};
+
+/// A requirement of section 7.1 of the CSAF standard.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Requirement {
+    Filename,
+    Tls,
+    TlpWhite,
+    ProviderMetadata,
+    SecurityTxt,
+    WellKnown,
+    Dns,
+    IndexTxt,
+    ChangesCsv,
+    RolieFeed,
+    RolieService,
+    RolieCategory,
+    Hashes,
+    Signatures,
+    PublicKeys,
+}
+
+impl Requirement {
+    pub const ALL: [Requirement; 15] = [
+        Self::Filename,
+        Self::Tls,
+        Self::TlpWhite,
+        Self::ProviderMetadata,
+        Self::SecurityTxt,
+        Self::WellKnown,
+        Self::Dns,
+        Self::IndexTxt,
+        Self::ChangesCsv,
+        Self::RolieFeed,
+        Self::RolieService,
+        Self::RolieCategory,
+        Self::Hashes,
+        Self::Signatures,
+        Self::PublicKeys,
+    ];
+
+    /// The section of the CSAF standard defining the requirement.
+    pub fn section(&self) -> &'static str {
+        match self {
+            Self::Filename => "7.1.2",
+            Self::Tls => "7.1.3",
+            Self::TlpWhite => "7.1.4",
+            Self::ProviderMetadata => "7.1.7",
+            Self::SecurityTxt => "7.1.8",
+            Self::WellKnown => "7.1.9",
+            Self::Dns => "7.1.10",
+            Self::IndexTxt => "7.1.12",
+            Self::ChangesCsv => "7.1.13",
+            Self::RolieFeed => "7.1.15",
+            Self::RolieService => "7.1.16",
+            Self::RolieCategory => "7.1.17",
+            Self::Hashes => "7.1.18",
+            Self::Signatures => "7.1.19",
+            Self::PublicKeys => "7.1.20",
+        }
+    }
+
+    /// Check if the requirement must be fulfilled by the role.
+    ///
+    /// Requirements which can be fulfilled by alternatives (like the different ways of
+    /// discovering the metadata) are not considered required on their own. They are checked by
+    /// [`ProviderReport::conforms`] instead.
+    pub fn is_required(&self, role: Role) -> bool {
+        match self {
+            Self::Filename | Self::Tls | Self::TlpWhite => true,
+            Self::ProviderMetadata => role != Role::Publisher,
+            Self::Hashes | Self::Signatures | Self::PublicKeys => role == Role::TrustedProvider,
+            _ => false,
+        }
+    }
+}
+
+impl Display for Requirement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Filename => f.write_str("Filename"),
+            Self::Tls => f.write_str("TLS"),
+            Self::TlpWhite => f.write_str("TLP:WHITE"),
+            Self::ProviderMetadata => f.write_str("provider-metadata.json"),
+            Self::SecurityTxt => f.write_str("security.txt"),
+            Self::WellKnown => f.write_str("Well-known URL for provider-metadata.json"),
+            Self::Dns => f.write_str("DNS path"),
+            Self::IndexTxt => f.write_str("index.txt"),
+            Self::ChangesCsv => f.write_str("changes.csv"),
+            Self::RolieFeed => f.write_str("ROLIE feed"),
+            Self::RolieService => f.write_str("ROLIE service document"),
+            Self::RolieCategory => f.write_str("ROLIE category document"),
+            Self::Hashes => f.write_str("Hashes"),
+            Self::Signatures => f.write_str("Signatures"),
+            Self::PublicKeys => f.write_str("Public OpenPGP Key"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// The requirement could not be checked, or does not apply.
+    Skipped,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pass => f.write_str("PASS"),
+            Self::Fail => f.write_str("FAIL"),
+            Self::Skipped => f.write_str("SKIP"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequirementState {
+    /// Number of checks performed
+    pub checked: usize,
+    /// Failed checks
+    pub failures: Vec<String>,
+}
+
+impl RequirementState {
+    pub fn outcome(&self) -> Outcome {
+        if !self.failures.is_empty() {
+            Outcome::Fail
+        } else if self.checked > 0 {
+            Outcome::Pass
+        } else {
+            Outcome::Skipped
+        }
+    }
+}
+
+/// The outcome of checking a provider.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProviderReport {
+    /// The role to check against
+    pub role: Role,
+    pub requirements: BTreeMap<Requirement, RequirementState>,
+    /// Documents which could not be retrieved, and so could not be checked
+    pub document_errors: Vec<String>,
+}
+
+impl ProviderReport {
+    pub fn new(role: Role) -> Self {
+        Self {
+            role,
+            requirements: Default::default(),
+            document_errors: Default::default(),
+        }
+    }
+
+    pub fn pass(&mut self, requirement: Requirement) {
+        self.requirements.entry(requirement).or_default().checked += 1;
+    }
+
+    pub fn fail(&mut self, requirement: Requirement, message: impl Into<String>) {
+        let state = self.requirements.entry(requirement).or_default();
+        state.checked += 1;
+        state.failures.push(message.into());
+    }
+
+    /// Record the outcome of a check.
+    pub fn check(&mut self, requirement: Requirement, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.pass(requirement),
+            Err(message) => self.fail(requirement, message),
+        }
+    }
+
+    pub fn outcome(&self, requirement: Requirement) -> Outcome {
+        self.requirements
+            .get(&requirement)
+            .map(RequirementState::outcome)
+            .unwrap_or(Outcome::Skipped)
+    }
+
+    fn passed(&self, requirement: Requirement) -> bool {
+        self.outcome(requirement) == Outcome::Pass
+    }
+
+    /// Check if the provider conforms to the requirements of its role.
+    ///
+    /// See section 7.2 of the CSAF standard.
+    pub fn conforms(&self) -> bool {
+        let required = Requirement::ALL
+            .iter()
+            .filter(|requirement| requirement.is_required(self.role))
+            .all(|requirement| match requirement {
+                // only applies to TLP:WHITE documents, which a provider doesn't need to publish
+                Requirement::TlpWhite => self.outcome(*requirement) != Outcome::Fail,
+                _ => self.passed(*requirement),
+            });
+
+        if self.role == Role::Publisher {
+            return required;
+        }
+
+        let discovery = self.passed(Requirement::SecurityTxt)
+            || self.passed(Requirement::WellKnown)
+            || self.passed(Requirement::Dns);
+
+        let distribution = (self.passed(Requirement::IndexTxt)
+            && self.passed(Requirement::ChangesCsv))
+            || (self.passed(Requirement::RolieFeed)
+                && self.outcome(Requirement::RolieService) != Outcome::Fail
+                && self.outcome(Requirement::RolieCategory) != Outcome::Fail);
+
+        required && discovery && distribution
+    }
+}
+
+/// Check a provider, given by its base domain or the full URL to its `provider-metadata.json`.
+pub struct ProviderChecker {
+    fetcher: Fetcher,
+    options: ValidationOptions,
+}
+
+impl ProviderChecker {
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher,
+            options: Default::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ValidationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub async fn check(&self, source: &str) -> anyhow::Result<ProviderReport> {
+        let domain = match Url::parse(source) {
+            Ok(url) => url.host_str().map(ToString::to_string),
+            Err(_) => Some(source.to_string()),
+        };
+
+        let metadata = MetadataRetriever::new(source)
+            .approach_full_url(&self.fetcher)
+            .await;
+
+        let mut report = ProviderReport::new(Role::Publisher);
+        let mut discovered = self.check_discovery(&mut report, domain).await;
+
+        let metadata = match metadata {
+            Ok(Some(metadata)) => Some(metadata),
+            Ok(None) => discovered.take(),
+            Err(err) => {
+                report.fail(Requirement::ProviderMetadata, err.to_string());
+                None
+            }
+        };
+
+        let Some(metadata) = metadata else {
+            report.fail(
+                Requirement::ProviderMetadata,
+                "unable to retrieve provider-metadata.json",
+            );
+            return Ok(report);
+        };
+
+        report.role = metadata.role;
+        report.pass(Requirement::ProviderMetadata);
+
+        self.check_metadata(&mut report, &metadata).await;
+        self.check_documents(&mut report, metadata).await?;
+
+        Ok(report)
+    }
+
+    /// Check the different approaches of discovering the metadata.
+    ///
+    /// Returns the metadata of the first successful approach.
+    async fn check_discovery(
+        &self,
+        report: &mut ProviderReport,
+        domain: Option<String>,
+    ) -> Option<ProviderMetadata> {
+        let domain = domain?;
+        let retriever = MetadataRetriever::new(domain);
+
+        let mut result = None;
+
+        for (requirement, outcome) in [
+            (
+                Requirement::WellKnown,
+                retriever.approach_well_known(&self.fetcher).await,
+            ),
+            (
+                Requirement::SecurityTxt,
+                retriever
+                    .approach_security_txt(&self.fetcher, ".well-known/security.txt")
+                    .await,
+            ),
+            (
+                Requirement::Dns,
+                retriever.approach_dns(&self.fetcher).await,
+            ),
+        ] {
+            match outcome {
+                Ok(Some(metadata)) => {
+                    report.pass(requirement);
+                    result.get_or_insert(metadata);
+                }
+                Ok(None) => report.fail(requirement, "not found"),
+                Err(err) => report.fail(requirement, err.to_string()),
+            }
+        }
+
+        result
+    }
+
+    async fn check_metadata(&self, report: &mut ProviderReport, metadata: &ProviderMetadata) {
+        check_tls(report, &metadata.canonical_url);
+
+        // keys
+
+        if metadata.public_openpgp_keys.is_empty() {
+            report.fail(Requirement::PublicKeys, "no keys listed");
+        }
+        for key in &metadata.public_openpgp_keys {
+            check_tls(report, &key.url);
+            let result = match self.fetcher.fetch::<bytes::Bytes>(key.url.clone()).await {
+                Ok(data) => {
+                    walker_common::utils::openpgp::validate_keys(data, key.fingerprint.as_deref())
+                        .map(|_| ())
+                        .map_err(|err| format!("{}: {err}", key.url))
+                }
+                Err(err) => Err(format!("{}: {err}", key.url)),
+            };
+            report.check(Requirement::PublicKeys, result);
+        }
+
+        // distributions
+
+        for distribution in &metadata.distributions {
+            if let Some(url) = &distribution.directory_url {
+                check_tls(report, url);
+
+                let index = match IndexSource::retrieve_optional(&self.fetcher, url).await {
+                    Ok(Some(index)) => {
+                        report.pass(Requirement::IndexTxt);
+                        Some(index)
+                    }
+                    Ok(None) => {
+                        report.fail(Requirement::IndexTxt, format!("{url}: missing index.txt"));
+                        None
+                    }
+                    Err(err) => {
+                        report.fail(Requirement::IndexTxt, format!("{url}: {err}"));
+                        None
+                    }
+                };
+
+                let changes = match ChangeSource::retrieve_optional(&self.fetcher, url).await {
+                    Ok(Some(changes)) => {
+                        report.pass(Requirement::ChangesCsv);
+                        Some(changes)
+                    }
+                    Ok(None) => {
+                        report.fail(
+                            Requirement::ChangesCsv,
+                            format!("{url}: missing changes.csv"),
+                        );
+                        None
+                    }
+                    Err(err) => {
+                        report.fail(Requirement::ChangesCsv, format!("{url}: {err}"));
+                        None
+                    }
+                };
+
+                if let (Some(index), Some(changes)) = (&index, &changes) {
+                    check_index_mismatch(report, url, IndexMismatch::compare(index, changes));
+                }
+            }
+
+            let Some(rolie) = &distribution.rolie else {
+                continue;
+            };
+
+            for feed in &rolie.feeds {
+                check_tls(report, &feed.url);
+                let result = RolieSource::retrieve(&self.fetcher, feed.url.clone()).await;
+                report.check(
+                    Requirement::RolieFeed,
+                    result
+                        .map(|_| ())
+                        .map_err(|err| format!("{}: {err}", feed.url)),
+                );
+            }
+
+            for url in &rolie.services {
+                check_tls(report, url);
+                let result = RolieService::retrieve(&self.fetcher, url.clone()).await;
+                report.check(
+                    Requirement::RolieService,
+                    result.map(|_| ()).map_err(|err| format!("{url}: {err}")),
+                );
+            }
+
+            for url in &rolie.categories {
+                check_tls(report, url);
+                let result = RolieCategories::retrieve(&self.fetcher, url.clone()).await;
+                report.check(
+                    Requirement::RolieCategory,
+                    result.map(|_| ()).map_err(|err| format!("{url}: {err}")),
+                );
+            }
+        }
+    }
+
+    /// Walk all documents, checking the requirements of each document.
+    async fn check_documents(
+        &self,
+        report: &mut ProviderReport,
+        metadata: ProviderMetadata,
+    ) -> anyhow::Result<()> {
+        let role = metadata.role;
+        let source = HttpSource::new(
+            LoadedMetadata(metadata),
+            self.fetcher.clone(),
+            HttpOptions::new(),
+        );
+
+        let checks = DocumentChecks {
+            report: Arc::new(Mutex::new(std::mem::replace(
+                report,
+                ProviderReport::new(role),
+            ))),
+            options: self.options.clone(),
+        };
+
+        let result = Walker::new(source.clone())
+            .walk(RetrievingVisitor::new(source, checks.clone()))
+            .await;
+
+        *report = checks.report.lock().await.clone();
+
+        result.map_err(|err| anyhow::anyhow!("{err}"))
+    }
+}
+
+/// The metadata, as already loaded by the checker.
+#[derive(Debug)]
+struct LoadedMetadata(ProviderMetadata);
+
+#[async_trait]
+impl MetadataSource for LoadedMetadata {
+    async fn load_metadata(&self, _fetcher: &Fetcher) -> Result<ProviderMetadata, metadata::Error> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Record entries which are only listed in one of `index.txt` and `changes.csv`.
+///
+/// Both files must list the same documents (sections 7.1.12 and 7.1.13).
+fn check_index_mismatch(report: &mut ProviderReport, url: &Url, mismatch: IndexMismatch) {
+    for file in mismatch.only_index {
+        report.fail(
+            Requirement::ChangesCsv,
+            format!("{url}: entry only listed in index.txt: {file}"),
+        );
+    }
+    for file in mismatch.only_changes {
+        report.fail(
+            Requirement::IndexTxt,
+            format!("{url}: entry only listed in changes.csv: {file}"),
+        );
+    }
+}
+
+fn check_tls(report: &mut ProviderReport, url: &Url) {
+    if url.scheme() == "https" {
+        report.pass(Requirement::Tls);
+    } else {
+        report.fail(Requirement::Tls, format!("{url}: not using HTTPS"));
+    }
+}
+
+/// Get the expected filename of a document, as defined in section 5.1 of the CSAF standard.
+///
+/// Each run of characters other than `_`, `+`, `-`, `a-z` and `0-9` is replaced by a single `_`.
+pub fn expected_filename(tracking_id: &str) -> String {
+    let mut name = String::with_capacity(tracking_id.len());
+    let mut replacing = false;
+    for c in tracking_id.to_lowercase().chars() {
+        match c {
+            'a'..='z' | '0'..='9' | '_' | '+' | '-' => {
+                name.push(c);
+                replacing = false;
+            }
+            _ if replacing => {}
+            _ => {
+                name.push('_');
+                replacing = true;
+            }
+        }
+    }
+
+    format!("{name}.json")
+}
+
+#[derive(Clone)]
+struct DocumentChecks {
+    report: Arc<Mutex<ProviderReport>>,
+    options: ValidationOptions,
+}
+
+impl DocumentChecks {
+    fn check(&self, report: &mut ProviderReport, advisory: &RetrievedAdvisory, keys: &[PublicKey]) {
+        let url = &advisory.url;
+
+        check_tls(report, url);
+
+        // filename and TLP
+
+        let document: serde_json::Value = match advisory
+            .data
+            .reader()
+            .map_err(serde_json::Error::io)
+            .and_then(serde_json::from_reader)
+        {
+            Ok(document) => document,
+            Err(err) => {
+                report.fail(Requirement::Filename, format!("{url}: {err}"));
+                return;
+            }
+        };
+
+        match document["document"]["tracking"]["id"].as_str() {
+            Some(id) => {
+                let expected = expected_filename(id);
+                let actual = url.path_segments().and_then(|mut s| s.next_back());
+                if actual == Some(expected.as_str()) {
+                    report.pass(Requirement::Filename);
+                } else {
+                    report.fail(
+                        Requirement::Filename,
+                        format!("{url}: expected filename '{expected}'"),
+                    );
+                }
+            }
+            None => report.fail(Requirement::Filename, format!("{url}: missing tracking ID")),
+        }
+
+        if matches!(
+            document["document"]["distribution"]["tlp"]["label"].as_str(),
+            Some("WHITE" | "CLEAR")
+        ) {
+            // we retrieved it without any credentials
+            report.pass(Requirement::TlpWhite);
+        }
+
+        // hashes
+
+        let sha256 = advisory.sha256.as_ref().map(|digest| digest.validate());
+        let sha512 = advisory.sha512.as_ref().map(|digest| digest.validate());
+        report.check(
+            Requirement::Hashes,
+            match (sha256, sha512) {
+                (None, None) => Err(format!("{url}: missing hash")),
+                (Some(Err((expected, actual))), _) | (_, Some(Err((expected, actual)))) => Err(
+                    format!("{url}: digest mismatch (expected: {expected}, actual: {actual})"),
+                ),
+                _ => Ok(()),
+            },
+        );
+
+        // signature
+
+        report.check(
+            Requirement::Signatures,
+            match &advisory.signature {
+                Some(signature) => {
+                    validate_signature_data(&self.options, keys, signature, &advisory.data)
+                        .map_err(|err| format!("{url}: {err}"))
+                }
+                None => Err(format!("{url}: missing signature")),
+            },
+        );
+    }
+}
+
+impl RetrievedVisitor<HttpSource> for DocumentChecks {
+    type Error = Infallible;
+    type Context = Vec<PublicKey>;
+
+    async fn visit_context(
+        &self,
+        context: &RetrievalContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        Ok(context.keys.clone())
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, HttpSource>>,
+    ) -> Result<(), Self::Error> {
+        let mut report = self.report.lock().await;
+        match result {
+            Ok(advisory) => self.check(&mut report, &advisory, context),
+            Err(err) => {
+                let RetrievalError::Source { err, discovered } = err;
+                check_document_error(
+                    &mut report,
+                    &discovered,
+                    error_status(&err),
+                    format!("{}: {err}", discovered.url),
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Record a document which could not be retrieved.
+///
+/// Documents of a TLP:WHITE distribution must be accessible without credentials (section 7.1.4),
+/// so being denied access to them fails that requirement.
+fn check_document_error(
+    report: &mut ProviderReport,
+    discovered: &DiscoveredAdvisory,
+    status: Option<StatusCode>,
+    message: String,
+) {
+    if *discovered.context.tlp_label() == TlpLabel::White
+        && matches!(
+            status,
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+        )
+    {
+        report.fail(Requirement::TlpWhite, message.clone());
+    }
+
+    report.document_errors.push(message);
+}
+
+/// Get the HTTP status code of a failed request, if the server responded.
+fn error_status(err: &HttpSourceError) -> Option<StatusCode> {
+    match err {
+        HttpSourceError::Fetcher(fetcher::Error::Request(err)) => err.status(),
+        _ => None,
+    }
+}
+
+/// Render a provider report as HTML.
+pub fn render_provider_to_html<W: std::io::Write>(
+    out: &mut W,
+    result: &ProviderReport,
+) -> anyhow::Result<()> {
+    report::render(
+        out,
+        "CSAF Provider Report",
+        HtmlProviderReport(result),
+        &Default::default(),
+    )
+}
+
+struct HtmlProviderReport<'r>(&'r ProviderReport);
+
+impl Display for HtmlProviderReport<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let role = match self.0.role {
+            Role::Publisher => "CSAF publisher",
+            Role::Provider => "CSAF provider",
+            Role::TrustedProvider => "CSAF trusted provider",
+        };
+        let conforms = match self.0.conforms() {
+            true => "Yes",
+            false => "No",
+        };
+
+        report::Summary(vec![
+            ("Role", role.to_string()),
+            ("Conforms", conforms.to_string()),
+        ])
+        .fmt(f)?;
+
+        writeln!(
+            f,
+            r#"
+    <table class="table">
+        <thead>
+            <tr>
+                <th scope="col">Section</th>
+                <th scope="col">Requirement</th>
+                <th scope="col">Required</th>
+                <th scope="col">Outcome</th>
+                <th scope="col">Messages</th>
+            </tr>
+        </thead>
+
+        <tbody>
+"#
+        )?;
+
+        for requirement in Requirement::ALL {
+            let outcome = self.0.outcome(requirement);
+            let class = match outcome {
+                Outcome::Pass => "text-bg-success",
+                Outcome::Fail => "text-bg-danger",
+                Outcome::Skipped => "text-bg-light",
+            };
+            let required = match requirement.is_required(self.0.role) {
+                true => "Yes",
+                false => "",
+            };
+
+            writeln!(
+                f,
+                r#"
+            <tr>
+                <td>{section}</td>
+                <td>{requirement}</td>
+                <td>{required}</td>
+                <td><span class="badge {class}">{outcome}</span></td>
+                <td><ul>
+"#,
+                section = requirement.section(),
+                requirement = html_escape::encode_text(&requirement.to_string()),
+            )?;
+
+            for message in self
+                .0
+                .requirements
+                .get(&requirement)
+                .iter()
+                .flat_map(|state| &state.failures)
+            {
+                writeln!(
+                    f,
+                    "<li><code>{}</code></li>",
+                    html_escape::encode_text(message)
+                )?;
+            }
+
+            writeln!(f, "</ul></td></tr>")?;
+        }
+
+        writeln!(f, "</tbody></table>")?;
+
+        if !self.0.document_errors.is_empty() {
+            writeln!(f, "<h2>Document errors</h2><ul>")?;
+            for message in &self.0.document_errors {
+                writeln!(
+                    f,
+                    "<li><code>{}</code></li>",
+                    html_escape::encode_text(message)
+                )?;
+            }
+            writeln!(f, "</ul>")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{discover::DistributionContext, model::metadata::Feed};
+    use bytes::Bytes;
+    use std::time::SystemTime;
+    use walker_common::retrieve::RetrievedData;
+
+    #[test]
+    fn filename() {
+        assert_eq!(expected_filename("CVE-2024-1234"), "cve-2024-1234.json");
+        assert_eq!(expected_filename("RHSA-2024:0001"), "rhsa-2024_0001.json");
+        assert_eq!(expected_filename("Example Co 1.0+"), "example_co_1_0+.json");
+        assert_eq!(expected_filename("FOO--BAR"), "foo--bar.json");
+        assert_eq!(expected_filename("a  b"), "a_b.json");
+        assert_eq!(expected_filename("a_:_b"), "a___b.json");
+        assert_eq!(expected_filename("a.:/b"), "a_b.json");
+        assert_eq!(expected_filename("Foo_Bar"), "foo_bar.json");
+    }
+
+    #[test]
+    fn conformance() {
+        let mut report = ProviderReport::new(Role::Publisher);
+        report.pass(Requirement::Filename);
+        report.pass(Requirement::Tls);
+        report.pass(Requirement::TlpWhite);
+        assert!(report.conforms());
+
+        report.role = Role::Provider;
+        report.pass(Requirement::ProviderMetadata);
+        assert!(!report.conforms());
+
+        report.pass(Requirement::WellKnown);
+        report.fail(Requirement::Dns, "not found");
+        report.pass(Requirement::RolieFeed);
+        assert!(report.conforms());
+
+        report.role = Role::TrustedProvider;
+        assert!(!report.conforms());
+        assert_eq!(report.outcome(Requirement::Hashes), Outcome::Skipped);
+    }
+
+    fn discovered(name: &str, tlp_label: TlpLabel) -> DiscoveredAdvisory {
+        let feed = Url::parse("https://example.com/feed.json").expect("must parse");
+        DiscoveredAdvisory {
+            url: feed.join(name).expect("must join"),
+            context: Arc::new(DistributionContext::Feed(Feed {
+                summary: None,
+                tlp_label,
+                url: feed,
+            })),
+            digest: None,
+            signature: None,
+            modified: SystemTime::UNIX_EPOCH,
+            categories: vec![],
+            page: None,
+            local: None,
+        }
+    }
+
+    #[test]
+    fn tlp_white_not_published() {
+        let checks = DocumentChecks {
+            report: Arc::new(Mutex::new(ProviderReport::new(Role::Publisher))),
+            options: Default::default(),
+        };
+
+        let mut report = ProviderReport::new(Role::Publisher);
+        for (id, label) in [("example-1", "GREEN"), ("example-2", "AMBER")] {
+            let document = serde_json::json!({
+                "document": {
+                    "tracking": { "id": id },
+                    "distribution": { "tlp": { "label": label } },
+                }
+            });
+            let advisory = RetrievedAdvisory {
+                discovered: discovered(&format!("{id}.json"), TlpLabel::Unlabeled),
+                data: RetrievedData::from(Bytes::from(document.to_string())),
+                signature: None,
+                sha256: None,
+                sha512: None,
+                metadata: Default::default(),
+            };
+            checks.check(&mut report, &advisory, &[]);
+        }
+
+        // without any TLP:WHITE documents, the requirement doesn't apply
+        assert_eq!(report.outcome(Requirement::Filename), Outcome::Pass);
+        assert_eq!(report.outcome(Requirement::Tls), Outcome::Pass);
+        assert_eq!(report.outcome(Requirement::TlpWhite), Outcome::Skipped);
+        assert!(report.conforms());
+
+        // other documents requiring credentials are fine
+        check_document_error(
+            &mut report,
+            &discovered("example-3.json", TlpLabel::Green),
+            Some(StatusCode::UNAUTHORIZED),
+            "denied".to_string(),
+        );
+        assert_eq!(report.outcome(Requirement::TlpWhite), Outcome::Skipped);
+        assert!(report.conforms());
+
+        // but TLP:WHITE documents must be accessible
+        check_document_error(
+            &mut report,
+            &discovered("example-4.json", TlpLabel::White),
+            Some(StatusCode::FORBIDDEN),
+            "denied".to_string(),
+        );
+        assert_eq!(report.outcome(Requirement::TlpWhite), Outcome::Fail);
+        assert!(!report.conforms());
+        assert_eq!(report.document_errors, vec!["denied", "denied"]);
+    }
+
+    #[test]
+    fn index_mismatch() {
+        let url = Url::parse("https://example.com/advisories/").unwrap();
+
+        let mut report = ProviderReport::new(Role::Provider);
+        check_index_mismatch(&mut report, &url, IndexMismatch::default());
+        assert_eq!(report.outcome(Requirement::IndexTxt), Outcome::Skipped);
+        assert_eq!(report.outcome(Requirement::ChangesCsv), Outcome::Skipped);
+
+        check_index_mismatch(
+            &mut report,
+            &url,
+            IndexMismatch {
+                only_index: vec!["2023/a.json".into()],
+                only_changes: vec![],
+            },
+        );
+        assert_eq!(report.outcome(Requirement::IndexTxt), Outcome::Skipped);
+        assert_eq!(report.outcome(Requirement::ChangesCsv), Outcome::Fail);
+        assert_eq!(
+            report.requirements[&Requirement::ChangesCsv].failures,
+            vec![
+                "https://example.com/advisories/: entry only listed in index.txt: 2023/a.json"
+                    .to_string()
+            ]
+        );
+    }
+}
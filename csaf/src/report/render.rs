@@ -26,6 +26,27 @@ pub fn render_to_html<W: std::io::Write>(
             result: report,
             base_url: options.base_url,
             source_url: options.source_url,
+            level: 2,
+        },
+        &Default::default(),
+    )?;
+
+    Ok(())
+}
+
+/// Render a report covering multiple providers, with a section for each provider.
+pub fn render_providers_to_html<W: std::io::Write>(
+    out: &mut W,
+    reports: &[(&str, ReportResult)],
+    options: ReportRenderOption,
+) -> anyhow::Result<()> {
+    report::render(
+        out,
+        "CSAF Report",
+        HtmlProvidersReport {
+            reports,
+            base_url: options.base_url,
+            source_url: options.source_url,
         },
         &Default::default(),
     )?;
@@ -38,6 +59,17 @@ pub enum Title {
     Duplicates,
     Warnings,
     Errors,
+    DistributionErrors,
+}
+
+impl Title {
+    /// The subject of the entries listed under this title
+    fn subject(&self) -> &'static str {
+        match self {
+            Self::DistributionErrors => "Distribution",
+            _ => "File",
+        }
+    }
 }
 
 impl Display for Title {
@@ -46,6 +78,7 @@ impl Display for Title {
             Self::Duplicates => f.write_str("Duplicates"),
             Self::Warnings => f.write_str("Warnings"),
             Self::Errors => f.write_str("Errors"),
+            Self::DistributionErrors => f.write_str("Distribution errors"),
         }
     }
 }
@@ -56,6 +89,8 @@ struct HtmlReport<'r> {
     base_url: &'r Option<Url>,
     /// Override source URL
     source_url: &'r Option<Url>,
+    /// The level of the section headings
+    level: usize,
 }
 
 impl HtmlReport<'_> {
@@ -81,7 +116,7 @@ impl HtmlReport<'_> {
         if !self.result.duplicates.duplicates.is_empty() {
             let total: usize = self.result.duplicates.duplicates.values().sum();
 
-            Self::render_table(
+            self.render_table(
                 f,
                 [count],
                 Title::Duplicates,
@@ -97,6 +132,41 @@ impl HtmlReport<'_> {
         Ok(())
     }
 
+    fn render_distribution_errors(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let count = self.result.distribution_errors.len();
+
+        let data = |f: &mut Formatter<'_>| {
+            for (url, v) in self.result.distribution_errors {
+                writeln!(
+                    f,
+                    r#"
+            <tr>
+                <td><a href="{url}" target="_blank" style="white-space: nowrap;">{label}</a></td>
+                <td><code>{v}</code></td>
+            </tr>
+            "#,
+                    url = html_escape::encode_quoted_attribute(url.as_str()),
+                    label = html_escape::encode_text(url.as_str()),
+                    v = html_escape::encode_text(&v),
+                )?;
+            }
+            Ok(())
+        };
+        if count > 0 {
+            self.render_table(
+                f,
+                [count],
+                Title::DistributionErrors,
+                &format!(
+                    "{count} distribution(s) failed to load, their documents are not part of this report",
+                    count = Formatted(count),
+                ),
+                data,
+            )?;
+        }
+        Ok(())
+    }
+
     fn render_errors(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let count = self.result.errors.len();
 
@@ -123,7 +193,7 @@ impl HtmlReport<'_> {
             Ok(())
         };
         if count > 0 {
-            Self::render_table(
+            self.render_table(
                 f,
                 [count],
                 Title::Errors,
@@ -135,6 +205,7 @@ impl HtmlReport<'_> {
     }
 
     fn render_table<F>(
+        &self,
         f: &mut Formatter<'_>,
         count: impl IntoIterator<Item = usize>,
         title: Title,
@@ -144,7 +215,7 @@ impl HtmlReport<'_> {
     where
         F: Fn(&mut Formatter<'_>) -> std::fmt::Result,
     {
-        Self::title(f, title, count)?;
+        self.title(f, title, count)?;
         writeln!(f, "<p>{sub_title}</p>")?;
 
         writeln!(
@@ -153,13 +224,14 @@ impl HtmlReport<'_> {
     <table class="table">
         <thead>
             <tr>
-                <th scope="col">File</th>
+                <th scope="col">{subject}</th>
                 <th scope="col">{title}</th>
             </tr>
         </thead>
 
         <tbody>
-"#
+"#,
+            subject = title.subject(),
         )?;
         data(f)?;
         writeln!(f, "</tbody></table>")?;
@@ -214,7 +286,7 @@ impl HtmlReport<'_> {
             Ok(())
         };
         if total_count > 0 {
-            Self::render_table(
+            self.render_table(
                 f,
                 [file_count, total_count],
                 Title::Warnings,
@@ -252,11 +324,12 @@ impl HtmlReport<'_> {
     }
 
     fn title(
+        &self,
         f: &mut Formatter<'_>,
         title: Title,
         count: impl IntoIterator<Item = usize>,
     ) -> std::fmt::Result {
-        write!(f, "<h2>{title}")?;
+        write!(f, "<h{level}>{title}", level = self.level)?;
 
         for count in count {
             let (class, text) = if count > 0 {
@@ -277,7 +350,7 @@ impl HtmlReport<'_> {
             )?;
         }
 
-        writeln!(f, "</h2>")?;
+        writeln!(f, "</h{level}>", level = self.level)?;
 
         Ok(())
     }
@@ -298,12 +371,52 @@ impl Display for HtmlReport<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         self.render_total(f)?;
         self.render_duplicates(f)?;
+        self.render_distribution_errors(f)?;
         self.render_errors(f)?;
         self.render_warnings(f)?;
         Ok(())
     }
 }
 
+struct HtmlProvidersReport<'r> {
+    reports: &'r [(&'r str, ReportResult<'r>)],
+    base_url: &'r Option<Url>,
+    source_url: &'r Option<Url>,
+}
+
+impl Display for HtmlProvidersReport<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let total = self.reports.iter().map(|(_, result)| result.total).sum();
+
+        let mut summary = Vec::new();
+        summary.push(("Total", Formatted(total).to_string()));
+        summary.push(("Providers", Formatted(self.reports.len()).to_string()));
+        if let Some(source) = self.source_url.as_ref().or(self.base_url.as_ref()) {
+            summary.push(("Source", source.to_string()));
+        }
+        Summary(summary).fmt(f)?;
+
+        for (name, result) in self.reports {
+            writeln!(
+                f,
+                r#"<h2 class="mt-5" id="{id}">{name}</h2>"#,
+                id = html_escape::encode_quoted_attribute(&format!("provider-{name}")),
+                name = html_escape::encode_text(name),
+            )?;
+
+            HtmlReport {
+                result,
+                base_url: self.base_url,
+                source_url: &None,
+                level: 3,
+            }
+            .fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -315,6 +428,7 @@ mod test {
         let details = ReportResult {
             total: 0,
             duplicates: &Default::default(),
+            distribution_errors: &Default::default(),
             errors: &Default::default(),
             warnings: &Default::default(),
         };
@@ -324,6 +438,7 @@ mod test {
             result: &details,
             base_url: &base_url,
             source_url: &None,
+            level: 2,
         };
 
         let (url, _label) = report.link_document(&DocumentKey {
@@ -334,4 +449,64 @@ mod test {
 
         assert_eq!(url, "distribution/2023/cve.json");
     }
+
+    #[test]
+    fn test_providers() {
+        let details = ReportResult {
+            total: 1,
+            duplicates: &Default::default(),
+            distribution_errors: &Default::default(),
+            errors: &Default::default(),
+            warnings: &Default::default(),
+        };
+
+        let mut out = Vec::new();
+        render_providers_to_html(
+            &mut out,
+            &[
+                ("a.example.com", details.clone()),
+                ("b.example.com", details),
+            ],
+            ReportRenderOption {
+                output: Path::new("report.html"),
+                base_url: &None,
+                source_url: &None,
+            },
+        )
+        .expect("must render");
+
+        let out = String::from_utf8(out).expect("must be UTF-8");
+        assert!(out.contains(r#"<h2 class="mt-5" id="provider-a.example.com">a.example.com</h2>"#));
+        assert!(out.contains(r#"<h2 class="mt-5" id="provider-b.example.com">b.example.com</h2>"#));
+    }
+
+    #[test]
+    fn test_distribution_errors() {
+        let distribution_errors = [(
+            Url::parse("https://example.com/feed.json").expect("example value must parse"),
+            "500 Internal Server Error".to_string(),
+        )]
+        .into();
+        let details = ReportResult {
+            total: 0,
+            duplicates: &Default::default(),
+            distribution_errors: &distribution_errors,
+            errors: &Default::default(),
+            warnings: &Default::default(),
+        };
+
+        let out = HtmlReport {
+            result: &details,
+            base_url: &None,
+            source_url: &None,
+            level: 2,
+        }
+        .to_string();
+
+        assert!(out.contains("Distribution errors"));
+        assert!(out.contains(r#"<th scope="col">Distribution</th>"#));
+        assert!(out.contains("<code>500 Internal Server Error</code>"));
+        // no document errors
+        assert!(!out.contains("<h2>Errors"));
+    }
 }
@@ -4,7 +4,6 @@ use crate::{
     discover::{AsDiscovered, DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor},
     source::Source,
 };
-use bytes::Bytes;
 use sha2::{Sha256, Sha512};
 use std::{
     fmt::Debug,
@@ -13,7 +12,9 @@ use std::{
 };
 use url::Url;
 use walker_common::{
-    retrieve::{RetrievalError, RetrievalMetadata, RetrievedDigest, RetrievedDocument},
+    retrieve::{
+        RetrievalError, RetrievalMetadata, RetrievedData, RetrievedDigest, RetrievedDocument,
+    },
     utils::{openpgp::PublicKey, url::Urlify},
     validate::source::{KeySource, KeySourceError},
 };
@@ -25,7 +26,7 @@ pub struct RetrievedAdvisory {
     pub discovered: DiscoveredAdvisory,
 
     /// The advisory data
-    pub data: Bytes,
+    pub data: RetrievedData,
     /// Signature data
     pub signature: Option<String>,
 
@@ -49,7 +50,7 @@ impl Urlify for RetrievedAdvisory {
 }
 
 /// Get a document as [`RetrievedAdvisory`]
-pub trait AsRetrieved: Debug {
+pub trait AsRetrieved: Debug + Send + Sync {
     fn as_retrieved(&self) -> &RetrievedAdvisory;
 }
 
@@ -96,27 +97,36 @@ impl<'c> Deref for RetrievalContext<'c> {
     }
 }
 
-pub trait RetrievedVisitor<S: Source> {
-    type Error: std::fmt::Display + Debug;
-    type Context;
+pub trait RetrievedVisitor<S: Source>: Send + Sync {
+    type Error: std::fmt::Display + Debug + Send;
+    type Context: Send + Sync;
 
     fn visit_context(
         &self,
         context: &RetrievalContext,
-    ) -> impl Future<Output = Result<Self::Context, Self::Error>>;
+    ) -> impl Future<Output = Result<Self::Context, Self::Error>> + Send;
 
     fn visit_advisory(
         &self,
         context: &Self::Context,
         result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>>,
-    ) -> impl Future<Output = Result<(), Self::Error>>;
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Visit an advisory which was not modified since the local copy was retrieved.
+    fn visit_unchanged(
+        &self,
+        _context: &Self::Context,
+        _discovered: DiscoveredAdvisory,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
 }
 
 impl<F, E, Fut, S> RetrievedVisitor<S> for F
 where
-    F: Fn(Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>>) -> Fut,
-    Fut: Future<Output = Result<(), E>>,
-    E: std::fmt::Display + Debug,
+    F: Fn(Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>>) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), E>> + Send,
+    E: std::fmt::Display + Debug + Send,
     S: Source,
 {
     type Error = E;
@@ -220,11 +230,17 @@ where
         context: &Self::Context,
         discovered: DiscoveredAdvisory,
     ) -> Result<(), Self::Error> {
-        let advisory = self
-            .source
-            .load_advisory(discovered.clone())
-            .await
-            .map_err(|err| RetrievalError::Source { err, discovered });
+        let advisory = match self.source.load_advisory(discovered.clone()).await {
+            Ok(Some(advisory)) => Ok(advisory),
+            Ok(None) => {
+                return self
+                    .visitor
+                    .visit_unchanged(context, discovered)
+                    .await
+                    .map_err(Error::Visitor);
+            }
+            Err(err) => Err(RetrievalError::Source { err, discovered }),
+        };
 
         self.visitor
             .visit_advisory(context, advisory)
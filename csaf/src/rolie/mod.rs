@@ -1,8 +1,11 @@
 mod roliefeed;
+mod service;
 
 pub use roliefeed::*;
+pub use service::*;
 
 use crate::source::HttpSourceError;
+use std::collections::{HashSet, VecDeque};
 use time::OffsetDateTime;
 use url::{ParseError, Url};
 use walker_common::fetcher::Json;
@@ -16,6 +19,8 @@ pub enum Error {
     Url(#[from] ParseError),
     #[error("JSON parse error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("Reached the limit of {0} feed pages, not retrieving: {1}")]
+    PageLimit(usize, Url),
 }
 
 impl From<Error> for HttpSourceError {
@@ -24,6 +29,7 @@ impl From<Error> for HttpSourceError {
             Error::Fetcher(err) => Self::Fetcher(err),
             Error::Url(err) => Self::Url(err),
             Error::Json(err) => Self::Json(err),
+            Error::PageLimit(limit, url) => Self::PageLimit(limit, url),
         }
     }
 }
@@ -42,8 +48,19 @@ pub struct SourceFile {
     /// The timestamp of the last change
     #[serde(with = "time::serde::iso8601")]
     pub timestamp: OffsetDateTime,
+
+    /// The categories of the feed and the entry
+    #[serde(default)]
+    pub categories: Vec<Category>,
+
+    /// The URL of the feed page the entry was found on
+    #[serde(default)]
+    pub page: Option<Url>,
 }
 
+/// The default maximum number of pages to retrieve for a single feed
+pub const DEFAULT_PAGE_LIMIT: usize = 1000;
+
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
 pub struct RolieSource {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -52,10 +69,41 @@ pub struct RolieSource {
 
 impl RolieSource {
     pub async fn retrieve(fetcher: &Fetcher, base_url: Url) -> Result<Self, Error> {
+        Self::retrieve_paged(fetcher, base_url, DEFAULT_PAGE_LIMIT).await
+    }
+
+    /// Retrieve a feed, following its paging links.
+    ///
+    /// Paged and archived feeds (RFC 5005) are followed using their `next` and `prev-archive`
+    /// links. Each page is only retrieved once. Reaching a page which would exceed `page_limit`
+    /// fails, as the feed would be incomplete otherwise.
+    pub async fn retrieve_paged(
+        fetcher: &Fetcher,
+        base_url: Url,
+        page_limit: usize,
+    ) -> Result<Self, Error> {
         let mut files = vec![];
-        let Json(result) = fetcher.fetch::<Json<RolieFeed>>(base_url).await?;
-        for entry in result.feed.entry {
-            files.push(find_file(entry));
+        let mut seen = HashSet::new();
+        let mut pages = VecDeque::from([base_url]);
+
+        while let Some(page) = pages.pop_front() {
+            if !seen.insert(page.clone()) {
+                log::warn!("Feed page was already visited, skipping: {page}");
+                continue;
+            }
+
+            if seen.len() > page_limit {
+                return Err(Error::PageLimit(page_limit, page));
+            }
+
+            let Json(result) = fetcher.fetch::<Json<RolieFeed>>(page.clone()).await?;
+            pages.extend(next_pages(&result.feed, &page)?);
+
+            log::debug!("found {} entries on page: {page}", result.feed.entry.len());
+
+            for entry in result.feed.entry {
+                files.push(find_file(&result.feed.category, &page, entry));
+            }
         }
 
         log::debug!("found {:?} files", files.len());
@@ -64,7 +112,16 @@ impl RolieSource {
     }
 }
 
-fn find_file(entry: Entry) -> SourceFile {
+/// Get the URLs of pages linked from this feed page.
+fn next_pages(feed: &Feed, page: &Url) -> Result<Vec<Url>, Error> {
+    feed.link
+        .iter()
+        .filter(|link| matches!(&*link.rel, "next" | "prev-archive"))
+        .map(|link| Ok(page.join(&link.href)?))
+        .collect()
+}
+
+fn find_file(feed_categories: &[Category], page: &Url, entry: Entry) -> SourceFile {
     let mut file = None;
     let mut signature = None;
     let mut digest = None;
@@ -78,50 +135,87 @@ fn find_file(entry: Entry) -> SourceFile {
         }
     }
 
+    let mut categories = feed_categories.to_vec();
+    for category in entry.category {
+        if !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+
     SourceFile {
         file: file.unwrap_or(entry.content.src),
         timestamp: entry.updated,
         signature,
         digest,
+        categories,
+        page: Some(page.clone()),
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        discover::DiscoveredAdvisory,
+        model::metadata::ProviderMetadata,
+        source::{HttpOptions, HttpSource},
+        walker::{DistributionFailures, ErrorPolicy, Walker},
+    };
+    use std::io::{Read, Write};
     use time::macros::datetime;
 
     #[test]
     fn find_by_link() {
-        let result = find_file(Entry {
-            link: vec![
-                Link {
-                    rel: "self".to_string(),
-                    href: "https://example.com/foo/bar/1.json".to_string(),
-                },
-                Link {
-                    rel: "hash".to_string(),
-                    href: "https://example.com/foo/bar/1.json.sha512".to_string(),
+        let feed_categories = vec![Category {
+            scheme: "urn:ietf:params:rolie:category:information-type".to_string(),
+            term: "csaf".to_string(),
+        }];
+
+        let page = Url::parse("https://example.com/foo/feed.json").expect("must parse");
+
+        let result = find_file(
+            &feed_categories,
+            &page,
+            Entry {
+                link: vec![
+                    Link {
+                        rel: "self".to_string(),
+                        href: "https://example.com/foo/bar/1.json".to_string(),
+                    },
+                    Link {
+                        rel: "hash".to_string(),
+                        href: "https://example.com/foo/bar/1.json.sha512".to_string(),
+                    },
+                    Link {
+                        rel: "signature".to_string(),
+                        href: "https://example.com/foo/bar/1.json.asc".to_string(),
+                    },
+                ],
+                format: Format {
+                    schema: "https://docs.oasis-open.org/csaf/csaf/v2.0/csaf_json_schema.json"
+                        .to_string(),
+                    version: "2.0".to_string(),
                 },
-                Link {
-                    rel: "signature".to_string(),
-                    href: "https://example.com/foo/bar/1.json.asc".to_string(),
+                id: "1".to_string(),
+                published: datetime!(2025-01-01 00:00:00 UTC ),
+                title: "Example entry".to_string(),
+                updated: datetime!(2025-01-02 00:00:00 UTC ),
+                content: Content {
+                    src: "https://example.com/foo/bar/1.json".to_string(),
+                    content_type: "application/json".to_string(),
                 },
-            ],
-            format: Format {
-                schema: "https://docs.oasis-open.org/csaf/csaf/v2.0/csaf_json_schema.json"
-                    .to_string(),
-                version: "2.0".to_string(),
-            },
-            id: "1".to_string(),
-            published: datetime!(2025-01-01 00:00:00 UTC ),
-            title: "Example entry".to_string(),
-            updated: datetime!(2025-01-02 00:00:00 UTC ),
-            content: Content {
-                src: "https://example.com/foo/bar/1.json".to_string(),
-                content_type: "application/json".to_string(),
+                category: vec![
+                    Category {
+                        scheme: "urn:ietf:params:rolie:category:information-type".to_string(),
+                        term: "csaf".to_string(),
+                    },
+                    Category {
+                        scheme: "https://example.com/products".to_string(),
+                        term: "Product A".to_string(),
+                    },
+                ],
             },
-        });
+        );
 
         assert_eq!(
             result,
@@ -130,7 +224,141 @@ mod test {
                 digest: Some("https://example.com/foo/bar/1.json.sha512".to_string()),
                 signature: Some("https://example.com/foo/bar/1.json.asc".to_string()),
                 timestamp: datetime!(2025-01-02 00:00:00 UTC ),
+                categories: vec![
+                    Category {
+                        scheme: "urn:ietf:params:rolie:category:information-type".to_string(),
+                        term: "csaf".to_string(),
+                    },
+                    Category {
+                        scheme: "https://example.com/products".to_string(),
+                        term: "Product A".to_string(),
+                    },
+                ],
+                page: Some(page),
             }
         );
     }
+
+    #[test]
+    fn paging_links() {
+        let feed: RolieFeed = serde_json::from_value(serde_json::json!({
+            "feed": {
+                "id": "example-feed",
+                "title": "Example feed",
+                "updated": "2025-01-02T00:00:00Z",
+                "link": [
+                    { "rel": "self", "href": "https://example.com/foo/feed-2.json" },
+                    { "rel": "next", "href": "feed-3.json" },
+                    { "rel": "prev-archive", "href": "https://example.com/archive/feed-1.json" },
+                    { "rel": "alternate", "href": "https://example.com/foo/" }
+                ],
+                "entry": []
+            }
+        }))
+        .expect("example must parse");
+
+        let page = Url::parse("https://example.com/foo/feed-2.json").expect("must parse");
+
+        assert_eq!(
+            next_pages(&feed.feed, &page).expect("must resolve"),
+            vec![
+                Url::parse("https://example.com/foo/feed-3.json").expect("must parse"),
+                Url::parse("https://example.com/archive/feed-1.json").expect("must parse"),
+            ]
+        );
+    }
+
+    /// Serve a feed of `pages` pages, each linking to the next one, returning the URL of the first.
+    fn serve_feed(pages: usize) -> Url {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let base = Url::parse(&format!(
+            "http://{}/",
+            listener.local_addr().expect("must have address")
+        ))
+        .expect("must parse");
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.expect("must accept");
+                let mut request = [0u8; 4096];
+                let len = stream.read(&mut request).expect("must read");
+                let request = String::from_utf8_lossy(&request[..len]);
+                let page = request
+                    .split(' ')
+                    .nth(1)
+                    .and_then(|path| path.strip_prefix("/feed-"))
+                    .and_then(|path| path.strip_suffix(".json"))
+                    .and_then(|page| page.parse::<usize>().ok());
+
+                let response = match page {
+                    Some(page) if (1..=pages).contains(&page) => {
+                        let link = match page < pages {
+                            true => {
+                                serde_json::json!([{ "rel": "next", "href": format!("feed-{}.json", page + 1) }])
+                            }
+                            false => serde_json::json!([]),
+                        };
+                        let body = serde_json::json!({
+                            "feed": {
+                                "id": "example-feed",
+                                "title": "Example feed",
+                                "updated": "2025-01-02T00:00:00Z",
+                                "link": link,
+                                "entry": []
+                            }
+                        })
+                        .to_string();
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                            body.len()
+                        )
+                    }
+                    _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string(),
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        base.join("feed-1.json").expect("must join")
+    }
+
+    #[tokio::test]
+    async fn page_limit() {
+        let feed = serve_feed(3);
+        let fetcher = Fetcher::new(Default::default())
+            .await
+            .expect("must create fetcher");
+
+        RolieSource::retrieve_paged(&fetcher, feed.clone(), 3)
+            .await
+            .expect("must retrieve all pages");
+        let result = RolieSource::retrieve_paged(&fetcher, feed.clone(), 2).await;
+        assert!(matches!(result, Err(Error::PageLimit(2, url)) if url.path() == "/feed-3.json"));
+
+        // a feed exceeding the limit is a failed distribution, so the walk is not complete
+        let metadata: ProviderMetadata = serde_json::from_value(serde_json::json!({
+            "canonical_url": "https://example.com/provider-metadata.json",
+            "distributions": [{
+                "rolie": { "feeds": [{ "tlp_label": "WHITE", "url": feed }] }
+            }],
+            "last_updated": "2024-01-01T00:00:00Z",
+            "metadata_version": "2.0",
+            "publisher": {
+                "category": "vendor",
+                "contact_details": "",
+                "name": "Example",
+                "namespace": "https://example.com",
+            },
+        }))
+        .expect("must parse");
+        let source = HttpSource::new(metadata, fetcher, HttpOptions::new().feed_page_limit(2));
+        let failures = DistributionFailures::new();
+        Walker::new(source)
+            .with_error_policy(ErrorPolicy::Continue)
+            .with_distribution_failures(failures.clone())
+            .walk(|_: DiscoveredAdvisory| async { Ok::<_, &str>(()) })
+            .await
+            .expect("must walk");
+        assert_eq!(failures.get().into_keys().collect::<Vec<_>>(), vec![feed]);
+    }
 }
@@ -49,6 +49,9 @@ pub struct Entry {
     pub updated: OffsetDateTime,
 
     pub content: Content,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub category: Vec<Category>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
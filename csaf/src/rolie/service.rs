@@ -0,0 +1,181 @@
+use super::{Category, Error};
+use crate::model::metadata::{Feed, TlpLabel};
+use url::Url;
+use walker_common::fetcher::{Fetcher, Json};
+
+/// A ROLIE service document
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct RolieService {
+    pub service: Service,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Service {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspace: Vec<Workspace>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Workspace {
+    pub title: String,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collection: Vec<Collection>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Collection {
+    pub title: String,
+
+    /// The URL of the feed
+    pub href: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Categories>,
+}
+
+impl Collection {
+    /// Guess the TLP label from the title of the collection.
+    ///
+    /// The standard recommends a title like `Example Company CSAF feed (TLP:WHITE)`. If no label
+    /// can be found, the collection is considered [`TlpLabel::Unlabeled`].
+    pub fn tlp_label(&self) -> TlpLabel {
+        let title = self.title.to_ascii_uppercase();
+        let Some((_, label)) = title.split_once("TLP:") else {
+            return TlpLabel::Unlabeled;
+        };
+
+        label
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or_default()
+            .parse()
+            .unwrap_or(TlpLabel::Unlabeled)
+    }
+
+    /// Convert the collection into a feed, as it would be listed in the provider metadata.
+    pub fn to_feed(&self, base: &Url) -> Result<Feed, Error> {
+        Ok(Feed {
+            summary: Some(self.title.clone()),
+            tlp_label: self.tlp_label(),
+            url: base.join(&self.href)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Categories {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub category: Vec<Category>,
+}
+
+impl RolieService {
+    pub async fn retrieve(fetcher: &Fetcher, url: Url) -> Result<Self, Error> {
+        let Json(result) = fetcher.fetch::<Json<RolieService>>(url).await?;
+        Ok(result)
+    }
+
+    /// Iterate over all collections of all workspaces.
+    pub fn collections(&self) -> impl Iterator<Item = &Collection> {
+        self.service
+            .workspace
+            .iter()
+            .flat_map(|workspace| workspace.collection.iter())
+    }
+
+    /// Resolve the service document into feeds.
+    ///
+    /// Relative collection URLs are resolved against the URL of the service document.
+    pub fn feeds(&self, base: &Url) -> Result<Vec<Feed>, Error> {
+        self.collections()
+            .map(|collection| collection.to_feed(base))
+            .collect()
+    }
+}
+
+/// A ROLIE category document
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct RolieCategories {
+    pub categories: Categories,
+}
+
+impl RolieCategories {
+    pub async fn retrieve(fetcher: &Fetcher, url: Url) -> Result<Self, Error> {
+        let Json(result) = fetcher.fetch::<Json<RolieCategories>>(url).await?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_service() {
+        let service: RolieService = serde_json::from_value(serde_json::json!({
+            "service": {
+                "workspace": [
+                    {
+                        "title": "Public CSAF feed",
+                        "collection": [
+                            {
+                                "title": "Example Company CSAF feed (TLP:WHITE)",
+                                "href": "feed-tlp-white.json",
+                                "categories": {
+                                    "category": [
+                                        {
+                                            "scheme": "urn:ietf:params:rolie:category:information-type",
+                                            "term": "csaf"
+                                        }
+                                    ]
+                                }
+                            },
+                            {
+                                "title": "Example Company CSAF feed",
+                                "href": "https://example.com/csaf/feed-other.json"
+                            }
+                        ]
+                    }
+                ]
+            }
+        }))
+        .expect("example must parse");
+
+        let base = Url::parse("https://example.com/csaf/service.json").expect("must parse");
+        let feeds = service.feeds(&base).expect("must resolve");
+
+        assert_eq!(
+            feeds,
+            vec![
+                Feed {
+                    summary: Some("Example Company CSAF feed (TLP:WHITE)".to_string()),
+                    tlp_label: TlpLabel::White,
+                    url: Url::parse("https://example.com/csaf/feed-tlp-white.json")
+                        .expect("must parse"),
+                },
+                Feed {
+                    summary: Some("Example Company CSAF feed".to_string()),
+                    tlp_label: TlpLabel::Unlabeled,
+                    url: Url::parse("https://example.com/csaf/feed-other.json")
+                        .expect("must parse"),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_categories() {
+        let categories: RolieCategories = serde_json::from_value(serde_json::json!({
+            "categories": {
+                "category": [
+                    { "scheme": "urn:ietf:params:rolie:category:information-type", "term": "csaf" },
+                    { "scheme": "https://example.com/products", "term": "Product A" }
+                ]
+            }
+        }))
+        .expect("example must parse");
+
+        assert_eq!(categories.categories.category.len(), 2);
+        assert_eq!(categories.categories.category[1].term, "Product A");
+    }
+}
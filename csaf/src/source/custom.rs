@@ -0,0 +1,341 @@
+use super::Source;
+use crate::{
+    discover::{DiscoverConfig, DiscoveredAdvisory, DistributionContext},
+    model::metadata::{Feed, ProviderMetadata},
+    retrieve::RetrievedAdvisory,
+    rolie::Category,
+};
+use futures::future::{BoxFuture, FutureExt};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, LazyLock, RwLock},
+};
+use url::Url;
+use walker_common::{
+    fetcher::Fetcher,
+    utils::openpgp::PublicKey,
+    validate::source::{Key, KeySource, KeySourceError, MapSourceError},
+};
+
+/// An object safe version of a [`Source`], which is a [`KeySource`] as well.
+///
+/// This allows plugging in sources which are not known to this crate, by wrapping them into a
+/// [`CustomSource`]. Any [`Source`] implementing [`KeySource`] implements this trait too, with its
+/// errors converted into [`anyhow::Error`].
+pub trait DynSource: Debug + Send + Sync {
+    fn load_metadata(&self) -> BoxFuture<'_, anyhow::Result<ProviderMetadata>>;
+
+    /// Load the metadata of all providers served by this source.
+    ///
+    /// By default, this is the single provider from [`DynSource::load_metadata`].
+    fn load_providers(&self) -> BoxFuture<'_, anyhow::Result<Vec<ProviderMetadata>>> {
+        async { Ok(vec![self.load_metadata().await?]) }.boxed()
+    }
+
+    /// Resolve a ROLIE service document into the feeds it announces.
+    ///
+    /// By default, no feeds are returned.
+    fn load_service(&self, _url: Url) -> BoxFuture<'_, anyhow::Result<Vec<Feed>>> {
+        async { Ok(vec![]) }.boxed()
+    }
+
+    /// Resolve a ROLIE category document into the categories it lists.
+    ///
+    /// By default, no categories are returned.
+    fn load_categories(&self, _url: Url) -> BoxFuture<'_, anyhow::Result<Vec<Category>>> {
+        async { Ok(vec![]) }.boxed()
+    }
+
+    fn load_index(
+        &self,
+        context: DistributionContext,
+    ) -> BoxFuture<'_, anyhow::Result<Vec<DiscoveredAdvisory>>>;
+
+    fn load_advisory(
+        &self,
+        advisory: DiscoveredAdvisory,
+    ) -> BoxFuture<'_, anyhow::Result<Option<RetrievedAdvisory>>>;
+
+    /// Get the size of an advisory, without loading it.
+    ///
+    /// By default, the size is unknown.
+    fn advisory_size<'a>(
+        &'a self,
+        _advisory: &'a DiscoveredAdvisory,
+    ) -> BoxFuture<'a, anyhow::Result<Option<u64>>> {
+        async { Ok(None) }.boxed()
+    }
+
+    fn load_public_key<'a>(
+        &'a self,
+        key: Key<'a>,
+    ) -> BoxFuture<'a, Result<PublicKey, KeySourceError<anyhow::Error>>>;
+}
+
+impl<S> DynSource for S
+where
+    S: Source + KeySource,
+    <S as walker_common::source::Source>::Error: Into<anyhow::Error>,
+    <S as KeySource>::Error: Into<anyhow::Error>,
+{
+    fn load_metadata(&self) -> BoxFuture<'_, anyhow::Result<ProviderMetadata>> {
+        async { Source::load_metadata(self).await.map_err(Into::into) }.boxed()
+    }
+
+    fn load_providers(&self) -> BoxFuture<'_, anyhow::Result<Vec<ProviderMetadata>>> {
+        async { Source::load_providers(self).await.map_err(Into::into) }.boxed()
+    }
+
+    fn load_service(&self, url: Url) -> BoxFuture<'_, anyhow::Result<Vec<Feed>>> {
+        async { Source::load_service(self, url).await.map_err(Into::into) }.boxed()
+    }
+
+    fn load_categories(&self, url: Url) -> BoxFuture<'_, anyhow::Result<Vec<Category>>> {
+        async { Source::load_categories(self, url).await.map_err(Into::into) }.boxed()
+    }
+
+    fn load_index(
+        &self,
+        context: DistributionContext,
+    ) -> BoxFuture<'_, anyhow::Result<Vec<DiscoveredAdvisory>>> {
+        async { Source::load_index(self, context).await.map_err(Into::into) }.boxed()
+    }
+
+    fn load_advisory(
+        &self,
+        advisory: DiscoveredAdvisory,
+    ) -> BoxFuture<'_, anyhow::Result<Option<RetrievedAdvisory>>> {
+        async {
+            Source::load_advisory(self, advisory)
+                .await
+                .map_err(Into::into)
+        }
+        .boxed()
+    }
+
+    fn advisory_size<'a>(
+        &'a self,
+        advisory: &'a DiscoveredAdvisory,
+    ) -> BoxFuture<'a, anyhow::Result<Option<u64>>> {
+        async {
+            Source::advisory_size(self, advisory)
+                .await
+                .map_err(Into::into)
+        }
+        .boxed()
+    }
+
+    fn load_public_key<'a>(
+        &'a self,
+        key: Key<'a>,
+    ) -> BoxFuture<'a, Result<PublicKey, KeySourceError<anyhow::Error>>> {
+        async {
+            KeySource::load_public_key(self, key)
+                .await
+                .map_source(Into::into)
+        }
+        .boxed()
+    }
+}
+
+/// A source, wrapping any [`DynSource`].
+///
+/// Unlike a [`DynSource`], this implements [`Source`] and [`KeySource`], so it can be used
+/// with the walker and all visitors.
+#[derive(Clone, Debug)]
+pub struct CustomSource(Arc<dyn DynSource>);
+
+impl CustomSource {
+    pub fn new(source: impl DynSource + 'static) -> Self {
+        Self(Arc::new(source))
+    }
+}
+
+impl From<Arc<dyn DynSource>> for CustomSource {
+    fn from(value: Arc<dyn DynSource>) -> Self {
+        Self(value)
+    }
+}
+
+impl walker_common::source::Source for CustomSource {
+    type Error = anyhow::Error;
+    type Retrieved = RetrievedAdvisory;
+}
+
+impl Source for CustomSource {
+    async fn load_metadata(&self) -> Result<ProviderMetadata, Self::Error> {
+        self.0.load_metadata().await
+    }
+
+    async fn load_providers(&self) -> Result<Vec<ProviderMetadata>, Self::Error> {
+        self.0.load_providers().await
+    }
+
+    async fn load_service(&self, url: Url) -> Result<Vec<Feed>, Self::Error> {
+        self.0.load_service(url).await
+    }
+
+    async fn load_categories(&self, url: Url) -> Result<Vec<Category>, Self::Error> {
+        self.0.load_categories(url).await
+    }
+
+    async fn load_index(
+        &self,
+        context: DistributionContext,
+    ) -> Result<Vec<DiscoveredAdvisory>, Self::Error> {
+        self.0.load_index(context).await
+    }
+
+    async fn load_advisory(
+        &self,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<Option<RetrievedAdvisory>, Self::Error> {
+        self.0.load_advisory(advisory).await
+    }
+
+    async fn advisory_size(
+        &self,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<Option<u64>, Self::Error> {
+        self.0.advisory_size(advisory).await
+    }
+}
+
+impl KeySource for CustomSource {
+    type Error = anyhow::Error;
+
+    async fn load_public_key(
+        &self,
+        key: Key<'_>,
+    ) -> Result<PublicKey, KeySourceError<Self::Error>> {
+        self.0.load_public_key(key).await
+    }
+}
+
+/// Creating a source for a URL with a custom scheme.
+pub type SourceFactory =
+    dyn Fn(Url, DiscoverConfig, Fetcher) -> anyhow::Result<CustomSource> + Send + Sync;
+
+static REGISTRY: LazyLock<RwLock<HashMap<String, Arc<SourceFactory>>>> =
+    LazyLock::new(Default::default);
+
+/// The schemes handled by the built-in sources, which cannot be registered.
+const BUILTIN_SCHEMES: [&str; 3] = ["https", "file", "s3"];
+
+/// Register a factory for sources using a custom URL scheme.
+///
+/// Once registered, [`super::SourceDescriptor`] resolves URLs with that scheme into a
+/// [`super::DispatchSource::Custom`]. Registering a scheme again replaces the previous factory.
+/// Built-in schemes (`https`, `file`, and `s3`) cannot be registered.
+pub fn register_scheme<F>(scheme: &str, factory: F) -> anyhow::Result<()>
+where
+    F: Fn(Url, DiscoverConfig, Fetcher) -> anyhow::Result<CustomSource> + Send + Sync + 'static,
+{
+    let scheme = scheme.to_ascii_lowercase();
+    if BUILTIN_SCHEMES.contains(&scheme.as_str()) {
+        anyhow::bail!("The scheme '{scheme}' is handled by a built-in source");
+    }
+
+    REGISTRY
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .insert(scheme, Arc::new(factory));
+
+    Ok(())
+}
+
+/// Get the factory registered for a scheme.
+pub(crate) fn registered_scheme(scheme: &str) -> Option<Arc<SourceFactory>> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(&scheme.to_ascii_lowercase())
+        .cloned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        metadata::MetadataVerification,
+        source::{DispatchSource, SourceDescriptor},
+    };
+    use std::str::FromStr;
+
+    #[derive(Debug)]
+    struct MockSource(Url);
+
+    impl DynSource for MockSource {
+        fn load_metadata(&self) -> BoxFuture<'_, anyhow::Result<ProviderMetadata>> {
+            async { anyhow::bail!("no metadata: {}", self.0) }.boxed()
+        }
+
+        fn load_index(
+            &self,
+            _context: DistributionContext,
+        ) -> BoxFuture<'_, anyhow::Result<Vec<DiscoveredAdvisory>>> {
+            async { Ok(vec![]) }.boxed()
+        }
+
+        fn load_advisory(
+            &self,
+            _advisory: DiscoveredAdvisory,
+        ) -> BoxFuture<'_, anyhow::Result<Option<RetrievedAdvisory>>> {
+            async { Ok(None) }.boxed()
+        }
+
+        fn load_public_key<'a>(
+            &'a self,
+            _key: Key<'a>,
+        ) -> BoxFuture<'a, Result<PublicKey, KeySourceError<anyhow::Error>>> {
+            async { Err(KeySourceError::Source(anyhow::anyhow!("no keys"))) }.boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_scheme() {
+        assert!(register_scheme("https", |_, _, _| unreachable!()).is_err());
+        assert!(register_scheme("s3", |_, _, _| unreachable!()).is_err());
+        assert!(matches!(
+            SourceDescriptor::from_str("s3://us-east-1/bucket/mirror"),
+            Ok(SourceDescriptor::S3(_))
+        ));
+
+        register_scheme("mock", |url, _, _| Ok(CustomSource::new(MockSource(url))))
+            .expect("must register");
+
+        let descriptor = SourceDescriptor::from_str("mock://example.com/csaf").expect("must parse");
+        assert!(
+            matches!(&descriptor, SourceDescriptor::Custom(url) if url.as_str() == "mock://example.com/csaf")
+        );
+
+        let fetcher = Fetcher::new(Default::default())
+            .await
+            .expect("must create fetcher");
+        let source = descriptor
+            .into_source_with_fetcher(
+                DiscoverConfig {
+                    source: "mock://example.com/csaf".to_string(),
+                    since: None,
+                    metadata_verification: MetadataVerification::new(),
+                    retrieval: Default::default(),
+                    feed_page_limit: None,
+                    directory_index: Default::default(),
+                    mirrors: Default::default(),
+                },
+                fetcher,
+            )
+            .expect("must create source");
+        assert!(matches!(source, DispatchSource::Custom(_)));
+
+        // the default of the trait is used, calling the custom implementation
+        let err = Source::load_providers(&source)
+            .await
+            .expect_err("must fail");
+        assert_eq!(err.to_string(), "no metadata: mock://example.com/csaf");
+
+        // still unknown
+        assert!(SourceDescriptor::from_str("gopher://example.com").is_err());
+    }
+}
@@ -1,24 +1,36 @@
 use crate::{
     discover::DiscoverConfig,
-    metadata::MetadataRetriever,
-    source::{DispatchSource, FileOptions, FileSource, HttpOptions, HttpSource},
+    metadata::{AggregatorRetriever, MetadataRetriever},
+    source::{
+        DispatchSource, FileOptions, FileSource, HttpOptions, HttpSource, S3Source,
+        registered_scheme,
+    },
 };
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use fluent_uri::UriRef;
 use std::path::PathBuf;
 use std::str::FromStr;
 use url::Url;
-use walker_common::fetcher::{Fetcher, FetcherOptions};
+use walker_common::{
+    fetcher::{Fetcher, FetcherOptions},
+    store::S3Storage,
+};
 
 /// A descriptor of the source.
 #[derive(Clone, Debug)]
 pub enum SourceDescriptor {
     /// A local file source
     File(PathBuf),
+    /// A source in an S3 compatible bucket: `s3://[<key id>:<access key>@]<region>/<bucket>[/<key>]`
+    S3(S3Storage),
     /// A remote URL source, pointing to the `provider-metadata.json`
     Url(Url),
+    /// A remote URL source, pointing to the `aggregator.json` of an aggregator or lister
+    Aggregator(Url),
     /// A source discovered by the lookup process, given the domain.
     Lookup(String),
+    /// A URL with a scheme handled by a custom source, see [`super::register_scheme`]
+    Custom(Url),
 }
 
 impl FromStr for SourceDescriptor {
@@ -27,8 +39,19 @@ impl FromStr for SourceDescriptor {
     fn from_str(source: &str) -> Result<Self, Self::Err> {
         match UriRef::parse(source) {
             Ok(uri) => match uri.scheme().map(|s| s.as_str()) {
-                Some("https") => Ok(SourceDescriptor::Url(Url::parse(source)?)),
+                Some("https") => {
+                    let url = Url::parse(source)?;
+                    if url.path().ends_with("/aggregator.json") {
+                        Ok(SourceDescriptor::Aggregator(url))
+                    } else {
+                        Ok(SourceDescriptor::Url(url))
+                    }
+                }
                 Some("file") => Ok(SourceDescriptor::File(PathBuf::from(uri.path().as_str()))),
+                Some("s3") => Ok(SourceDescriptor::S3(S3Storage::try_from(source)?)),
+                Some(other) if registered_scheme(other).is_some() => {
+                    Ok(SourceDescriptor::Custom(Url::parse(source)?))
+                }
                 Some(other) => bail!("URLs with scheme '{other}' are not supported"),
                 None => Ok(SourceDescriptor::Lookup(source.to_string())),
             },
@@ -56,20 +79,54 @@ impl SourceDescriptor {
             Self::File(path) => {
                 Ok(FileSource::new(path, FileOptions::new().since(discover.since))?.into())
             }
+            Self::S3(storage) => {
+                Ok(S3Source::new(storage, FileOptions::new().since(discover.since)).into())
+            }
+            other => other.into_source_with_fetcher(discover, Fetcher::new(fetcher).await?),
+        }
+    }
+
+    /// If possible, turn this into a source, using an existing fetcher.
+    ///
+    /// This allows sharing the fetcher, including its rate limits and statistics, between sources.
+    pub fn into_source_with_fetcher(
+        self,
+        discover: DiscoverConfig,
+        fetcher: Fetcher,
+    ) -> anyhow::Result<DispatchSource> {
+        let options = HttpOptions::from(&discover);
+
+        match self {
+            Self::File(path) => {
+                Ok(FileSource::new(path, FileOptions::new().since(discover.since))?.into())
+            }
+            Self::S3(storage) => {
+                Ok(S3Source::new(storage, FileOptions::new().since(discover.since)).into())
+            }
             Self::Url(url) => Ok(HttpSource::new(
-                url,
-                Fetcher::new(fetcher).await?,
-                HttpOptions::new().since(discover.since),
+                MetadataRetriever::new(url).verification(discover.metadata_verification),
+                fetcher,
+                options,
+            )
+            .into()),
+            Self::Aggregator(url) => Ok(HttpSource::new(
+                AggregatorRetriever::new(url)
+                    .mirrors(discover.mirrors)
+                    .verification(discover.metadata_verification),
+                fetcher,
+                options,
+            )
+            .into()),
+            Self::Lookup(source) => Ok(HttpSource::new(
+                MetadataRetriever::new(source).verification(discover.metadata_verification),
+                fetcher,
+                options,
             )
             .into()),
-            Self::Lookup(source) => {
-                let fetcher = Fetcher::new(fetcher).await?;
-                Ok(HttpSource::new(
-                    MetadataRetriever::new(source),
-                    fetcher,
-                    HttpOptions::new().since(discover.since),
-                )
-                .into())
+            Self::Custom(url) => {
+                let factory = registered_scheme(url.scheme())
+                    .ok_or_else(|| anyhow!("No source registered for scheme '{}'", url.scheme()))?;
+                Ok(factory(url, discover, fetcher)?.into())
             }
         }
     }
@@ -1,8 +1,10 @@
 use super::{HttpSourceError, Source};
 use crate::discover::{DiscoveredAdvisory, DistributionContext};
-use crate::model::metadata::ProviderMetadata;
+use crate::model::metadata::{Feed, ProviderMetadata};
 use crate::retrieve::RetrievedAdvisory;
-use crate::source::{FileSource, HttpSource};
+use crate::rolie::Category;
+use crate::source::{CustomSource, FileSource, HttpSource, S3Source};
+use url::Url;
 use walker_common::{
     utils::openpgp::PublicKey,
     validate::source::{Key, KeySource, KeySourceError, MapSourceError},
@@ -10,15 +12,16 @@ use walker_common::{
 
 /// A common source type, dispatching to the known implementations.
 ///
-/// This helps to create implementations which don't need to know the exact type. Unfortunately we
-/// cannot just "box" this, as the [`Source`] needs to implement [`Clone`], which requires [`Sized`],
-/// which prevents us from using `dyn` ("cannot be made into an object").
-///
-/// There may be a better way around this, feel free to send a PR ;-)
+/// This helps to create implementations which don't need to know the exact type. As the
+/// [`Source`] needs to implement [`Clone`], which requires [`Sized`], it cannot be used as `dyn`
+/// ("cannot be made into an object"). Other sources can still be used through a
+/// [`CustomSource`], wrapping the object safe [`super::DynSource`].
 #[derive(Clone, Debug)]
 pub enum DispatchSource {
     File(FileSource),
+    S3(S3Source),
     Http(HttpSource),
+    Custom(CustomSource),
 }
 
 impl From<FileSource> for DispatchSource {
@@ -27,18 +30,34 @@ impl From<FileSource> for DispatchSource {
     }
 }
 
+impl From<S3Source> for DispatchSource {
+    fn from(value: S3Source) -> Self {
+        Self::S3(value)
+    }
+}
+
 impl From<HttpSource> for DispatchSource {
     fn from(value: HttpSource) -> Self {
         Self::Http(value)
     }
 }
 
+impl From<CustomSource> for DispatchSource {
+    fn from(value: CustomSource) -> Self {
+        Self::Custom(value)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DispatchSourceError {
     #[error(transparent)]
     File(anyhow::Error),
     #[error(transparent)]
+    S3(anyhow::Error),
+    #[error(transparent)]
     Http(HttpSourceError),
+    #[error(transparent)]
+    Custom(anyhow::Error),
 }
 
 impl walker_common::source::Source for DispatchSource {
@@ -53,10 +72,81 @@ impl Source for DispatchSource {
                 .load_metadata()
                 .await
                 .map_err(DispatchSourceError::File),
+            Self::S3(source) => source
+                .load_metadata()
+                .await
+                .map_err(DispatchSourceError::S3),
             Self::Http(source) => source
                 .load_metadata()
                 .await
                 .map_err(DispatchSourceError::Http),
+            Self::Custom(source) => source
+                .load_metadata()
+                .await
+                .map_err(DispatchSourceError::Custom),
+        }
+    }
+
+    async fn load_providers(&self) -> Result<Vec<ProviderMetadata>, Self::Error> {
+        match self {
+            Self::File(source) => source
+                .load_providers()
+                .await
+                .map_err(DispatchSourceError::File),
+            Self::S3(source) => source
+                .load_providers()
+                .await
+                .map_err(DispatchSourceError::S3),
+            Self::Http(source) => source
+                .load_providers()
+                .await
+                .map_err(DispatchSourceError::Http),
+            Self::Custom(source) => source
+                .load_providers()
+                .await
+                .map_err(DispatchSourceError::Custom),
+        }
+    }
+
+    async fn load_service(&self, url: Url) -> Result<Vec<Feed>, Self::Error> {
+        match self {
+            Self::File(source) => source
+                .load_service(url)
+                .await
+                .map_err(DispatchSourceError::File),
+            Self::S3(source) => source
+                .load_service(url)
+                .await
+                .map_err(DispatchSourceError::S3),
+            Self::Http(source) => source
+                .load_service(url)
+                .await
+                .map_err(DispatchSourceError::Http),
+            Self::Custom(source) => source
+                .load_service(url)
+                .await
+                .map_err(DispatchSourceError::Custom),
+        }
+    }
+
+    async fn load_categories(&self, url: Url) -> Result<Vec<Category>, Self::Error> {
+        match self {
+            Self::File(source) => source
+                .load_categories(url)
+                .await
+                .map_err(DispatchSourceError::File),
+            Self::S3(source) => source
+                .load_categories(url)
+                .await
+                .map_err(DispatchSourceError::S3),
+            Self::Http(source) => source
+                .load_categories(url)
+                .await
+                .map_err(DispatchSourceError::Http),
+            Self::Custom(source) => source
+                .load_categories(url)
+                .await
+                .map_err(DispatchSourceError::Custom),
         }
     }
 
@@ -69,26 +159,66 @@ impl Source for DispatchSource {
                 .load_index(context)
                 .await
                 .map_err(DispatchSourceError::File),
+            Self::S3(source) => source
+                .load_index(context)
+                .await
+                .map_err(DispatchSourceError::S3),
             Self::Http(source) => source
                 .load_index(context)
                 .await
                 .map_err(DispatchSourceError::Http),
+            Self::Custom(source) => source
+                .load_index(context)
+                .await
+                .map_err(DispatchSourceError::Custom),
         }
     }
 
     async fn load_advisory(
         &self,
         advisory: DiscoveredAdvisory,
-    ) -> Result<RetrievedAdvisory, Self::Error> {
+    ) -> Result<Option<RetrievedAdvisory>, Self::Error> {
         match self {
             Self::File(source) => source
                 .load_advisory(advisory)
                 .await
                 .map_err(DispatchSourceError::File),
+            Self::S3(source) => source
+                .load_advisory(advisory)
+                .await
+                .map_err(DispatchSourceError::S3),
             Self::Http(source) => source
                 .load_advisory(advisory)
                 .await
                 .map_err(DispatchSourceError::Http),
+            Self::Custom(source) => source
+                .load_advisory(advisory)
+                .await
+                .map_err(DispatchSourceError::Custom),
+        }
+    }
+
+    async fn advisory_size(
+        &self,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<Option<u64>, Self::Error> {
+        match self {
+            Self::File(source) => source
+                .advisory_size(advisory)
+                .await
+                .map_err(DispatchSourceError::File),
+            Self::S3(source) => source
+                .advisory_size(advisory)
+                .await
+                .map_err(DispatchSourceError::S3),
+            Self::Http(source) => source
+                .advisory_size(advisory)
+                .await
+                .map_err(DispatchSourceError::Http),
+            Self::Custom(source) => source
+                .advisory_size(advisory)
+                .await
+                .map_err(DispatchSourceError::Custom),
         }
     }
 }
@@ -102,10 +232,12 @@ impl KeySource for DispatchSource {
     ) -> Result<PublicKey, KeySourceError<Self::Error>> {
         match self {
             Self::File(source) => source.load_public_key(key).await,
+            Self::S3(source) => source.load_public_key(key).await,
             Self::Http(source) => source
                 .load_public_key(key)
                 .await
                 .map_source(|err| err.into()),
+            Self::Custom(source) => source.load_public_key(key).await,
         }
     }
 }
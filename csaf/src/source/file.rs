@@ -7,7 +7,7 @@ use crate::{
     },
     retrieve::RetrievedAdvisory,
     source::Source,
-    visitors::store::DIR_METADATA,
+    visitors::store::{ATTR_CATEGORIES, DIR_METADATA, stored_categories},
 };
 use anyhow::{Context, anyhow};
 use bytes::Bytes;
@@ -23,6 +23,7 @@ use walkdir::WalkDir;
 use walker_common::{
     retrieve::RetrievalMetadata,
     source::file::{read_sig_and_digests, to_path},
+    store::{staged_file, stored_attribute},
     utils::{self, openpgp::PublicKey},
     validate::source::{Key, KeySource, KeySourceError},
 };
@@ -119,9 +120,11 @@ impl FileSource {
 
         tokio::task::spawn_blocking(move || {
             for entry in WalkDir::new(path).into_iter().filter_entry(|entry| {
-                // if it's a file but doesn't end with .json -> skip it
+                // if it's a file but doesn't end with .json -> skip it, unless it's left over from
+                // storing a document
                 !entry.file_type().is_file()
                     || entry.file_name().to_string_lossy().ends_with(".json")
+                    || staged_file(entry.path()).is_some()
             }) {
                 if let Err(err) = tx.blocking_send(entry) {
                     // channel closed, abort
@@ -204,6 +207,15 @@ impl Source for FileSource {
                 None => continue,
             };
 
+            if let Some(staged) = staged_file(path) {
+                // the store was interrupted, so the document is missing or still the previous one
+                log::warn!(
+                    "Skipping incomplete entry: {}, will be fetched again",
+                    staged.display()
+                );
+                continue;
+            }
+
             if !name.ends_with(".json") {
                 continue;
             }
@@ -227,6 +239,9 @@ impl Source for FileSource {
                 digest: None,
                 signature: None,
                 context: context.clone(),
+                categories: stored_categories(stored_attribute(path, ATTR_CATEGORIES)),
+                page: None,
+                local: None,
             })
         }
 
@@ -236,7 +251,7 @@ impl Source for FileSource {
     async fn load_advisory(
         &self,
         discovered: DiscoveredAdvisory,
-    ) -> Result<RetrievedAdvisory, Self::Error> {
+    ) -> Result<Option<RetrievedAdvisory>, Self::Error> {
         let path = discovered
             .url
             .to_file_path()
@@ -260,9 +275,9 @@ impl Source for FileSource {
         #[cfg(not(any(target_os = "linux", target_os = "macos")))]
         let etag = None;
 
-        Ok(RetrievedAdvisory {
+        Ok(Some(RetrievedAdvisory {
             discovered,
-            data,
+            data: data.into(),
             signature,
             sha256,
             sha512,
@@ -270,7 +285,19 @@ impl Source for FileSource {
                 last_modification,
                 etag,
             },
-        })
+        }))
+    }
+
+    async fn advisory_size(
+        &self,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<Option<u64>, Self::Error> {
+        let path = advisory
+            .url
+            .to_file_path()
+            .map_err(|()| anyhow!("Unable to convert URL into path: {}", advisory.url))?;
+
+        Ok(Some(tokio::fs::metadata(&path).await?.len()))
     }
 }
 
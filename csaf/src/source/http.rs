@@ -1,12 +1,11 @@
 use crate::{
-    discover::{DiscoveredAdvisory, DistributionContext},
+    discover::{DiscoverConfig, DiscoveredAdvisory, DistributionContext},
     metadata::{self, MetadataSource},
-    model::metadata::ProviderMetadata,
+    model::metadata::{Feed, ProviderMetadata},
     retrieve::RetrievedAdvisory,
-    rolie::{RolieSource, SourceFile},
+    rolie::{Category, DEFAULT_PAGE_LIMIT, RolieCategories, RolieService, RolieSource, SourceFile},
     source::Source,
 };
-use bytes::{BufMut, Bytes, BytesMut};
 use digest::Digest;
 use futures::try_join;
 use reqwest::Response;
@@ -17,7 +16,11 @@ use url::{ParseError, Url};
 use walker_common::{
     changes::{self, ChangeEntry, ChangeSource},
     fetcher::{self, DataProcessor, Fetcher},
-    retrieve::{RetrievalMetadata, RetrievedDigest, RetrievingDigest},
+    index::{self, DirectoryIndex, IndexMismatch, IndexSource},
+    retrieve::{
+        RetrievalMetadata, RetrievalOptions, RetrievedData, RetrievedDigest, RetrievingData,
+        RetrievingDigest,
+    },
     utils::openpgp::PublicKey,
     validate::source::{Key, KeySource, KeySourceError},
 };
@@ -26,6 +29,12 @@ use walker_common::{
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct HttpOptions {
     pub since: Option<SystemTime>,
+    /// The maximum number of pages to retrieve for a single ROLIE feed
+    pub feed_page_limit: Option<usize>,
+    /// How to use the `index.txt` file of directory distributions
+    pub directory_index: DirectoryIndex,
+    /// How to retrieve the content of documents
+    pub retrieval: RetrievalOptions,
 }
 
 impl HttpOptions {
@@ -37,6 +46,31 @@ impl HttpOptions {
         self.since = since.into();
         self
     }
+
+    pub fn feed_page_limit(mut self, feed_page_limit: impl Into<Option<usize>>) -> Self {
+        self.feed_page_limit = feed_page_limit.into();
+        self
+    }
+
+    pub fn directory_index(mut self, directory_index: DirectoryIndex) -> Self {
+        self.directory_index = directory_index;
+        self
+    }
+
+    pub fn retrieval(mut self, retrieval: RetrievalOptions) -> Self {
+        self.retrieval = retrieval;
+        self
+    }
+}
+
+impl From<&DiscoverConfig> for HttpOptions {
+    fn from(value: &DiscoverConfig) -> Self {
+        Self::new()
+            .since(value.since)
+            .feed_page_limit(value.feed_page_limit)
+            .directory_index(value.directory_index)
+            .retrieval(value.retrieval.clone())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -58,6 +92,139 @@ impl HttpSource {
             options,
         }
     }
+
+    /// The fetcher used by this source.
+    pub fn fetcher(&self) -> &Fetcher {
+        &self.fetcher
+    }
+}
+
+impl HttpSource {
+    /// Load the entries of a directory distribution, along with their change timestamp.
+    ///
+    /// Entries only known from the `index.txt` file have no timestamp.
+    async fn load_directory_entries(
+        &self,
+        base: &Url,
+    ) -> Result<Vec<(String, Option<OffsetDateTime>)>, HttpSourceError> {
+        let from_changes = |changes: ChangeSource| {
+            changes
+                .entries
+                .into_iter()
+                .map(|ChangeEntry { file, timestamp }| (file, Some(timestamp)))
+                .collect::<Vec<_>>()
+        };
+
+        match self.options.directory_index {
+            DirectoryIndex::Changes => Ok(from_changes(
+                ChangeSource::retrieve(&self.fetcher, base).await?,
+            )),
+            DirectoryIndex::Fallback => {
+                match ChangeSource::retrieve_optional(&self.fetcher, base).await? {
+                    Some(changes) => Ok(from_changes(changes)),
+                    None => {
+                        log::warn!("Missing 'changes.csv', falling back to 'index.txt': {base}");
+                        let index = IndexSource::retrieve(&self.fetcher, base).await?;
+                        Ok(index.entries.into_iter().map(|file| (file, None)).collect())
+                    }
+                }
+            }
+            DirectoryIndex::Union => {
+                let (changes, index) = try_join!(
+                    async {
+                        ChangeSource::retrieve_optional(&self.fetcher, base)
+                            .await
+                            .map_err(HttpSourceError::from)
+                    },
+                    async {
+                        IndexSource::retrieve_optional(&self.fetcher, base)
+                            .await
+                            .map_err(HttpSourceError::from)
+                    },
+                )?;
+
+                match (changes, index) {
+                    (Some(changes), Some(index)) => {
+                        let mismatch = IndexMismatch::compare(&index, &changes);
+                        for file in &mismatch.only_index {
+                            log::warn!("Entry only listed in 'index.txt' ({base}): {file}");
+                        }
+                        for file in &mismatch.only_changes {
+                            log::warn!("Entry only listed in 'changes.csv' ({base}): {file}");
+                        }
+
+                        let mut result = from_changes(changes);
+                        result.extend(mismatch.only_index.into_iter().map(|file| (file, None)));
+                        Ok(result)
+                    }
+                    (Some(changes), None) => {
+                        log::warn!("Missing 'index.txt': {base}");
+                        Ok(from_changes(changes))
+                    }
+                    (None, Some(index)) => {
+                        log::warn!("Missing 'changes.csv': {base}");
+                        Ok(index.entries.into_iter().map(|file| (file, None)).collect())
+                    }
+                    (None, None) => {
+                        log::warn!("Missing both 'changes.csv' and 'index.txt': {base}");
+                        Ok(vec![])
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fetch the signature and the digest files of an advisory.
+    async fn load_sidecars(
+        &self,
+        discovered: &DiscoveredAdvisory,
+    ) -> Result<(Option<String>, Option<String>, Option<String>), fetcher::Error> {
+        try_join!(
+            async {
+                // If we have a signature source, use it. Otherwise, guess.
+                match discovered.signature.clone() {
+                    Some(signature) => self.fetcher.fetch::<Option<String>>(signature).await,
+                    None => {
+                        self.fetcher
+                            .fetch::<Option<String>>(format!("{url}.asc", url = discovered.url))
+                            .await
+                    }
+                }
+            },
+            async {
+                match discovered.digest.clone() {
+                    Some(digest) if digest.as_str().ends_with(".sha256") => {
+                        self.fetcher.fetch::<Option<String>>(digest).await
+                    }
+                    Some(_) => Ok(None),
+                    None => {
+                        self.fetcher
+                            .fetch::<Option<String>>(format!("{url}.sha256", url = discovered.url))
+                            .await
+                    }
+                }
+            },
+            async {
+                match discovered.digest.clone() {
+                    Some(digest) if digest.as_str().ends_with(".sha512") => {
+                        self.fetcher.fetch::<Option<String>>(digest).await
+                    }
+                    Some(_) => Ok(None),
+                    None => {
+                        self.fetcher
+                            .fetch::<Option<String>>(format!("{url}.sha512", url = discovered.url))
+                            .await
+                    }
+                }
+            },
+        )
+    }
+}
+
+/// Get the expected digest from the content of a digest file.
+fn expected_digest(content: Option<String>) -> Option<String> {
+    // take the first "word" from the line
+    content.and_then(|expected| expected.split(' ').next().map(ToString::to_string))
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -72,6 +239,19 @@ pub enum HttpSourceError {
     Csv(#[from] csv::Error),
     #[error("JSON parse error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Reached the limit of {0} feed pages, not retrieving: {1}")]
+    PageLimit(usize, Url),
+}
+
+impl From<index::Error> for HttpSourceError {
+    fn from(value: index::Error) -> Self {
+        match value {
+            index::Error::Fetcher(err) => Self::Fetcher(err),
+            index::Error::Url(err) => Self::Url(err),
+        }
+    }
 }
 
 impl From<changes::Error> for HttpSourceError {
@@ -94,6 +274,20 @@ impl Source for HttpSource {
         Ok(self.metadata_source.load_metadata(&self.fetcher).await?)
     }
 
+    async fn load_providers(&self) -> Result<Vec<ProviderMetadata>, Self::Error> {
+        Ok(self.metadata_source.load_providers(&self.fetcher).await?)
+    }
+
+    async fn load_service(&self, url: Url) -> Result<Vec<Feed>, Self::Error> {
+        let service = RolieService::retrieve(&self.fetcher, url.clone()).await?;
+        Ok(service.feeds(&url)?)
+    }
+
+    async fn load_categories(&self, url: Url) -> Result<Vec<Category>, Self::Error> {
+        let categories = RolieCategories::retrieve(&self.fetcher, url).await?;
+        Ok(categories.categories.category)
+    }
+
     async fn load_index(
         &self,
         context: DistributionContext,
@@ -109,6 +303,9 @@ impl Source for HttpSource {
                     digest: _,
                     signature: _,
                     modified,
+                    categories: _,
+                    page: _,
+                    local: _,
                 }),
                 Some(since),
             ) => modified >= since,
@@ -126,13 +323,13 @@ impl Source for HttpSource {
                     Url::parse(&format!("{}{s}", base))
                 };
 
-                let changes = ChangeSource::retrieve(&self.fetcher, &base.clone()).await?;
+                let entries = self.load_directory_entries(base).await?;
 
-                Ok(changes
-                    .entries
+                Ok(entries
                     .into_iter()
-                    .map(|ChangeEntry { file, timestamp }| {
-                        let modified = timestamp.into();
+                    .map(|(file, timestamp)| {
+                        // without change information, we must assume it was changed
+                        let modified = timestamp.map(Into::into).unwrap_or_else(SystemTime::now);
                         let url = join_url(&file)?;
 
                         Ok::<_, ParseError>(DiscoveredAdvisory {
@@ -141,6 +338,9 @@ impl Source for HttpSource {
                             modified,
                             signature: None,
                             digest: None,
+                            categories: vec![],
+                            page: None,
+                            local: None,
                         })
                     })
                     .filter(since_filter)
@@ -148,7 +348,12 @@ impl Source for HttpSource {
             }
 
             DistributionContext::Feed(feed) => {
-                let source_files = RolieSource::retrieve(&self.fetcher, feed.clone()).await?;
+                let source_files = RolieSource::retrieve_paged(
+                    &self.fetcher,
+                    feed.url.clone(),
+                    self.options.feed_page_limit.unwrap_or(DEFAULT_PAGE_LIMIT),
+                )
+                .await?;
                 Ok(source_files
                     .files
                     .into_iter()
@@ -158,6 +363,8 @@ impl Source for HttpSource {
                              timestamp,
                              digest,
                              signature,
+                             categories,
+                             page,
                          }| {
                             let modified = timestamp.into();
                             let url = Url::parse(&file)?;
@@ -172,6 +379,9 @@ impl Source for HttpSource {
                                 digest,
                                 signature,
                                 modified,
+                                categories,
+                                page,
+                                local: None,
                             })
                         },
                     )
@@ -184,76 +394,77 @@ impl Source for HttpSource {
     async fn load_advisory(
         &self,
         discovered: DiscoveredAdvisory,
-    ) -> Result<RetrievedAdvisory, Self::Error> {
-        let (signature, sha256, sha512) = try_join!(
-            async {
-                // If we have a signature source, use it. Otherwise, guess.
-                match discovered.signature.clone() {
-                    Some(signature) => self.fetcher.fetch::<Option<String>>(signature).await,
-                    None => {
-                        self.fetcher
-                            .fetch::<Option<String>>(format!("{url}.asc", url = discovered.url))
-                            .await
-                    }
-                }
-            },
-            async {
-                match discovered.digest.clone() {
-                    Some(digest) if digest.as_str().ends_with(".sha256") => {
-                        self.fetcher.fetch::<Option<String>>(digest).await
-                    }
-                    Some(_) => Ok(None),
-                    None => {
-                        self.fetcher
-                            .fetch::<Option<String>>(format!("{url}.sha256", url = discovered.url))
-                            .await
-                    }
-                }
-            },
-            async {
-                match discovered.digest.clone() {
-                    Some(digest) if digest.as_str().ends_with(".sha512") => {
-                        self.fetcher.fetch::<Option<String>>(digest).await
-                    }
-                    Some(_) => Ok(None),
-                    None => {
-                        self.fetcher
-                            .fetch::<Option<String>>(format!("{url}.sha512", url = discovered.url))
-                            .await
-                    }
-                }
-            },
-        )?;
+    ) -> Result<Option<RetrievedAdvisory>, Self::Error> {
+        let Some(local) = &discovered.local else {
+            let (signature, sha256, sha512) = self.load_sidecars(&discovered).await?;
+
+            let processor = FetchingRetrievedAdvisory {
+                sha256: expected_digest(sha256).map(|expected| RetrievingDigest {
+                    expected,
+                    current: Sha256::new(),
+                }),
+                sha512: expected_digest(sha512).map(|expected| RetrievingDigest {
+                    expected,
+                    current: Sha512::new(),
+                }),
+                options: self.options.retrieval.clone(),
+            };
+
+            let advisory = self
+                .fetcher
+                .fetch_processed(discovered.url.clone(), processor)
+                .await??;
+
+            return Ok(Some(advisory.into_retrieved(discovered, signature)));
+        };
 
-        let sha256 = sha256
-            // take the first "word" from the line
-            .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
-            .map(|expected| RetrievingDigest {
-                expected,
+        // We only know which digests to expect once we have fetched the digest files. Which we
+        // only want to do when the document was modified. So we calculate both digests, and
+        // drop the ones without a digest file afterward.
+        let processor = FetchingRetrievedAdvisory {
+            sha256: Some(RetrievingDigest {
+                expected: Default::default(),
                 current: Sha256::new(),
-            });
-        let sha512 = sha512
-            // take the first "word" from the line
-            .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
-            .map(|expected| RetrievingDigest {
-                expected,
+            }),
+            sha512: Some(RetrievingDigest {
+                expected: Default::default(),
                 current: Sha512::new(),
-            });
+            }),
+            options: self.options.retrieval.clone(),
+        };
 
-        let advisory = self
+        let Some(advisory) = self
             .fetcher
-            .fetch_processed(
-                discovered.url.clone(),
-                FetchingRetrievedAdvisory { sha256, sha512 },
-            )
-            .await?;
+            .fetch_processed_conditional(discovered.url.clone(), processor, local)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let mut advisory = advisory?;
+
+        let (signature, sha256, sha512) = self.load_sidecars(&discovered).await?;
+        advisory.sha256 = advisory
+            .sha256
+            .zip(expected_digest(sha256))
+            .map(|(digest, expected)| RetrievedDigest { expected, ..digest });
+        advisory.sha512 = advisory
+            .sha512
+            .zip(expected_digest(sha512))
+            .map(|(digest, expected)| RetrievedDigest { expected, ..digest });
+
+        Ok(Some(advisory.into_retrieved(discovered, signature)))
+    }
 
-        Ok(advisory.into_retrieved(discovered, signature))
+    async fn advisory_size(
+        &self,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<Option<u64>, Self::Error> {
+        Ok(self.fetcher.content_length(advisory.url.clone()).await?)
     }
 }
 
 pub struct FetchedRetrievedAdvisory {
-    data: Bytes,
+    data: RetrievedData,
     sha256: Option<RetrievedDigest<Sha256>>,
     sha512: Option<RetrievedDigest<Sha512>>,
     metadata: RetrievalMetadata,
@@ -279,15 +490,19 @@ impl FetchedRetrievedAdvisory {
 pub struct FetchingRetrievedAdvisory {
     pub sha256: Option<RetrievingDigest<Sha256>>,
     pub sha512: Option<RetrievingDigest<Sha512>>,
+    pub options: RetrievalOptions,
 }
 
 impl DataProcessor for FetchingRetrievedAdvisory {
-    type Type = FetchedRetrievedAdvisory;
+    type Type = Result<FetchedRetrievedAdvisory, std::io::Error>;
 
     async fn process(&self, response: Response) -> Result<Self::Type, reqwest::Error> {
         let mut response = response.error_for_status()?;
 
-        let mut data = BytesMut::new();
+        let mut data = match RetrievingData::new(&self.options) {
+            Ok(data) => data,
+            Err(err) => return Ok(Err(err)),
+        };
         let mut sha256 = self.sha256.clone();
         let mut sha512 = self.sha512.clone();
 
@@ -298,7 +513,9 @@ impl DataProcessor for FetchingRetrievedAdvisory {
             if let Some(d) = &mut sha512 {
                 d.update(&chunk);
             }
-            data.put(chunk);
+            if let Err(err) = data.write(&chunk).await {
+                return Ok(Err(err));
+            }
         }
 
         let etag = response
@@ -313,15 +530,20 @@ impl DataProcessor for FetchingRetrievedAdvisory {
             .and_then(|s| s.to_str().ok())
             .and_then(|s| OffsetDateTime::parse(s, &Rfc2822).ok());
 
-        Ok(FetchedRetrievedAdvisory {
-            data: data.freeze(),
+        let data = match data.finish().await {
+            Ok(data) => data,
+            Err(err) => return Ok(Err(err)),
+        };
+
+        Ok(Ok(FetchedRetrievedAdvisory {
+            data,
             sha256: sha256.map(|d| d.into()),
             sha512: sha512.map(|d| d.into()),
             metadata: RetrievalMetadata {
                 last_modification,
                 etag,
             },
-        })
+        }))
     }
 }
 
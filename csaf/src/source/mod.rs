@@ -1,36 +1,87 @@
 //! Sources
 
+mod custom;
 mod descriptor;
 mod dispatch;
 mod file;
 mod http;
+mod s3;
 
+pub use custom::*;
 pub use descriptor::*;
 pub use dispatch::*;
 pub use file::*;
 pub use http::*;
+pub use s3::*;
 
 use crate::{
     discover::{DiscoverConfig, DiscoveredAdvisory, DistributionContext},
-    model::metadata::ProviderMetadata,
+    model::metadata::{Feed, ProviderMetadata},
     retrieve::RetrievedAdvisory,
+    rolie::Category,
 };
 use std::{fmt::Debug, future::Future, str::FromStr};
+use url::Url;
 use walker_common::fetcher::FetcherOptions;
 
 /// A source of CSAF documents
-pub trait Source: walker_common::source::Source + Clone + Debug {
-    fn load_metadata(&self) -> impl Future<Output = Result<ProviderMetadata, Self::Error>>;
+pub trait Source: walker_common::source::Source + Clone + Debug + Send + Sync {
+    fn load_metadata(&self) -> impl Future<Output = Result<ProviderMetadata, Self::Error>> + Send;
+
+    /// Load the metadata of all providers served by this source.
+    ///
+    /// By default, this is the single provider from [`Source::load_metadata`]. A source
+    /// representing an aggregator will return all of its listed providers.
+    fn load_providers(
+        &self,
+    ) -> impl Future<Output = Result<Vec<ProviderMetadata>, Self::Error>> + Send {
+        async { Ok(vec![self.load_metadata().await?]) }
+    }
+
+    /// Resolve a ROLIE service document into the feeds it announces.
+    ///
+    /// By default, no feeds are returned. This is the case for sources which already contain
+    /// the resolved feeds in their metadata, like the [`FileSource`].
+    fn load_service(
+        &self,
+        _url: Url,
+    ) -> impl Future<Output = Result<Vec<Feed>, Self::Error>> + Send {
+        async { Ok(vec![]) }
+    }
+
+    /// Resolve a ROLIE category document into the categories it lists.
+    ///
+    /// By default, no categories are returned.
+    fn load_categories(
+        &self,
+        _url: Url,
+    ) -> impl Future<Output = Result<Vec<Category>, Self::Error>> + Send {
+        async { Ok(vec![]) }
+    }
 
     fn load_index(
         &self,
         context: DistributionContext,
-    ) -> impl Future<Output = Result<Vec<DiscoveredAdvisory>, Self::Error>>;
+    ) -> impl Future<Output = Result<Vec<DiscoveredAdvisory>, Self::Error>> + Send;
 
+    /// Load an advisory.
+    ///
+    /// If the advisory has a known local copy (see [`DiscoveredAdvisory::local`]), and the source
+    /// can tell that the advisory was not modified since, it returns `Ok(None)`.
     fn load_advisory(
         &self,
         advisory: DiscoveredAdvisory,
-    ) -> impl Future<Output = Result<RetrievedAdvisory, Self::Error>>;
+    ) -> impl Future<Output = Result<Option<RetrievedAdvisory>, Self::Error>> + Send;
+
+    /// Get the size of an advisory, without loading it.
+    ///
+    /// By default, the size is unknown.
+    fn advisory_size(
+        &self,
+        _advisory: &DiscoveredAdvisory,
+    ) -> impl Future<Output = Result<Option<u64>, Self::Error>> + Send {
+        async { Ok(None) }
+    }
 }
 
 /// A common way to create a new CSAF source.
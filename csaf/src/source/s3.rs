@@ -0,0 +1,180 @@
+use crate::{
+    discover::{DiscoveredAdvisory, DistributionContext},
+    model::{
+        metadata::{self, ProviderMetadata},
+        store::distribution_name,
+    },
+    retrieve::RetrievedAdvisory,
+    source::{FileOptions, Source},
+    visitors::store::{ATTR_CATEGORIES, DIR_METADATA, stored_categories},
+};
+use anyhow::{Context, anyhow};
+use std::sync::Arc;
+use url::Url;
+use walker_common::{
+    source::s3::{head_objects, read_document, scan_keys},
+    store::S3Storage,
+    utils::{self, openpgp::PublicKey},
+    validate::source::{Key, KeySource, KeySourceError},
+};
+
+/// A source reading from an S3 compatible bucket, written by the
+/// [`crate::visitors::store::StoreVisitor`].
+///
+/// Documents use URLs of the form `s3://<region>/<bucket>/<key>`. The modification timestamp of
+/// a document is the one stored with it, falling back to the time the object was stored.
+#[derive(Clone, Debug)]
+pub struct S3Source {
+    base: S3Storage,
+    options: FileOptions,
+}
+
+impl S3Source {
+    pub fn new(base: S3Storage, options: impl Into<Option<FileOptions>>) -> Self {
+        Self {
+            base,
+            options: options.into().unwrap_or_default(),
+        }
+    }
+
+    /// The storage a URL of this source points to.
+    fn storage(&self, url: &Url) -> anyhow::Result<S3Storage> {
+        self.base
+            .from_url(url)
+            .ok_or_else(|| anyhow!("URL is not part of the source: {url}"))
+    }
+}
+
+impl walker_common::source::Source for S3Source {
+    type Error = anyhow::Error;
+    type Retrieved = RetrievedAdvisory;
+}
+
+impl Source for S3Source {
+    async fn load_metadata(&self) -> Result<ProviderMetadata, Self::Error> {
+        let storage = self.base.join(DIR_METADATA).join("provider-metadata.json");
+        let data = storage
+            .read_optional()
+            .await?
+            .ok_or_else(|| anyhow!("Failed to find provider metadata: {storage}"))?;
+
+        let mut metadata: ProviderMetadata =
+            serde_json::from_slice(&data).context("Failed to read stored provider metadata")?;
+
+        metadata.public_openpgp_keys = scan_keys(&self.base.join(DIR_METADATA).join("keys"))
+            .await?
+            .into_iter()
+            .map(|(fingerprint, url)| metadata::Key {
+                fingerprint: Some(fingerprint),
+                url,
+            })
+            .collect();
+
+        for dist in &mut metadata.distributions {
+            if let Some(directory_url) = &dist.directory_url {
+                dist.directory_url = Some(
+                    self.base
+                        .join(&distribution_name(directory_url.as_str()))
+                        .directory_url(),
+                );
+            }
+
+            if let Some(rolie) = &mut dist.rolie {
+                for feed in &mut rolie.feeds {
+                    feed.url = self
+                        .base
+                        .join(&distribution_name(feed.url.as_str()))
+                        .directory_url();
+                }
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    async fn load_index(
+        &self,
+        context: DistributionContext,
+    ) -> Result<Vec<DiscoveredAdvisory>, Self::Error> {
+        log::info!("Loading index - since: {:?}", self.options.since);
+
+        let storage = self.storage(context.url())?;
+        let context = Arc::new(context);
+
+        let objects = storage
+            .list()
+            .await?
+            .into_iter()
+            .filter(|object| object.storage.key().ends_with(".json"));
+
+        let mut result = vec![];
+
+        for (object, head) in head_objects(objects).await? {
+            let modified = head.modified();
+
+            if let Some(since) = self.options.since {
+                if modified < since {
+                    log::debug!("Skipping object due to modification constraint: {modified:?}");
+                    continue;
+                }
+            }
+
+            result.push(DiscoveredAdvisory {
+                url: object.storage.url(),
+                modified,
+                digest: None,
+                signature: None,
+                context: context.clone(),
+                categories: stored_categories(head.attribute(ATTR_CATEGORIES)),
+                page: None,
+                local: None,
+            })
+        }
+
+        Ok(result)
+    }
+
+    async fn load_advisory(
+        &self,
+        discovered: DiscoveredAdvisory,
+    ) -> Result<Option<RetrievedAdvisory>, Self::Error> {
+        let storage = self.storage(&discovered.url)?;
+
+        let Some(document) = read_document(&storage, discovered.local.as_ref()).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(RetrievedAdvisory {
+            discovered,
+            data: document.data.into(),
+            signature: document.signature,
+            sha256: document.sha256,
+            sha512: document.sha512,
+            metadata: document.metadata,
+        }))
+    }
+
+    async fn advisory_size(
+        &self,
+        advisory: &DiscoveredAdvisory,
+    ) -> Result<Option<u64>, Self::Error> {
+        Ok(self.storage(&advisory.url)?.size().await?)
+    }
+}
+
+impl KeySource for S3Source {
+    type Error = anyhow::Error;
+
+    async fn load_public_key(
+        &self,
+        key: Key<'_>,
+    ) -> Result<PublicKey, KeySourceError<Self::Error>> {
+        let storage = self.storage(key.url).map_err(KeySourceError::Source)?;
+        let bytes = storage
+            .read_optional()
+            .await
+            .map_err(|err| KeySourceError::Source(err.into()))?
+            .ok_or_else(|| KeySourceError::Source(anyhow!("Key not found: {storage}")))?;
+        utils::openpgp::validate_keys(bytes, key.fingerprint).map_err(KeySourceError::OpenPgp)
+    }
+}
@@ -13,9 +13,9 @@ use deno_core::{
     _ops::RustToV8NoScope, Extension, JsRuntime, OpDecl, PollEventLoopOptions, RuntimeOptions,
     StaticModuleLoader, op2, serde_v8, v8,
 };
-use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::{
+    cell::RefCell,
     collections::HashSet,
     fmt::Debug,
     rc::Rc,
@@ -25,6 +25,10 @@ use std::{
     },
     time::Duration,
 };
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::LocalSet,
+};
 use url::Url;
 
 const MODULE_ID: &str = "internal://bundle.js";
@@ -232,8 +236,88 @@ pub enum Profile {
     Optional,
 }
 
+/// A request to validate a document, sent to the runtime thread.
+struct Request {
+    doc: serde_json::Value,
+    validations: Vec<ValidationSet>,
+    ignore: HashSet<String>,
+    timeout: Option<Duration>,
+    reply: oneshot::Sender<anyhow::Result<Option<TestResult>>>,
+}
+
+/// Spawn the thread running the JavaScript runtimes.
+///
+/// A [`JsRuntime`] can't be moved between threads. So all runtimes live on a dedicated thread,
+/// and documents get sent there for validation. Requests are processed concurrently, each using
+/// a runtime from the pool. The thread ends once all senders are dropped.
+fn spawn_runtime() -> mpsc::UnboundedSender<Request> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Request>();
+
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                log::error!("Failed to create runtime for csaf-validator-lib: {err}");
+                return;
+            }
+        };
+
+        let pool = Rc::new(RefCell::new(Vec::<InnerCheck>::new()));
+
+        LocalSet::new().block_on(&runtime, async move {
+            while let Some(request) = rx.recv().await {
+                let pool = pool.clone();
+                tokio::task::spawn_local(async move {
+                    let Request {
+                        doc,
+                        validations,
+                        ignore,
+                        timeout,
+                        reply,
+                    } = request;
+                    let result = validate(&pool, doc, &validations, &ignore, timeout).await;
+                    // the requester might have gone away
+                    let _ = reply.send(result);
+                });
+            }
+        });
+    });
+
+    tx
+}
+
+async fn validate(
+    pool: &RefCell<Vec<InnerCheck>>,
+    doc: serde_json::Value,
+    validations: &[ValidationSet],
+    ignore: &HashSet<String>,
+    timeout: Option<Duration>,
+) -> anyhow::Result<Option<TestResult>> {
+    let mut inner = {
+        let inner = pool.borrow_mut().pop();
+        match inner {
+            Some(inner) => inner,
+            None => InnerCheck::new().await?,
+        }
+    };
+
+    let test_result = inner
+        .validate::<_, TestResult>(doc, validations, ignore, timeout)
+        .await?;
+
+    if test_result.is_some() {
+        // not timed out, not failed, we can re-use it
+        pool.borrow_mut().push(inner);
+    }
+
+    Ok(test_result)
+}
+
 pub struct CsafValidatorLib {
-    runtime: Rc<Mutex<Vec<InnerCheck>>>,
+    runtime: mpsc::UnboundedSender<Request>,
     validations: Vec<ValidationSet>,
     timeout: Option<Duration>,
     ignore: HashSet<String>,
@@ -241,7 +325,7 @@ pub struct CsafValidatorLib {
 
 impl CsafValidatorLib {
     pub fn new(profile: Profile) -> Self {
-        let runtime = Rc::new(Mutex::new(vec![]));
+        let runtime = spawn_runtime();
 
         let validations = match profile {
             Profile::Schema => vec![ValidationSet::Schema],
@@ -293,20 +377,24 @@ impl CsafValidatorLib {
     }
 }
 
-#[async_trait(?Send)]
+#[async_trait]
 impl Check for CsafValidatorLib {
     async fn check(&self, csaf: &Csaf) -> anyhow::Result<Vec<CheckError>> {
-        let mut inner = {
-            let inner = self.runtime.lock().pop();
-            match inner {
-                Some(inner) => inner,
-                None => InnerCheck::new().await?,
-            }
-        };
-
-        let test_result = inner
-            .validate::<_, TestResult>(csaf, &self.validations, &self.ignore, self.timeout)
-            .await?;
+        let (reply, test_result) = oneshot::channel();
+
+        self.runtime
+            .send(Request {
+                doc: serde_json::to_value(csaf)?,
+                validations: self.validations.clone(),
+                ignore: self.ignore.clone(),
+                timeout: self.timeout,
+                reply,
+            })
+            .map_err(|_| anyhow!("csaf-validator-lib runtime is not running"))?;
+
+        let test_result = test_result
+            .await
+            .map_err(|_| anyhow!("csaf-validator-lib runtime stopped"))??;
 
         log::trace!("Result: {test_result:?}");
 
@@ -314,9 +402,6 @@ impl Check for CsafValidatorLib {
             return Ok(vec!["check timed out".into()]);
         };
 
-        // not timed out, not failed, we can re-use it
-        self.runtime.lock().push(inner);
-
         let mut result = vec![];
 
         for entry in test_result.tests {
@@ -24,17 +24,17 @@ pub mod csaf_validator_lib;
 
 pub type CheckError = Cow<'static, str>;
 
-#[async_trait(?Send)]
-pub trait Check {
+#[async_trait]
+pub trait Check: Send + Sync {
     /// Perform a check on a CSAF document
     async fn check(&self, csaf: &Csaf) -> anyhow::Result<Vec<CheckError>>;
 }
 
 /// Implementation to allow a simple function style check
-#[async_trait(?Send)]
+#[async_trait]
 impl<F> Check for F
 where
-    F: Fn(&Csaf) -> Vec<CheckError>,
+    F: Fn(&Csaf) -> Vec<CheckError> + Send + Sync,
 {
     async fn check(&self, csaf: &Csaf) -> anyhow::Result<Vec<CheckError>> {
         Ok((self)(csaf))
@@ -29,7 +29,7 @@ pub mod check;
 pub struct VerifiedAdvisory<A, I>
 where
     A: AsRetrieved,
-    I: Clone + PartialEq + Eq + Hash,
+    I: Clone + PartialEq + Eq + Hash + Send + Sync,
 {
     pub advisory: A,
     pub csaf: Csaf,
@@ -40,7 +40,7 @@ where
 impl<A, I> Deref for VerifiedAdvisory<A, I>
 where
     A: AsRetrieved,
-    I: Clone + PartialEq + Eq + Hash,
+    I: Clone + PartialEq + Eq + Hash + Send + Sync,
 {
     type Target = A;
 
@@ -52,7 +52,7 @@ where
 impl<A, I> DerefMut for VerifiedAdvisory<A, I>
 where
     A: AsRetrieved,
-    I: Clone + PartialEq + Eq + Hash,
+    I: Clone + PartialEq + Eq + Hash + Send + Sync,
 {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.advisory
@@ -107,25 +107,25 @@ where
 pub struct VerificationContext {}
 
 /// A visitor accepting a verified advisory
-pub trait VerifiedVisitor<A, E, I>
+pub trait VerifiedVisitor<A, E, I>: Send + Sync
 where
     A: AsRetrieved,
-    E: Display + Debug,
-    I: Clone + PartialEq + Eq + Hash,
+    E: Display + Debug + Send + Sync,
+    I: Clone + PartialEq + Eq + Hash + Send + Sync,
 {
-    type Error: Display + Debug;
-    type Context;
+    type Error: Display + Debug + Send;
+    type Context: Send + Sync;
 
     fn visit_context(
         &self,
         context: &VerificationContext,
-    ) -> impl Future<Output = Result<Self::Context, Self::Error>>;
+    ) -> impl Future<Output = Result<Self::Context, Self::Error>> + Send;
 
     fn visit_advisory(
         &self,
         context: &Self::Context,
         result: Result<VerifiedAdvisory<A, I>, VerificationError<E, A>>,
-    ) -> impl Future<Output = Result<(), Self::Error>>;
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -142,8 +142,8 @@ pub struct VerifyingVisitor<A, E, V, I>
 where
     A: AsRetrieved,
     V: VerifiedVisitor<A, E, I>,
-    E: Display + Debug,
-    I: Clone + PartialEq + Eq + Hash,
+    E: Display + Debug + Send + Sync,
+    I: Clone + PartialEq + Eq + Hash + Send + Sync,
 {
     visitor: V,
     checks: Vec<(I, Box<dyn Check>)>,
@@ -154,8 +154,8 @@ impl<A, E, V, I> VerifyingVisitor<A, E, V, I>
 where
     A: AsRetrieved,
     V: VerifiedVisitor<A, E, I>,
-    E: Display + Debug,
-    I: Clone + PartialEq + Eq + Hash,
+    E: Display + Debug + Send + Sync,
+    I: Clone + PartialEq + Eq + Hash + Send + Sync,
 {
     pub fn new(visitor: V) -> Self {
         Self {
@@ -181,8 +181,11 @@ where
     async fn verify(&self, advisory: A) -> Result<VerifiedAdvisory<A, I>, VerificationError<E, A>> {
         let data = advisory.as_retrieved().data.clone();
 
-        let csaf = match tokio::task::spawn_blocking(move || serde_json::from_slice::<Csaf>(&data))
-            .await
+        let csaf = match tokio::task::spawn_blocking(move || {
+            // read spooled documents from their file, instead of loading them into memory first
+            serde_json::from_reader::<_, Csaf>(data.reader().map_err(serde_json::Error::io)?)
+        })
+        .await
         {
             Ok(Ok(csaf)) => csaf,
             Ok(Err(error)) => return Err(VerificationError::Parsing { error, advisory }),
@@ -222,7 +225,7 @@ impl<V, I, S> RetrievedVisitor<S>
     for VerifyingVisitor<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>, V, I>
 where
     V: VerifiedVisitor<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>, I>,
-    I: Clone + PartialEq + Eq + Hash,
+    I: Clone + PartialEq + Eq + Hash + Send + Sync,
     S: Source,
 {
     type Error = Error<V::Error>;
@@ -260,7 +263,7 @@ where
 impl<V, I, S> ValidatedVisitor<S> for VerifyingVisitor<ValidatedAdvisory, ValidationError<S>, V, I>
 where
     V: VerifiedVisitor<ValidatedAdvisory, ValidationError<S>, I>,
-    I: Clone + PartialEq + Eq + Hash,
+    I: Clone + PartialEq + Eq + Hash + Send + Sync,
     S: Source,
 {
     type Error = Error<V::Error>;
@@ -297,12 +300,12 @@ where
 
 impl<F, E, Fut, A, I, UE> VerifiedVisitor<A, UE, I> for F
 where
-    UE: Debug + Display + 'static,
-    F: Fn(Result<VerifiedAdvisory<A, I>, VerificationError<UE, A>>) -> Fut,
-    Fut: Future<Output = Result<(), E>>,
-    E: Display + Debug + 'static,
+    UE: Debug + Display + Send + Sync + 'static,
+    F: Fn(Result<VerifiedAdvisory<A, I>, VerificationError<UE, A>>) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), E>> + Send,
+    E: Display + Debug + Send + 'static,
     A: AsRetrieved + 'static,
-    I: Clone + PartialEq + Eq + Hash + 'static,
+    I: Clone + PartialEq + Eq + Hash + Send + Sync + 'static,
 {
     type Error = E;
     type Context = ();
@@ -0,0 +1,256 @@
+use crate::{
+    discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor},
+    retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
+    source::Source,
+    validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
+};
+use std::sync::Arc;
+use walker_common::{checkpoint::Checkpoint, retrieve::RetrievalError};
+
+/// An intercepting visitor, skipping advisories completed by a previous run, and recording the
+/// ones completed by this run.
+///
+/// As a [`DiscoveredVisitor`], advisories completed by a previous run are skipped. As a
+/// [`RetrievedVisitor`] or [`ValidatedVisitor`], advisories are recorded once they were processed
+/// successfully by the inner visitor. Failed advisories are never recorded, so the recording part
+/// must be placed below any visitor skipping failures (like the
+/// [`super::skip::SkipFailedVisitor`]).
+///
+/// Without a checkpoint, all advisories are passed on.
+pub struct CheckpointVisitor<V> {
+    pub visitor: V,
+    pub checkpoint: Option<Arc<Checkpoint>>,
+}
+
+impl<V> CheckpointVisitor<V> {
+    fn complete(&self, advisory: &DiscoveredAdvisory) {
+        let Some(checkpoint) = &self.checkpoint else {
+            return;
+        };
+
+        if let Err(err) = checkpoint.complete(&advisory.url, advisory.modified) {
+            log::warn!("Failed to record advisory in checkpoint: {err}");
+        }
+    }
+}
+
+impl<V: DiscoveredVisitor> DiscoveredVisitor for CheckpointVisitor<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        if let Some(checkpoint) = &self.checkpoint {
+            if checkpoint.is_completed(&advisory.url, advisory.modified) {
+                log::debug!("Skipping completed advisory: {}", advisory.url);
+                return Ok(());
+            }
+        }
+
+        self.visitor.visit_advisory(context, advisory).await
+    }
+}
+
+impl<V: RetrievedVisitor<S>, S: Source> RetrievedVisitor<S> for CheckpointVisitor<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &RetrievalContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<RetrievedAdvisory, RetrievalError<DiscoveredAdvisory, S>>,
+    ) -> Result<(), Self::Error> {
+        let discovered = result
+            .as_ref()
+            .ok()
+            .map(|advisory| advisory.discovered.clone());
+
+        self.visitor.visit_advisory(context, result).await?;
+
+        if let Some(discovered) = discovered {
+            self.complete(&discovered);
+        }
+
+        Ok(())
+    }
+
+    async fn visit_unchanged(
+        &self,
+        context: &Self::Context,
+        discovered: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        self.visitor
+            .visit_unchanged(context, discovered.clone())
+            .await?;
+        self.complete(&discovered);
+        Ok(())
+    }
+}
+
+impl<V: ValidatedVisitor<S>, S: Source> ValidatedVisitor<S> for CheckpointVisitor<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &ValidationContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        result: Result<ValidatedAdvisory, ValidationError<S>>,
+    ) -> Result<(), Self::Error> {
+        let discovered = result
+            .as_ref()
+            .ok()
+            .map(|advisory| advisory.discovered.clone());
+
+        self.visitor.visit_advisory(context, result).await?;
+
+        if let Some(discovered) = discovered {
+            self.complete(&discovered);
+        }
+
+        Ok(())
+    }
+
+    async fn visit_unchanged(
+        &self,
+        context: &Self::Context,
+        discovered: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        self.visitor
+            .visit_unchanged(context, discovered.clone())
+            .await?;
+        self.complete(&discovered);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        discover::DistributionContext, model::metadata::ProviderMetadata,
+        retrieve::RetrievingVisitor, source::FileSource, validation::ValidationVisitor,
+        visitors::skip::SkipFailedVisitor,
+    };
+    use std::{path::Path, sync::Mutex, time::SystemTime};
+    use url::Url;
+
+    /// Walk the advisories once, like a single run, returning the visited ones.
+    async fn run(dir: &Path, checkpoint: Checkpoint, names: &[&str]) -> Vec<String> {
+        let checkpoint = Some(Arc::new(checkpoint));
+        let visited = Arc::new(Mutex::new(vec![]));
+
+        let visitor = CheckpointVisitor {
+            visitor: RetrievingVisitor::new(
+                FileSource::new(dir, None).expect("must create"),
+                ValidationVisitor::new(SkipFailedVisitor::new(CheckpointVisitor {
+                    visitor: {
+                        let visited = visited.clone();
+                        move |result: Result<ValidatedAdvisory, ValidationError<FileSource>>| {
+                            let visited = visited.clone();
+                            async move {
+                                let advisory = result?;
+                                let name = advisory.url.path().rsplit('/').next();
+                                visited
+                                    .lock()
+                                    .expect("must lock")
+                                    .push(name.unwrap_or_default().to_string());
+                                Ok::<_, anyhow::Error>(())
+                            }
+                        }
+                    },
+                    checkpoint: checkpoint.clone(),
+                })),
+            ),
+            checkpoint,
+        };
+
+        let metadata: ProviderMetadata = serde_json::from_value(serde_json::json!({
+            "canonical_url": "https://example.com/provider-metadata.json",
+            "distributions": [],
+            "last_updated": "2024-01-01T00:00:00Z",
+            "metadata_version": "2.0",
+            "publisher": {
+                "category": "vendor",
+                "contact_details": "",
+                "name": "Example",
+                "namespace": "https://example.com",
+            },
+        }))
+        .expect("must parse");
+        let context = visitor
+            .visit_context(&DiscoveredContext {
+                metadata: &metadata,
+                categories: &[],
+            })
+            .await
+            .expect("must create context");
+
+        let distribution = Url::from_directory_path(dir).expect("must convert");
+        let distribution_context = Arc::new(DistributionContext::Directory(distribution.clone()));
+        for name in names {
+            let advisory = DiscoveredAdvisory {
+                url: distribution.join(name).expect("must join"),
+                context: distribution_context.clone(),
+                digest: None,
+                signature: None,
+                modified: SystemTime::UNIX_EPOCH,
+                categories: vec![],
+                page: None,
+                local: None,
+            };
+            visitor
+                .visit_advisory(&context, advisory)
+                .await
+                .expect("must visit");
+        }
+
+        visited.lock().expect("must lock").clone()
+    }
+
+    #[tokio::test]
+    async fn retry_failed() {
+        let dir = tempfile::tempdir().expect("must create");
+        let file = dir.path().join("checkpoint.jsonl");
+        std::fs::write(dir.path().join("a.json"), "{}").expect("must write");
+
+        // "b.json" fails to load, and gets skipped
+        let checkpoint = Checkpoint::open(&file).expect("must open");
+        let visited = run(dir.path(), checkpoint, &["a.json", "b.json"]).await;
+        assert_eq!(visited, vec!["a.json"]);
+
+        // after a restart, only the skipped one is processed again
+        std::fs::write(dir.path().join("b.json"), "{}").expect("must write");
+        let checkpoint = Checkpoint::open(&file).expect("must open");
+        let visited = run(dir.path(), checkpoint, &["a.json", "b.json"]).await;
+        assert_eq!(visited, vec!["b.json"]);
+
+        // now both are completed
+        let checkpoint = Checkpoint::open(&file).expect("must open");
+        let visited = run(dir.path(), checkpoint, &["a.json", "b.json"]).await;
+        assert!(visited.is_empty());
+    }
+}
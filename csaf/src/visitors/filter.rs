@@ -1,4 +1,7 @@
-use crate::discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor};
+use crate::{
+    discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor, DistributionContext},
+    model::metadata::TlpLabel,
+};
 use std::collections::HashSet;
 
 /// A visitor, skipping advisories for existing files.
@@ -18,6 +21,12 @@ pub struct FilterConfig {
     pub ignored_distributions: HashSet<String>,
     pub ignored_prefixes: Vec<String>,
     pub only_prefixes: Vec<String>,
+    /// Only accept advisories which have at least one of the ROLIE category terms
+    pub only_categories: Vec<String>,
+    /// Only accept distributions with one of the TLP labels
+    ///
+    /// Directory distributions don't carry a label, they are considered [`TlpLabel::Unlabeled`].
+    pub only_tlp_labels: Vec<TlpLabel>,
 }
 
 impl FilterConfig {
@@ -88,6 +97,63 @@ impl FilterConfig {
         self.only_prefixes.extend(only_prefixes);
         self
     }
+
+    pub fn only_categories<I>(mut self, only_categories: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.only_categories = Vec::from_iter(only_categories);
+        self
+    }
+
+    pub fn add_only_category(mut self, only_category: impl Into<String>) -> Self {
+        self.only_categories.push(only_category.into());
+        self
+    }
+
+    pub fn extend_only_categories<I>(mut self, only_categories: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.only_categories.extend(only_categories);
+        self
+    }
+
+    pub fn only_tlp_labels<I>(mut self, only_tlp_labels: I) -> Self
+    where
+        I: IntoIterator<Item = TlpLabel>,
+    {
+        self.only_tlp_labels = Vec::from_iter(only_tlp_labels);
+        self
+    }
+
+    pub fn add_only_tlp_label(mut self, only_tlp_label: TlpLabel) -> Self {
+        self.only_tlp_labels.push(only_tlp_label);
+        self
+    }
+
+    pub fn extend_only_tlp_labels<I>(mut self, only_tlp_labels: I) -> Self
+    where
+        I: IntoIterator<Item = TlpLabel>,
+    {
+        self.only_tlp_labels.extend(only_tlp_labels);
+        self
+    }
+
+    /// Check if a distribution is accepted by the filter.
+    ///
+    /// This can also be used with [`crate::walker::Walker::with_distribution_filter`], to not
+    /// even discover filtered distributions.
+    pub fn accepts_distribution(&self, distribution: &DistributionContext) -> bool {
+        if self
+            .ignored_distributions
+            .contains(distribution.url().as_str())
+        {
+            return false;
+        }
+
+        self.only_tlp_labels.is_empty() || self.only_tlp_labels.contains(distribution.tlp_label())
+    }
 }
 
 impl<V: DiscoveredVisitor> DiscoveredVisitor for FilteringVisitor<V> {
@@ -98,6 +164,19 @@ impl<V: DiscoveredVisitor> DiscoveredVisitor for FilteringVisitor<V> {
         &self,
         discovered: &DiscoveredContext<'_>,
     ) -> Result<Self::Context, Self::Error> {
+        // only check the terms if the provider lists its categories
+        if !discovered.categories.is_empty() {
+            for term in &self.config.only_categories {
+                if !discovered
+                    .categories
+                    .iter()
+                    .any(|category| &category.term == term)
+                {
+                    log::warn!("Category '{term}' is not listed by any ROLIE category document");
+                }
+            }
+        }
+
         self.visitor.visit_context(discovered).await
     }
 
@@ -108,11 +187,7 @@ impl<V: DiscoveredVisitor> DiscoveredVisitor for FilteringVisitor<V> {
     ) -> Result<(), Self::Error> {
         // ignore distributions
 
-        if self
-            .config
-            .ignored_distributions
-            .contains(advisory.context.url().as_str())
-        {
+        if !self.config.accepts_distribution(&advisory.context) {
             return Ok(());
         };
 
@@ -144,6 +219,17 @@ impl<V: DiscoveredVisitor> DiscoveredVisitor for FilteringVisitor<V> {
             return Ok(());
         }
 
+        // "only" categories
+
+        if !self.config.only_categories.is_empty()
+            && !advisory
+                .categories
+                .iter()
+                .any(|category| self.config.only_categories.contains(&category.term))
+        {
+            return Ok(());
+        }
+
         // ok to proceed
 
         self.visitor.visit_advisory(context, advisory).await
@@ -153,7 +239,7 @@ impl<V: DiscoveredVisitor> DiscoveredVisitor for FilteringVisitor<V> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::discover::DistributionContext;
+    use crate::{model::metadata::Feed, rolie::Category};
     use std::sync::Arc;
     use std::time::SystemTime;
     use tokio::sync::Mutex;
@@ -186,6 +272,17 @@ mod test {
     }
 
     async fn issue<V>(filter: &FilteringVisitor<V>, name: &str) -> Result<(), anyhow::Error>
+    where
+        V: DiscoveredVisitor<Error = anyhow::Error, Context = ()>,
+    {
+        issue_with_categories(filter, name, &[]).await
+    }
+
+    async fn issue_with_categories<V>(
+        filter: &FilteringVisitor<V>,
+        name: &str,
+        categories: &[&str],
+    ) -> Result<(), anyhow::Error>
     where
         V: DiscoveredVisitor<Error = anyhow::Error, Context = ()>,
     {
@@ -194,6 +291,13 @@ mod test {
         )?));
         let url = Url::parse(&format!("https://localhost/{name}"))?;
         let modified = SystemTime::now();
+        let categories = categories
+            .iter()
+            .map(|term| Category {
+                scheme: "https://localhost/products".to_string(),
+                term: term.to_string(),
+            })
+            .collect();
 
         filter
             .visit_advisory(
@@ -204,6 +308,9 @@ mod test {
                     digest: None,
                     signature: None,
                     modified,
+                    categories,
+                    page: None,
+                    local: None,
                 },
             )
             .await?;
@@ -256,4 +363,51 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn only_category() -> anyhow::Result<()> {
+        let mock = MockVisitor::default();
+        let filter = FilteringVisitor {
+            config: FilterConfig::new().add_only_category("Product A"),
+            visitor: mock,
+        };
+
+        issue_with_categories(&filter, "foo-1", &["Product A"]).await?;
+        issue_with_categories(&filter, "foo-2", &["Product A", "Product B"]).await?;
+        issue_with_categories(&filter, "foo-3", &["Product B"]).await?;
+        issue(&filter, "foo-4").await?;
+
+        let items = filter.visitor.items.lock().await.clone();
+        assert_eq!(items.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_tlp() -> anyhow::Result<()> {
+        let config = FilterConfig::new()
+            .add_only_tlp_label(TlpLabel::White)
+            .add_only_tlp_label(TlpLabel::Green);
+
+        let feed = |tlp_label| {
+            Ok::<_, url::ParseError>(DistributionContext::Feed(Feed {
+                summary: None,
+                tlp_label,
+                url: Url::parse("https://localhost/feed.json")?,
+            }))
+        };
+
+        assert!(config.accepts_distribution(&feed(TlpLabel::White)?));
+        assert!(config.accepts_distribution(&feed(TlpLabel::Green)?));
+        assert!(!config.accepts_distribution(&feed(TlpLabel::Amber)?));
+        assert!(!config.accepts_distribution(&feed(TlpLabel::Red)?));
+        assert!(!config.accepts_distribution(&feed(TlpLabel::Unlabeled)?));
+        assert!(
+            !config.accepts_distribution(&DistributionContext::Directory(Url::parse(
+                "https://localhost/"
+            )?))
+        );
+
+        Ok(())
+    }
 }
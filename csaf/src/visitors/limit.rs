@@ -0,0 +1,34 @@
+use crate::discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// An intercepting visitor, limiting the number of advisories processed concurrently.
+///
+/// The semaphore can be shared between visitors of different walks, limiting the total number
+/// of advisories processed by all of them.
+pub struct LimitingVisitor<V: DiscoveredVisitor> {
+    pub visitor: V,
+    pub limit: Arc<Semaphore>,
+}
+
+impl<V: DiscoveredVisitor> DiscoveredVisitor for LimitingVisitor<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        // the semaphore is never closed
+        let _permit = self.limit.acquire().await;
+        self.visitor.visit_advisory(context, advisory).await
+    }
+}
@@ -1,6 +1,10 @@
 //! Ready-to use visitors
 
+pub mod checkpoint;
 pub mod duplicates;
 pub mod filter;
+pub mod limit;
+pub mod plan;
+pub mod prune;
 pub mod skip;
 pub mod store;
@@ -0,0 +1,337 @@
+use crate::{
+    discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor},
+    source::Source,
+};
+use chrono::{DateTime, Utc};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    sync::{Arc, Mutex},
+};
+use url::Url;
+
+/// The action planned for an advisory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlannedAction {
+    /// The advisory would be retrieved
+    Fetch,
+    /// The advisory would be skipped
+    Skip,
+}
+
+impl Display for PlannedAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fetch => f.pad("fetch"),
+            Self::Skip => f.pad("skip"),
+        }
+    }
+}
+
+/// The reason for the action planned for an advisory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanReason {
+    /// There is no local copy of the advisory
+    New,
+    /// The local copy of the advisory is outdated
+    Modified,
+    /// The local copy of the advisory is up-to-date
+    Unchanged,
+    /// The advisory is filtered out
+    Filtered,
+}
+
+impl PlanReason {
+    /// The action resulting from the reason.
+    pub fn action(&self) -> PlannedAction {
+        match self {
+            Self::New | Self::Modified => PlannedAction::Fetch,
+            Self::Unchanged | Self::Filtered => PlannedAction::Skip,
+        }
+    }
+}
+
+impl Display for PlanReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::New => f.pad("new"),
+            Self::Modified => f.pad("modified"),
+            Self::Unchanged => f.pad("unchanged"),
+            Self::Filtered => f.pad("filtered"),
+        }
+    }
+}
+
+/// An advisory, and what would be done with it.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedAdvisory {
+    pub url: Url,
+    /// The URL of the distribution listing the advisory
+    pub distribution: Url,
+    /// The modification timestamp reported by the distribution
+    pub modified: DateTime<Utc>,
+    pub action: PlannedAction,
+    pub reason: PlanReason,
+    /// The size of the advisory, if it would be retrieved and the source reports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+impl PlannedAdvisory {
+    fn new(advisory: &DiscoveredAdvisory, reason: PlanReason, size: Option<u64>) -> Self {
+        Self {
+            url: advisory.url.clone(),
+            distribution: advisory.context.url().clone(),
+            modified: advisory.modified.into(),
+            action: reason.action(),
+            reason,
+            size,
+        }
+    }
+}
+
+/// The totals of a plan.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanSummary {
+    /// The number of advisories which would be retrieved
+    pub fetch: usize,
+    /// The number of advisories which would be skipped
+    pub skip: usize,
+    /// The total size of the advisories which would be retrieved, as far as it is known
+    pub size: u64,
+    /// The number of advisories which would be retrieved, but have an unknown size
+    pub unknown_size: usize,
+}
+
+/// The advisories discovered by a dry run, and what would be done with them.
+///
+/// This can be cloned, and shared between the visitors of a walk.
+#[derive(Clone, Debug, Default)]
+pub struct Plan(Arc<Mutex<BTreeMap<Url, PlannedAdvisory>>>);
+
+impl Plan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record what would be done with an advisory, replacing what was recorded before.
+    pub fn record(&self, advisory: PlannedAdvisory) {
+        self.0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(advisory.url.clone(), advisory);
+    }
+
+    /// All planned advisories, ordered by their URL.
+    pub fn advisories(&self) -> Vec<PlannedAdvisory> {
+        self.0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn summary(&self) -> PlanSummary {
+        let mut summary = PlanSummary::default();
+
+        for advisory in self
+            .0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .values()
+        {
+            match (advisory.action, advisory.size) {
+                (PlannedAction::Skip, _) => summary.skip += 1,
+                (PlannedAction::Fetch, Some(size)) => {
+                    summary.fetch += 1;
+                    summary.size += size;
+                }
+                (PlannedAction::Fetch, None) => {
+                    summary.fetch += 1;
+                    summary.unknown_size += 1;
+                }
+            }
+        }
+
+        summary
+    }
+}
+
+/// An intercepting visitor, recording advisories as skipped for a reason, before passing them on.
+///
+/// This is put in front of a visitor which might skip advisories, like the
+/// [`crate::visitors::filter::FilteringVisitor`]. Advisories which get passed on are recorded
+/// again by later stages, replacing the reason. Without a plan, all advisories are just passed on.
+pub struct PlanningStage<V: DiscoveredVisitor> {
+    pub visitor: V,
+    pub plan: Option<Plan>,
+    /// The reason for skipping the advisory, in case the visitor doesn't pass it on
+    pub reason: PlanReason,
+}
+
+impl<V: DiscoveredVisitor> DiscoveredVisitor for PlanningStage<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        if let Some(plan) = &self.plan {
+            plan.record(PlannedAdvisory::new(&advisory, self.reason, None));
+        }
+
+        self.visitor.visit_advisory(context, advisory).await
+    }
+}
+
+/// A visitor replacing the retrieval of advisories with recording them in a plan.
+///
+/// This is put in place of the visitor retrieving advisories. The size of the advisory is
+/// requested from the source (e.g. using a HEAD request). Without a plan, all advisories are
+/// passed on.
+pub struct PlanningVisitor<V: DiscoveredVisitor, S: Source> {
+    pub visitor: V,
+    pub source: S,
+    pub plan: Option<Plan>,
+}
+
+impl<V: DiscoveredVisitor, S: Source> DiscoveredVisitor for PlanningVisitor<V, S> {
+    type Error = V::Error;
+    /// The context of the visitor, which is not being visited when planning.
+    type Context = Option<V::Context>;
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        match self.plan {
+            Some(_) => Ok(None),
+            None => Ok(Some(self.visitor.visit_context(context).await?)),
+        }
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        let (Some(plan), None) = (&self.plan, context) else {
+            return match context {
+                Some(context) => self.visitor.visit_advisory(context, advisory).await,
+                // the context is only missing when planning
+                None => Ok(()),
+            };
+        };
+
+        // a local copy is only attached to outdated advisories
+        let reason = match advisory.local {
+            Some(_) => PlanReason::Modified,
+            None => PlanReason::New,
+        };
+
+        let size = match self.source.advisory_size(&advisory).await {
+            Ok(size) => size,
+            Err(err) => {
+                log::warn!("Failed to get the size of {}: {err}", advisory.url);
+                None
+            }
+        };
+
+        plan.record(PlannedAdvisory::new(&advisory, reason, size));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        discover::DistributionContext,
+        source::FileSource,
+        visitors::filter::{FilterConfig, FilteringVisitor},
+    };
+    use std::time::SystemTime;
+    use walker_common::retrieve::RetrievalMetadata;
+
+    #[tokio::test]
+    async fn plan() {
+        let dir = tempfile::tempdir().expect("must create");
+        std::fs::write(dir.path().join("a.json"), "{}").expect("must write");
+        std::fs::write(dir.path().join("b.json"), "{ }").expect("must write");
+
+        let distribution = Url::from_directory_path(dir.path()).expect("must convert");
+        let context = Arc::new(DistributionContext::Directory(distribution.clone()));
+        let advisory = |name: &str, local: bool| DiscoveredAdvisory {
+            url: distribution.join(name).expect("must join"),
+            context: context.clone(),
+            digest: None,
+            signature: None,
+            modified: SystemTime::UNIX_EPOCH,
+            categories: vec![],
+            page: None,
+            local: local.then(RetrievalMetadata::default),
+        };
+
+        let plan = Plan::new();
+        let visitor = PlanningStage {
+            visitor: FilteringVisitor {
+                visitor: PlanningVisitor {
+                    visitor: |_: DiscoveredAdvisory| async { Err::<(), _>("must not retrieve") },
+                    source: FileSource::new(dir.path(), None).expect("must create"),
+                    plan: Some(plan.clone()),
+                },
+                config: FilterConfig::new().add_ignored_prefix("c"),
+            },
+            plan: Some(plan.clone()),
+            reason: PlanReason::Filtered,
+        };
+
+        // when planning, the context of the retrieving visitor is never created
+        let context = None;
+        for (name, local) in [("a.json", false), ("b.json", true), ("c.json", false)] {
+            visitor
+                .visit_advisory(&context, advisory(name, local))
+                .await
+                .expect("must visit");
+        }
+
+        let advisories = plan
+            .advisories()
+            .into_iter()
+            .map(|advisory| (advisory.reason, advisory.size))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            advisories,
+            vec![
+                (PlanReason::New, Some(2)),
+                (PlanReason::Modified, Some(3)),
+                (PlanReason::Filtered, None),
+            ]
+        );
+
+        assert_eq!(
+            plan.summary(),
+            PlanSummary {
+                fetch: 2,
+                skip: 1,
+                size: 5,
+                unknown_size: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor},
+    model::store::distribution_base,
+};
+use anyhow::Context;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use walkdir::WalkDir;
+
+/// The sidecar files stored next to a document
+const SIDECARS: [&str; 3] = ["asc", "sha256", "sha512"];
+
+/// The documents listed by each distribution, by the URL of the distribution.
+///
+/// This can be cloned, and shared with a [`Pruner`].
+#[derive(Clone, Debug, Default)]
+pub struct Listing(Arc<Mutex<BTreeMap<String, BTreeSet<String>>>>);
+
+impl Listing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an advisory as listed by its distribution.
+    pub fn record(&self, advisory: &DiscoveredAdvisory) {
+        let Some(name) = advisory.context.url().make_relative(&advisory.url) else {
+            return;
+        };
+
+        self.0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(advisory.context.url().to_string())
+            .or_default()
+            .insert(name);
+    }
+
+    fn get(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+}
+
+/// An intercepting visitor, recording all advisories listed by the distributions.
+///
+/// Without a listing, all advisories are just passed on.
+pub struct ListingVisitor<V: DiscoveredVisitor> {
+    pub visitor: V,
+    pub listing: Option<Listing>,
+}
+
+impl<V: DiscoveredVisitor> DiscoveredVisitor for ListingVisitor<V> {
+    type Error = V::Error;
+    type Context = V::Context;
+
+    async fn visit_context(
+        &self,
+        context: &DiscoveredContext<'_>,
+    ) -> Result<Self::Context, Self::Error> {
+        self.visitor.visit_context(context).await
+    }
+
+    async fn visit_advisory(
+        &self,
+        context: &Self::Context,
+        advisory: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        if let Some(listing) = &self.listing {
+            listing.record(&advisory);
+        }
+
+        self.visitor.visit_advisory(context, advisory).await
+    }
+}
+
+/// What to do with documents no longer listed by their distribution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum PruneMode {
+    /// Remove the documents.
+    #[default]
+    Remove,
+    /// Move the documents into a directory, using the same layout as the store.
+    Quarantine(PathBuf),
+}
+
+/// Pruning documents stored by a [`crate::visitors::store::StoreVisitor`], which are no longer
+/// listed by their distribution.
+///
+/// Only distributions which listed at least one document are pruned. So a distribution which
+/// failed to load, or turned up empty, doesn't lose all of its documents.
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct Pruner {
+    /// the output base of the store
+    pub base: PathBuf,
+
+    /// what to do with orphaned documents
+    pub mode: PruneMode,
+
+    /// only report orphaned documents, without touching them
+    pub dry_run: bool,
+}
+
+/// The outcome of pruning.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// The documents pruned, or which would be pruned in case of a dry run
+    pub documents: Vec<PathBuf>,
+    /// The number of sidecar files pruned along with the documents
+    pub sidecars: usize,
+}
+
+impl Pruner {
+    pub fn new(base: impl Into<PathBuf>) -> Self {
+        Self {
+            base: base.into(),
+            mode: PruneMode::default(),
+            dry_run: false,
+        }
+    }
+
+    pub fn base(mut self, base: impl Into<PathBuf>) -> Self {
+        self.base = base.into();
+        self
+    }
+
+    pub fn mode(mut self, mode: PruneMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Prune all stored documents, which are not part of the listing.
+    pub fn prune(&self, listing: &Listing) -> anyhow::Result<PruneReport> {
+        let mut report = PruneReport::default();
+
+        for (distribution, listed) in listing.get() {
+            let base = distribution_base(&self.base, &distribution);
+            if !base.is_dir() {
+                continue;
+            }
+
+            for entry in WalkDir::new(&base) {
+                let entry = entry?;
+                let path = entry.path();
+                if !entry.file_type().is_file()
+                    || path.extension().is_none_or(|extension| extension != "json")
+                {
+                    continue;
+                }
+
+                let name = path
+                    .strip_prefix(&base)?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+
+                if listed.contains(&name) {
+                    continue;
+                }
+
+                report.sidecars += self.prune_document(&distribution, &name, path)?;
+                report.documents.push(path.to_path_buf());
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Prune a document and its sidecar files, returning the number of sidecar files.
+    fn prune_document(&self, distribution: &str, name: &str, path: &Path) -> anyhow::Result<usize> {
+        let sidecars = SIDECARS
+            .iter()
+            .map(|extension| PathBuf::from(format!("{}.{extension}", path.display())))
+            .filter(|sidecar| sidecar.is_file())
+            .collect::<Vec<_>>();
+
+        if self.dry_run {
+            log::info!("Would prune: {}", path.display());
+            return Ok(sidecars.len());
+        }
+
+        log::info!("Pruning: {}", path.display());
+
+        for file in std::iter::once(path).chain(sidecars.iter().map(PathBuf::as_path)) {
+            match &self.mode {
+                PruneMode::Remove => std::fs::remove_file(file)
+                    .with_context(|| format!("Failed to remove file: {}", file.display()))?,
+                PruneMode::Quarantine(quarantine) => {
+                    let mut target = distribution_base(quarantine, distribution).join(name);
+                    if let Some(extension) = file.extension().filter(|_| file != path) {
+                        target.as_mut_os_string().push(".");
+                        target.as_mut_os_string().push(extension);
+                    }
+                    if let Some(parent) = target.parent() {
+                        std::fs::create_dir_all(parent).with_context(|| {
+                            format!("Failed to create directory: {}", parent.display())
+                        })?;
+                    }
+                    std::fs::rename(file, &target).with_context(|| {
+                        format!(
+                            "Failed to move file {} to: {}",
+                            file.display(),
+                            target.display()
+                        )
+                    })?;
+                }
+            }
+        }
+
+        Ok(sidecars.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::discover::DistributionContext;
+    use std::time::SystemTime;
+    use url::Url;
+
+    fn listing(distribution: &Url, names: &[&str]) -> Listing {
+        let listing = Listing::new();
+        let context = Arc::new(DistributionContext::Directory(distribution.clone()));
+        for name in names {
+            listing.record(&DiscoveredAdvisory {
+                url: distribution.join(name).expect("must join"),
+                context: context.clone(),
+                digest: None,
+                signature: None,
+                modified: SystemTime::UNIX_EPOCH,
+                categories: vec![],
+                page: None,
+                local: None,
+            });
+        }
+        listing
+    }
+
+    #[test]
+    fn prune() {
+        let dir = tempfile::tempdir().expect("must create");
+        let distribution = Url::parse("https://example.com/dir/").expect("must parse");
+        let base = distribution_base(dir.path(), distribution.as_str());
+
+        std::fs::create_dir_all(base.join("2023")).expect("must create");
+        for file in [
+            "2023/a.json",
+            "2023/b.json",
+            "2023/b.json.asc",
+            "2023/b.json.sha256",
+            "2023/c.json",
+        ] {
+            std::fs::write(base.join(file), "{}").expect("must write");
+        }
+
+        let listing = listing(&distribution, &["2023/a.json", "2023/c.json"]);
+        let pruner = Pruner::new(dir.path().join("store"));
+
+        // nothing stored for the distribution
+        let report = pruner.prune(&listing).expect("must prune");
+        assert_eq!(report, PruneReport::default());
+
+        let pruner = pruner.base(dir.path());
+        let report = pruner
+            .clone()
+            .dry_run(true)
+            .prune(&listing)
+            .expect("must prune");
+        assert_eq!(report.documents, vec![base.join("2023/b.json")]);
+        assert_eq!(report.sidecars, 2);
+        assert!(base.join("2023/b.json").exists());
+
+        let quarantine = dir.path().join("quarantine");
+        let report = pruner
+            .mode(PruneMode::Quarantine(quarantine.clone()))
+            .prune(&listing)
+            .expect("must prune");
+        assert_eq!(report.documents, vec![base.join("2023/b.json")]);
+
+        let moved = distribution_base(&quarantine, distribution.as_str());
+        for file in ["2023/b.json", "2023/b.json.asc", "2023/b.json.sha256"] {
+            assert!(!base.join(file).exists());
+            assert!(moved.join(file).exists());
+        }
+        assert!(base.join("2023/a.json").exists());
+        assert!(base.join("2023/c.json").exists());
+    }
+}
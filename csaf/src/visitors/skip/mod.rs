@@ -1,12 +1,13 @@
 use crate::discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor};
-use crate::model::store::distribution_base;
+use crate::model::store::distribution_name;
 use crate::source::Source;
 use crate::validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError};
 use std::fmt::{Debug, Display};
-use std::path::PathBuf;
 use std::time::SystemTime;
-use tokio::fs;
-use walker_common::utils::url::Urlify;
+use walker_common::{
+    store::{Storage, StoreError},
+    utils::url::Urlify,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error<VE: Display + Debug> {
@@ -14,6 +15,8 @@ pub enum Error<VE: Display + Debug> {
     Visitor(VE),
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Store(#[from] StoreError),
     #[error("Unable to get name from URL")]
     Name,
 }
@@ -21,7 +24,7 @@ pub enum Error<VE: Display + Debug> {
 /// A visitor, skipping advisories for existing files.
 pub struct SkipExistingVisitor<V: DiscoveredVisitor> {
     pub visitor: V,
-    pub output: PathBuf,
+    pub output: Storage,
     /// The time "since" when we consider changes "new"
     ///
     /// Overrides the "file modified" timestamp which is used by default.
@@ -45,19 +48,25 @@ impl<V: DiscoveredVisitor> DiscoveredVisitor for SkipExistingVisitor<V> {
     async fn visit_advisory(
         &self,
         context: &Self::Context,
-        advisory: DiscoveredAdvisory,
+        mut advisory: DiscoveredAdvisory,
     ) -> Result<(), Self::Error> {
         let name = match advisory.context.url().clone().make_relative(&advisory.url) {
             Some(name) => name,
             None => return Err(Error::Name),
         };
-        let path = distribution_base(&self.output, advisory.context.url().as_str()).join(&name);
+        let path = self
+            .output
+            .join(&distribution_name(advisory.context.url().as_str()))
+            .join(&name);
 
-        if fs::try_exists(&path).await? {
+        if let Some(local) = path.stored_metadata().await? {
             // if we have a "since", we use it as the file modification timestamp
             let file_modified = match self.since {
                 Some(since) => since,
-                None => fs::metadata(&path).await?.modified()?,
+                None => local
+                    .last_modification
+                    .map(SystemTime::from)
+                    .unwrap_or(SystemTime::UNIX_EPOCH),
             };
 
             log::debug!(
@@ -72,8 +81,14 @@ impl<V: DiscoveredVisitor> DiscoveredVisitor for SkipExistingVisitor<V> {
                 // the file was modified after the change date, skip it
                 return Ok(());
             }
+
+            // We have a local copy, the source may only retrieve the advisory if it was modified.
+            // Unless we have a "since", which forces all changes after it to be re-synced.
+            if self.since.is_none() {
+                advisory.local = Some(local);
+            }
         } else {
-            log::debug!("File did not exist: {}", path.display());
+            log::debug!("File did not exist: {path}");
         }
 
         self.visitor
@@ -123,4 +138,12 @@ impl<V: ValidatedVisitor<S>, S: Source> ValidatedVisitor<S> for SkipFailedVisito
             }
         }
     }
+
+    async fn visit_unchanged(
+        &self,
+        context: &Self::Context,
+        discovered: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        self.visitor.visit_unchanged(context, discovered).await
+    }
 }
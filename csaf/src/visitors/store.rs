@@ -1,43 +1,56 @@
 use crate::{
     discover::DiscoveredAdvisory,
-    model::{metadata::ProviderMetadata, store::distribution_base},
+    model::{
+        metadata::ProviderMetadata,
+        store::{distribution_base, distribution_name},
+    },
     retrieve::{RetrievalContext, RetrievedAdvisory, RetrievedVisitor},
+    rolie::Category,
     source::Source,
     validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
 };
 use anyhow::Context;
 use sequoia_openpgp::{Cert, armor::Kind, serialize::SerializeInto};
-use std::{
-    fmt::Debug,
-    io::{ErrorKind, Write},
-    path::{Path, PathBuf},
-    rc::Rc,
-};
+use std::{fmt::Debug, io::Write, sync::Arc};
 use tokio::fs;
 use walker_common::{
     retrieve::RetrievalError,
-    store::{Document, StoreError, store_document},
+    store::{Document, Storage, StoreError},
     utils::openpgp::PublicKey,
 };
 
 pub const DIR_METADATA: &str = "metadata";
 
+/// The attribute holding the ROLIE categories of a stored advisory, as JSON.
+pub const ATTR_CATEGORIES: &str = "categories";
+
+/// Parse the ROLIE categories stored with an advisory, see [`ATTR_CATEGORIES`].
+pub(crate) fn stored_categories(value: Option<String>) -> Vec<Category> {
+    value
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
 /// Stores all data so that it can be used as a [`crate::source::Source`] later.
 #[non_exhaustive]
+#[derive(Clone, Debug)]
 pub struct StoreVisitor {
-    /// the output base
-    pub base: PathBuf,
+    /// the output base, a directory or a key prefix in an S3 bucket
+    pub base: Storage,
 
     /// whether to set the file modification timestamps
     pub no_timestamps: bool,
 
-    /// whether to store additional metadata (like the etag) using extended attributes
+    /// whether to store additional metadata (like the etag and the ROLIE categories) using
+    /// extended attributes
+    ///
+    /// When storing in an S3 bucket, the metadata is always stored with the object.
     #[cfg(any(target_os = "linux", target_os = "macos"))]
     pub no_xattrs: bool,
 }
 
 impl StoreVisitor {
-    pub fn new(base: impl Into<PathBuf>) -> Self {
+    pub fn new(base: impl Into<Storage>) -> Self {
         Self {
             base: base.into(),
             no_timestamps: false,
@@ -78,17 +91,18 @@ pub enum StoreValidatedError<S: Source> {
 
 impl<S: Source + Debug> RetrievedVisitor<S> for StoreVisitor {
     type Error = StoreRetrievedError<S>;
-    type Context = Rc<ProviderMetadata>;
+    type Context = Arc<ProviderMetadata>;
 
     async fn visit_context(
         &self,
         context: &RetrievalContext<'_>,
     ) -> Result<Self::Context, Self::Error> {
+        self.remove_staged_files()?;
         self.store_provider_metadata(context.metadata).await?;
         self.prepare_distributions(context.metadata).await?;
         self.store_keys(context.keys).await?;
 
-        Ok(Rc::new(context.metadata.clone()))
+        Ok(Arc::new(context.metadata.clone()))
     }
 
     async fn visit_advisory(
@@ -99,6 +113,15 @@ impl<S: Source + Debug> RetrievedVisitor<S> for StoreVisitor {
         self.store(&result?).await?;
         Ok(())
     }
+
+    async fn visit_unchanged(
+        &self,
+        _context: &Self::Context,
+        discovered: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        self.unchanged(&discovered).await?;
+        Ok(())
+    }
 }
 
 impl<S: Source> ValidatedVisitor<S> for StoreVisitor {
@@ -109,6 +132,7 @@ impl<S: Source> ValidatedVisitor<S> for StoreVisitor {
         &self,
         context: &ValidationContext<'_>,
     ) -> Result<Self::Context, Self::Error> {
+        self.remove_staged_files()?;
         self.store_provider_metadata(context.metadata).await?;
         self.prepare_distributions(context.metadata).await?;
         self.store_keys(context.retrieval.keys).await?;
@@ -123,13 +147,39 @@ impl<S: Source> ValidatedVisitor<S> for StoreVisitor {
         self.store(&result?.retrieved).await?;
         Ok(())
     }
+
+    async fn visit_unchanged(
+        &self,
+        _context: &Self::Context,
+        discovered: DiscoveredAdvisory,
+    ) -> Result<(), Self::Error> {
+        self.unchanged(&discovered).await?;
+        Ok(())
+    }
 }
 
 impl StoreVisitor {
+    /// Remove staging files left behind by an interrupted run, before storing new documents.
+    fn remove_staged_files(&self) -> Result<(), StoreError> {
+        let removed = self.base.remove_staged_files()?;
+        if removed > 0 {
+            log::info!(
+                "Removed {removed} stale staging file(s) from: {}",
+                self.base
+            );
+        }
+        Ok(())
+    }
+
     async fn prepare_distributions(&self, metadata: &ProviderMetadata) -> Result<(), StoreError> {
+        // there are no directories in a bucket
+        let Some(store_base) = self.base.as_path() else {
+            return Ok(());
+        };
+
         for dist in &metadata.distributions {
             if let Some(directory_url) = &dist.directory_url {
-                let base = distribution_base(&self.base, directory_url.as_str());
+                let base = distribution_base(store_base, directory_url.as_str());
                 log::debug!("Creating base distribution directory: {}", base.display());
 
                 fs::create_dir_all(&base)
@@ -144,7 +194,7 @@ impl StoreVisitor {
             }
             if let Some(rolie) = &dist.rolie {
                 for feed in &rolie.feeds {
-                    let base = distribution_base(&self.base, feed.url.as_str());
+                    let base = distribution_base(store_base, feed.url.as_str());
                     fs::create_dir_all(&base)
                         .await
                         .with_context(|| {
@@ -162,47 +212,19 @@ impl StoreVisitor {
     }
 
     async fn store_provider_metadata(&self, metadata: &ProviderMetadata) -> Result<(), StoreError> {
-        let metadir = self.base.join(DIR_METADATA);
-
-        fs::create_dir(&metadir)
-            .await
-            .or_else(|err| match err.kind() {
-                ErrorKind::AlreadyExists => Ok(()),
-                _ => Err(err),
-            })
-            .with_context(|| format!("Failed to create metadata directory: {}", metadir.display()))
-            .map_err(StoreError::Io)?;
-
-        let file = metadir.join("provider-metadata.json");
-        let mut out = std::fs::File::create(&file)
-            .with_context(|| {
-                format!(
-                    "Unable to open provider metadata file for writing: {}",
-                    file.display()
-                )
-            })
-            .map_err(StoreError::Io)?;
-        serde_json::to_writer_pretty(&mut out, metadata)
+        let data = serde_json::to_vec_pretty(metadata)
             .context("Failed serializing provider metadata")
             .map_err(StoreError::Io)?;
-        Ok(())
+
+        self.base
+            .join(DIR_METADATA)
+            .join("provider-metadata.json")
+            .write(data)
+            .await
     }
 
     async fn store_keys(&self, keys: &[PublicKey]) -> Result<(), StoreError> {
         let metadata = self.base.join(DIR_METADATA).join("keys");
-        std::fs::create_dir(&metadata)
-            // ignore if the directory already exists
-            .or_else(|err| match err.kind() {
-                ErrorKind::AlreadyExists => Ok(()),
-                _ => Err(err),
-            })
-            .with_context(|| {
-                format!(
-                    "Failed to create metadata directory: {}",
-                    metadata.display()
-                )
-            })
-            .map_err(StoreError::Io)?;
 
         for cert in keys.iter().flat_map(|k| &k.certs) {
             log::info!("Storing key: {}", cert.fingerprint());
@@ -212,16 +234,12 @@ impl StoreVisitor {
         Ok(())
     }
 
-    async fn store_cert(&self, cert: &Cert, path: &Path) -> Result<(), StoreError> {
-        let name = path.join(format!("{}.txt", cert.fingerprint().to_hex()));
+    async fn store_cert(&self, cert: &Cert, base: &Storage) -> Result<(), StoreError> {
+        let name = base.join(&format!("{}.txt", cert.fingerprint().to_hex()));
 
         let data = Self::serialize_key(cert).map_err(StoreError::SerializeKey)?;
 
-        fs::write(&name, data)
-            .await
-            .with_context(|| format!("Failed to store key: {}", name.display()))
-            .map_err(StoreError::Io)?;
-        Ok(())
+        name.write(data).await
     }
 
     fn serialize_key(cert: &Cert) -> Result<Vec<u8>, anyhow::Error> {
@@ -230,6 +248,29 @@ impl StoreVisitor {
         Ok(writer.finalize()?)
     }
 
+    /// Handle an advisory which was not modified since the local copy was stored.
+    ///
+    /// The local copy is up-to-date with the change information, so we bump its modification
+    /// timestamp to prevent re-checking it with the next run.
+    async fn unchanged(&self, advisory: &DiscoveredAdvisory) -> Result<(), StoreError> {
+        log::info!("Unchanged: {}", advisory.url);
+
+        if self.no_timestamps {
+            return Ok(());
+        }
+
+        let name = match advisory.context.url().make_relative(&advisory.url) {
+            Some(name) => name,
+            None => return Err(StoreError::Filename(advisory.url.to_string())),
+        };
+        let file = self
+            .base
+            .join(&distribution_name(advisory.context.url().as_str()))
+            .join(&name);
+
+        file.set_document_timestamp(advisory.modified).await
+    }
+
     async fn store(&self, advisory: &RetrievedAdvisory) -> Result<(), StoreError> {
         log::info!(
             "Storing: {} (modified: {:?})",
@@ -243,26 +284,33 @@ impl StoreVisitor {
             None => return Err(StoreError::Filename(advisory.url.to_string())),
         };
 
-        // create a distribution base
-        let distribution_base = distribution_base(&self.base, advisory.context.url().as_str());
-
-        // put the file there
-        let file = distribution_base.join(name);
-
-        store_document(
-            &file,
-            Document {
-                data: &advisory.data,
-                changed: advisory.modified,
-                metadata: &advisory.metadata,
-                sha256: &advisory.sha256,
-                sha512: &advisory.sha512,
-                signature: &advisory.signature,
-                no_timestamps: self.no_timestamps,
-                #[cfg(any(target_os = "linux", target_os = "macos"))]
-                no_xattrs: self.no_xattrs,
-            },
-        )
+        // put the file into the distribution base
+        let file = self
+            .base
+            .join(&distribution_name(advisory.context.url().as_str()))
+            .join(name);
+
+        // keep the categories, so that they can still be filtered on when reading the documents
+        let mut attributes = vec![];
+        if !advisory.categories.is_empty() {
+            let categories = serde_json::to_string(&advisory.categories)
+                .context("Failed serializing categories")
+                .map_err(StoreError::Io)?;
+            attributes.push((ATTR_CATEGORIES, categories));
+        }
+
+        file.store_document(Document {
+            data: &advisory.data,
+            changed: advisory.modified,
+            metadata: &advisory.metadata,
+            attributes: &attributes,
+            sha256: &advisory.sha256,
+            sha512: &advisory.sha512,
+            signature: &advisory.signature,
+            no_timestamps: self.no_timestamps,
+            #[cfg(any(target_os = "linux", target_os = "macos"))]
+            no_xattrs: self.no_xattrs,
+        })
         .await?;
 
         Ok(())
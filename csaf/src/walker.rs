@@ -1,14 +1,19 @@
 //! The actual walker
 
 use crate::{
-    discover::{DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor, DistributionContext},
-    model::metadata::Distribution,
+    discover::{
+        Deduplication, DiscoveredAdvisory, DiscoveredContext, DiscoveredVisitor,
+        DistributionContext,
+    },
+    model::metadata::{Distribution, ProviderMetadata},
+    rolie::Category,
     source::Source,
 };
-use futures::{Stream, StreamExt, TryFutureExt, TryStream, TryStreamExt, stream};
-use std::{fmt::Debug, sync::Arc};
+use futures::{SinkExt, StreamExt, TryStreamExt, channel::mpsc, stream};
+use std::{collections::BTreeMap, fmt::Debug, future::Future, sync::Arc};
 use tokio::sync::Mutex;
-use url::ParseError;
+use tokio_util::{sync::CancellationToken, task::AbortOnDropHandle};
+use url::{ParseError, Url};
 use walker_common::progress::{Progress, ProgressBar};
 
 #[derive(Debug, thiserror::Error)]
@@ -23,14 +28,63 @@ where
     Url(#[from] ParseError),
     #[error("Visitor error: {0}")]
     Visitor(VE),
+    #[error("Walk was cancelled")]
+    Cancelled,
 }
 
-pub type DistributionFilter = Box<dyn Fn(&DistributionContext) -> bool>;
+pub type DistributionFilter = Box<dyn Fn(&DistributionContext) -> bool + Send + Sync>;
+
+/// How to handle a distribution failing to load its index.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Abort the walk.
+    #[default]
+    FailFast,
+    /// Record the failure, and continue with the next distribution.
+    Continue,
+}
+
+/// Distributions, and ROLIE service documents, which failed to load, by their URL.
+///
+/// This can be cloned, and shared with visitors and reports.
+#[derive(Clone, Debug, Default)]
+pub struct DistributionFailures(Arc<std::sync::Mutex<BTreeMap<Url, String>>>);
+
+impl DistributionFailures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, url: Url, error: impl ToString) {
+        self.lock().insert(url, error.to_string());
+    }
+
+    /// Get the failures recorded so far.
+    pub fn get(&self) -> BTreeMap<Url, String> {
+        self.lock().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<Url, String>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
 
 pub struct Walker<S: Source, P: Progress> {
-    source: S,
+    pub(crate) source: S,
     progress: P,
     distribution_filter: Option<DistributionFilter>,
+    cancellation: CancellationToken,
+    error_policy: ErrorPolicy,
+    failures: DistributionFailures,
+    deduplication: Deduplication,
 }
 
 impl<S: Source> Walker<S, ()> {
@@ -39,6 +93,10 @@ impl<S: Source> Walker<S, ()> {
             source,
             progress: (),
             distribution_filter: None,
+            cancellation: CancellationToken::new(),
+            error_policy: ErrorPolicy::default(),
+            failures: DistributionFailures::default(),
+            deduplication: Deduplication::default(),
         }
     }
 }
@@ -49,6 +107,88 @@ impl<S: Source, P: Progress> Walker<S, P> {
             progress,
             source: self.source,
             distribution_filter: self.distribution_filter,
+            cancellation: self.cancellation,
+            error_policy: self.error_policy,
+            failures: self.failures,
+            deduplication: self.deduplication,
+        }
+    }
+
+    /// Set how to remove advisories discovered more than once, before they are visited.
+    ///
+    /// Deduplicating requires the indexes of all distributions of a provider to be loaded before
+    /// the first advisory can be visited.
+    pub fn with_deduplication(mut self, deduplication: Deduplication) -> Self {
+        self.deduplication = deduplication;
+        self
+    }
+
+    fn deduplicate(&self, advisories: Vec<DiscoveredAdvisory>) -> Vec<DiscoveredAdvisory> {
+        let total = advisories.len();
+        let advisories = self.deduplication.deduplicate(advisories);
+        let removed = total - advisories.len();
+        if removed > 0 {
+            log::info!("Removed {removed} duplicate advisories");
+        }
+        advisories
+    }
+
+    /// Set how to handle a distribution failing to load its index.
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Set where to record distributions failing to load their index.
+    pub fn with_distribution_failures(mut self, failures: DistributionFailures) -> Self {
+        self.failures = failures;
+        self
+    }
+
+    /// Load the index of a distribution, handling a failure according to the error policy.
+    ///
+    /// Returns [`None`] if the distribution should be skipped.
+    async fn load_index<VE>(
+        &self,
+        distribution: DistributionContext,
+    ) -> Result<Option<Vec<DiscoveredAdvisory>>, Error<VE, S::Error>>
+    where
+        VE: std::fmt::Display + Debug,
+    {
+        let url = distribution.url().clone();
+
+        match self.source.load_index(distribution).await {
+            Ok(index) => Ok(Some(index)),
+            Err(err) => {
+                self.failures.record(url.clone(), &err);
+                match self.error_policy {
+                    ErrorPolicy::FailFast => Err(Error::Source(err)),
+                    ErrorPolicy::Continue => {
+                        log::warn!("Skipping distribution {url}, failed to load index: {err}");
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Set a token for cancelling the walk.
+    ///
+    /// Once cancelled, advisories already being visited will be completed, but no new ones will be
+    /// started. The walk will then fail with [`Error::Cancelled`].
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    fn check_cancelled<VE, SE>(&self) -> Result<(), Error<VE, SE>>
+    where
+        VE: std::fmt::Display + Debug,
+        SE: std::fmt::Display + Debug,
+    {
+        match self.cancellation.is_cancelled() {
+            true => Err(Error::Cancelled),
+            false => Ok(()),
         }
     }
 
@@ -58,7 +198,7 @@ impl<S: Source, P: Progress> Walker<S, P> {
     /// will not even be fetched.
     pub fn with_distribution_filter<F>(mut self, distribution_filter: F) -> Self
     where
-        F: Fn(&DistributionContext) -> bool + 'static,
+        F: Fn(&DistributionContext) -> bool + Send + Sync + 'static,
     {
         self.distribution_filter = Some(Box::new(distribution_filter));
         self
@@ -72,7 +212,7 @@ impl<S: Source, P: Progress> Walker<S, P> {
                     .rolie
                     .into_iter()
                     .flat_map(|rolie| rolie.feeds)
-                    .map(|feed| DistributionContext::Feed(feed.url))
+                    .map(DistributionContext::Feed)
                     .chain(
                         distribution
                             .directory_url
@@ -89,15 +229,115 @@ impl<S: Source, P: Progress> Walker<S, P> {
             .collect()
     }
 
+    /// Resolve all ROLIE service documents into feeds.
+    ///
+    /// Feeds announced by a service document will be added to the distribution, unless the feed
+    /// is already listed. A service document failing to load is handled according to the error
+    /// policy, like a failing distribution.
+    async fn resolve_services<VE>(
+        &self,
+        mut metadata: ProviderMetadata,
+    ) -> Result<ProviderMetadata, Error<VE, S::Error>>
+    where
+        VE: std::fmt::Display + Debug,
+    {
+        for distribution in &mut metadata.distributions {
+            let Some(rolie) = &mut distribution.rolie else {
+                continue;
+            };
+
+            for service in &rolie.services {
+                log::info!("Resolving ROLIE service document: {service}");
+
+                let feeds = match self.source.load_service(service.clone()).await {
+                    Ok(feeds) => feeds,
+                    Err(err) => {
+                        self.failures.record(service.clone(), &err);
+                        match self.error_policy {
+                            ErrorPolicy::FailFast => return Err(Error::Source(err)),
+                            ErrorPolicy::Continue => {
+                                log::warn!(
+                                    "Skipping ROLIE service document {service}, failed to load: {err}"
+                                );
+                                continue;
+                            }
+                        }
+                    }
+                };
+
+                for feed in feeds {
+                    if rolie.feeds.iter().all(|existing| existing.url != feed.url) {
+                        log::debug!("  Discovered feed: {}", feed.url);
+                        rolie.feeds.push(feed);
+                    }
+                }
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Resolve all ROLIE category documents, returning the categories they list.
+    ///
+    /// Category documents only describe the categories used by the feeds, so failing to load one
+    /// doesn't fail the walk.
+    async fn resolve_categories(&self, metadata: &ProviderMetadata) -> Vec<Category> {
+        let mut result: Vec<Category> = vec![];
+
+        for url in metadata
+            .distributions
+            .iter()
+            .filter_map(|distribution| distribution.rolie.as_ref())
+            .flat_map(|rolie| &rolie.categories)
+        {
+            log::info!("Resolving ROLIE category document: {url}");
+
+            match self.source.load_categories(url.clone()).await {
+                Ok(categories) => {
+                    for category in categories {
+                        if !result.contains(&category) {
+                            result.push(category);
+                        }
+                    }
+                }
+                Err(err) => log::warn!("Failed to load ROLIE category document ({url}): {err}"),
+            }
+        }
+
+        result
+    }
+
     pub async fn walk<V>(self, visitor: V) -> Result<(), Error<V::Error, S::Error>>
     where
         V: DiscoveredVisitor,
     {
-        let metadata = self.source.load_metadata().await.map_err(Error::Source)?;
+        let providers = self.source.load_providers().await.map_err(Error::Source)?;
+
+        for metadata in providers {
+            self.check_cancelled()?;
+            self.walk_provider(&visitor, metadata).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn walk_provider<V>(
+        &self,
+        visitor: &V,
+        metadata: ProviderMetadata,
+    ) -> Result<(), Error<V::Error, S::Error>>
+    where
+        V: DiscoveredVisitor,
+    {
+        log::info!("Walking provider: {}", metadata.canonical_url);
+
+        let metadata = self.resolve_services(metadata).await?;
+        let categories = self.resolve_categories(&metadata).await;
 
         let context = visitor
             .visit_context(&DiscoveredContext {
                 metadata: &metadata,
+                categories: &categories,
             })
             .await
             .map_err(Error::Visitor)?;
@@ -105,42 +345,127 @@ impl<S: Source, P: Progress> Walker<S, P> {
         let distributions = self.collect_distributions(metadata.distributions);
         log::info!("processing {} distribution URLs", distributions.len());
 
+        // advisories to deduplicate, once all indexes are loaded
+        let mut pending = vec![];
+
         for distribution in distributions {
+            self.check_cancelled()?;
             log::info!("Walking directory URL: {:?}", distribution);
-            let index = self
-                .source
-                .load_index(distribution)
-                .await
-                .map_err(Error::Source)?;
-
-            let mut progress = self.progress.start(index.len());
-
-            for advisory in index {
-                log::debug!("  Discovered advisory: {advisory:?}");
-                progress
-                    .set_message(
-                        advisory
-                            .url
-                            .path()
-                            .rsplit_once('/')
-                            .map(|(_, s)| s)
-                            .unwrap_or(advisory.url.as_str())
-                            .to_string(),
-                    )
-                    .await;
-                visitor
-                    .visit_advisory(&context, advisory)
-                    .await
-                    .map_err(Error::Visitor)?;
-                progress.tick().await;
+            let Some(index) = self.load_index(distribution).await? else {
+                continue;
+            };
+
+            match self.deduplication {
+                Deduplication::None => self.visit_index(visitor, &context, index).await?,
+                _ => pending.extend(index),
             }
+        }
 
-            progress.finish().await;
+        if !pending.is_empty() {
+            let index = self.deduplicate(pending);
+            self.visit_index(visitor, &context, index).await?;
         }
 
         Ok(())
     }
 
+    async fn visit_index<V>(
+        &self,
+        visitor: &V,
+        context: &V::Context,
+        index: Vec<DiscoveredAdvisory>,
+    ) -> Result<(), Error<V::Error, S::Error>>
+    where
+        V: DiscoveredVisitor,
+    {
+        let mut progress = self.progress.start(index.len());
+
+        for advisory in index {
+            self.check_cancelled()?;
+            log::debug!("  Discovered advisory: {advisory:?}");
+            progress
+                .set_message(
+                    advisory
+                        .url
+                        .path()
+                        .rsplit_once('/')
+                        .map(|(_, s)| s)
+                        .unwrap_or(advisory.url.as_str())
+                        .to_string(),
+                )
+                .await;
+            visitor
+                .visit_advisory(context, advisory)
+                .await
+                .map_err(Error::Visitor)?;
+            progress.tick().await;
+        }
+
+        progress.finish().await;
+
+        Ok(())
+    }
+
+    /// Load the indexes of all distributions, sending them to the channel.
+    ///
+    /// When deduplicating, a single index is sent once all indexes are loaded. Returns the number
+    /// of discovered advisories.
+    async fn load_indexes<V>(
+        &self,
+        distributions: Vec<DistributionContext>,
+        mut tx: mpsc::Sender<Vec<DiscoveredAdvisory>>,
+        progress: &Mutex<P::Instance>,
+    ) -> Result<usize, Error<V::Error, S::Error>>
+    where
+        V: DiscoveredVisitor,
+    {
+        let mut size = 0;
+        let mut pending = vec![];
+
+        for distribution in distributions {
+            if self.cancellation.is_cancelled() {
+                // processing will report the cancellation
+                return Ok(size);
+            }
+
+            log::debug!("Walking: {}", distribution.url());
+
+            let Some(index) = self.load_index::<V::Error>(distribution).await? else {
+                continue;
+            };
+
+            if self.deduplication != Deduplication::None {
+                pending.extend(index);
+                continue;
+            }
+
+            size += index.len();
+            progress.lock().await.add_work(index.len()).await;
+
+            if tx.send(index).await.is_err() {
+                // processing stopped, and will report the reason
+                return Ok(size);
+            }
+        }
+
+        if !pending.is_empty() {
+            let index = self.deduplicate(pending);
+
+            size += index.len();
+            progress.lock().await.add_work(index.len()).await;
+
+            // if processing stopped, it will report the reason
+            let _ = tx.send(index).await;
+        }
+
+        Ok(size)
+    }
+
+    /// Walk all advisories, visiting up to `limit` advisories concurrently.
+    ///
+    /// The advisories are visited as part of the returned future, so they are processed
+    /// concurrently, but not in parallel. Use [`Self::walk_parallel_spawned`] to spread them over
+    /// the worker threads of a multi-threaded runtime.
     pub async fn walk_parallel<V>(
         self,
         limit: usize,
@@ -149,76 +474,515 @@ impl<S: Source, P: Progress> Walker<S, P> {
     where
         V: DiscoveredVisitor,
     {
-        let metadata = self.source.load_metadata().await.map_err(Error::Source)?;
+        let visitor = &visitor;
+        let providers = self.source.load_providers().await.map_err(Error::Source)?;
+
+        for metadata in providers {
+            self.check_cancelled()?;
+            self.walk_provider_parallel(
+                limit,
+                visitor,
+                metadata,
+                move |context, advisory| async move {
+                    visitor
+                        .visit_advisory(&context, advisory)
+                        .await
+                        .map_err(Error::Visitor)
+                },
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Walk all advisories, visiting up to `limit` advisories concurrently, each in its own task.
+    ///
+    /// Unlike [`Self::walk_parallel`], the visits can run on all worker threads of a
+    /// multi-threaded runtime. This must be called from within a Tokio runtime, and requires a
+    /// visitor which can be moved into a task.
+    pub async fn walk_parallel_spawned<V>(
+        self,
+        limit: usize,
+        visitor: V,
+    ) -> Result<(), Error<V::Error, S::Error>>
+    where
+        V: DiscoveredVisitor + 'static,
+        V::Context: 'static,
+        V::Error: 'static,
+    {
+        let visitor = Arc::new(visitor);
+        let providers = self.source.load_providers().await.map_err(Error::Source)?;
+
+        for metadata in providers {
+            self.check_cancelled()?;
+            self.walk_provider_parallel(limit, &*visitor, metadata, |context, advisory| {
+                let visitor = visitor.clone();
+                let task = AbortOnDropHandle::new(tokio::spawn(async move {
+                    visitor.visit_advisory(&context, advisory).await
+                }));
+
+                async move {
+                    match task.await {
+                        Ok(result) => result.map_err(Error::Visitor),
+                        Err(err) => match err.try_into_panic() {
+                            Ok(panic) => std::panic::resume_unwind(panic),
+                            Err(_) => Err(Error::Cancelled),
+                        },
+                    }
+                }
+            })
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Walk a single provider, visiting up to `limit` advisories concurrently using `visit`.
+    async fn walk_provider_parallel<V, F, Fut>(
+        &self,
+        limit: usize,
+        visitor: &V,
+        metadata: ProviderMetadata,
+        visit: F,
+    ) -> Result<(), Error<V::Error, S::Error>>
+    where
+        V: DiscoveredVisitor,
+        F: Fn(Arc<V::Context>, DiscoveredAdvisory) -> Fut,
+        Fut: Future<Output = Result<(), Error<V::Error, S::Error>>>,
+    {
+        log::info!("Walking provider: {}", metadata.canonical_url);
+
+        let metadata = self.resolve_services(metadata).await?;
+        let categories = self.resolve_categories(&metadata).await;
+
         let context = visitor
             .visit_context(&DiscoveredContext {
                 metadata: &metadata,
+                categories: &categories,
             })
             .await
             .map_err(Error::Visitor)?;
-
         let context = Arc::new(context);
-        let visitor = Arc::new(visitor);
 
         let distributions = self.collect_distributions(metadata.distributions);
         log::info!("processing {} distribution URLs", distributions.len());
 
-        let advisories: Vec<_> = collect_advisories::<V, S>(&self.source, distributions)
-            .try_collect()
-            .await?;
-
-        let size = advisories.len();
-        log::info!("Discovered {size} advisories");
+        // the total grows as the indexes arrive
+        let progress = Arc::new(Mutex::new(self.progress.start(0)));
 
-        let progress = Arc::new(Mutex::new(self.progress.start(size)));
+        // the index of the next distribution is loaded while the current one is being processed
+        let (tx, rx) = mpsc::channel(1);
 
-        stream::iter(advisories)
+        let load = self.load_indexes::<V>(distributions, tx, &progress);
+        let process = rx
+            .flat_map(stream::iter)
+            .take_until(self.cancellation.cancelled())
             .map(Ok)
-            .try_for_each_concurrent(limit, async |advisory| {
+            .try_for_each_concurrent(limit, |advisory: DiscoveredAdvisory| {
                 log::debug!("Discovered advisory: {}", advisory.url);
 
-                let result = visitor
-                    .visit_advisory(&context, advisory.clone())
-                    .map_err(Error::Visitor)
-                    .await;
+                let visit = visit(context.clone(), advisory);
+                let progress = progress.clone();
+                async move {
+                    let result = visit.await;
 
-                progress.lock().await.tick().await;
+                    progress.lock().await.tick().await;
 
-                result
-            })
-            .await?;
+                    result
+                }
+            });
 
-        if let Ok(progress) = Arc::try_unwrap(progress) {
-            let progress = progress.into_inner();
-            progress.finish().await;
+        let (size, ()) = futures::try_join!(load, process)?;
+        self.check_cancelled()?;
+        log::info!("Processed {size} advisories");
+
+        if let Some(progress) = Arc::into_inner(progress) {
+            progress.into_inner().finish().await;
         }
 
         Ok(())
     }
 }
 
-#[allow(clippy::needless_lifetimes)] // false positive
-fn collect_sources<'s, V: DiscoveredVisitor, S: Source>(
-    source: &'s S,
-    discover_contexts: Vec<DistributionContext>,
-) -> impl TryStream<Ok = impl Stream<Item = DiscoveredAdvisory>, Error = Error<V::Error, S::Error>> + 's
-{
-    stream::iter(discover_contexts).then(async |discover_context| {
-        log::debug!("Walking: {}", discover_context.url());
-        Ok(stream::iter(
-            source
-                .load_index(discover_context.clone())
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{model::metadata::Feed, retrieve::RetrievedAdvisory};
+    use std::{sync::Arc, time::Duration, time::SystemTime};
+    use tokio::sync::watch;
+
+    /// A source with a number of directory distributions, counting the loaded indexes.
+    #[derive(Clone, Debug)]
+    struct MockSource {
+        distributions: usize,
+        advisories: usize,
+        /// The index of the last distribution can only be loaded once an advisory was visited
+        blocking: bool,
+        /// A distribution failing to load its index
+        failing: Option<usize>,
+        /// Add a ROLIE distribution, with a service document failing to load
+        failing_service: bool,
+        loaded: Arc<watch::Sender<usize>>,
+        visited: Arc<watch::Sender<usize>>,
+    }
+
+    impl walker_common::source::Source for MockSource {
+        type Error = anyhow::Error;
+        type Retrieved = RetrievedAdvisory;
+    }
+
+    impl Source for MockSource {
+        async fn load_metadata(&self) -> Result<ProviderMetadata, Self::Error> {
+            let mut distributions = (0..self.distributions)
+                .map(|i| serde_json::json!({"directory_url": format!("https://example.com/{i}/")}))
+                .collect::<Vec<_>>();
+            if self.failing_service {
+                distributions.push(serde_json::json!({
+                    "rolie": {"services": ["https://example.com/service.json"]}
+                }));
+            }
+
+            Ok(serde_json::from_value(serde_json::json!({
+                "canonical_url": "https://example.com/provider-metadata.json",
+                "distributions": distributions,
+                "last_updated": "2024-01-01T00:00:00Z",
+                "metadata_version": "2.0",
+                "publisher": {
+                    "category": "vendor",
+                    "contact_details": "",
+                    "name": "Example",
+                    "namespace": "https://example.com",
+                },
+            }))?)
+        }
+
+        async fn load_index(
+            &self,
+            context: DistributionContext,
+        ) -> Result<Vec<DiscoveredAdvisory>, Self::Error> {
+            if self
+                .failing
+                .is_some_and(|failing| context.url().path() == format!("/{failing}/"))
+            {
+                anyhow::bail!("failed to load index");
+            }
+
+            if self.blocking && context.url().path() == format!("/{}/", self.distributions - 1) {
+                let mut visited = self.visited.subscribe();
+                tokio::time::timeout(Duration::from_secs(5), visited.wait_for(|n| *n > 0))
+                    .await
+                    .map_err(|_| anyhow::anyhow!("nothing was visited"))??;
+            }
+
+            let context = Arc::new(context);
+            let index = (0..self.advisories)
+                .map(|i| DiscoveredAdvisory {
+                    url: context.url().join(&format!("{i}.json")).expect("must join"),
+                    context: context.clone(),
+                    digest: None,
+                    signature: None,
+                    modified: SystemTime::UNIX_EPOCH,
+                    categories: vec![],
+                    page: None,
+                    local: None,
+                })
+                .collect();
+
+            self.loaded.send_modify(|loaded| *loaded += 1);
+
+            Ok(index)
+        }
+
+        async fn load_service(&self, _url: Url) -> Result<Vec<Feed>, Self::Error> {
+            anyhow::bail!("failed to load service document")
+        }
+
+        async fn load_advisory(
+            &self,
+            _advisory: DiscoveredAdvisory,
+        ) -> Result<Option<RetrievedAdvisory>, Self::Error> {
+            anyhow::bail!("advisories are not retrieved by the walker")
+        }
+    }
+
+    #[tokio::test]
+    async fn parallel() {
+        let loaded = Arc::new(watch::Sender::new(0));
+        let visited = Arc::new(watch::Sender::new(0));
+        let source = MockSource {
+            distributions: 3,
+            advisories: 5,
+            blocking: true,
+            failing: None,
+            failing_service: false,
+            loaded: loaded.clone(),
+            visited: visited.clone(),
+        };
+
+        Walker::new(source)
+            .walk_parallel(2, {
+                let visited = visited.clone();
+                move |advisory: DiscoveredAdvisory| {
+                    let visited = visited.clone();
+                    let mut loaded = loaded.subscribe();
+                    async move {
+                        // the first distribution can only be processed when the next index gets loaded
+                        if advisory.url.path().starts_with("/0/") {
+                            tokio::time::timeout(
+                                Duration::from_secs(5),
+                                loaded.wait_for(|loaded| *loaded > 1),
+                            )
+                            .await
+                            .map_err(|_| "index of the next distribution was not loaded")?
+                            .map_err(|_| "source dropped")?;
+                        }
+                        visited.send_modify(|visited| *visited += 1);
+                        Ok::<_, &str>(())
+                    }
+                }
+            })
+            .await
+            .expect("must walk");
+
+        assert_eq!(*visited.borrow(), 15);
+    }
+
+    async fn walk_cancelled(workers: usize) {
+        let visited = Arc::new(watch::Sender::new(0));
+        let source = MockSource {
+            distributions: 2,
+            advisories: 5,
+            blocking: false,
+            failing: None,
+            failing_service: false,
+            loaded: Arc::new(watch::Sender::new(0)),
+            visited: visited.clone(),
+        };
+
+        let cancellation = CancellationToken::new();
+        let walker = Walker::new(source).with_cancellation(cancellation.clone());
+        let visitor = {
+            let visited = visited.clone();
+            move |_: DiscoveredAdvisory| {
+                let visited = visited.clone();
+                let cancellation = cancellation.clone();
+                async move {
+                    cancellation.cancel();
+                    visited.send_modify(|visited| *visited += 1);
+                    Ok::<_, &str>(())
+                }
+            }
+        };
+
+        let result = match workers {
+            1 => walker.walk(visitor).await,
+            n => walker.walk_parallel(n, visitor).await,
+        };
+
+        assert!(matches!(result, Err(Error::Cancelled)));
+        // advisories started before the cancellation was noticed are completed
+        assert!((1..=workers).contains(&*visited.borrow()));
+    }
+
+    async fn walk_deduplicated(workers: usize, deduplication: Deduplication) -> usize {
+        let visited = Arc::new(watch::Sender::new(0));
+        let source = MockSource {
+            distributions: 3,
+            advisories: 5,
+            blocking: false,
+            failing: None,
+            failing_service: false,
+            loaded: Arc::new(watch::Sender::new(0)),
+            visited: visited.clone(),
+        };
+
+        let walker = Walker::new(source).with_deduplication(deduplication);
+        let visitor = {
+            let visited = visited.clone();
+            move |_: DiscoveredAdvisory| {
+                visited.send_modify(|visited| *visited += 1);
+                async { Ok::<_, &str>(()) }
+            }
+        };
+
+        match workers {
+            1 => walker.walk(visitor).await,
+            n => walker.walk_parallel(n, visitor).await,
+        }
+        .expect("must walk");
+
+        *visited.borrow()
+    }
+
+    #[tokio::test]
+    async fn deduplicate() {
+        for workers in [1, 2] {
+            // all distributions have advisories with the same filenames
+            assert_eq!(walk_deduplicated(workers, Deduplication::None).await, 15);
+            assert_eq!(walk_deduplicated(workers, Deduplication::Url).await, 15);
+            assert_eq!(walk_deduplicated(workers, Deduplication::Filename).await, 5);
+        }
+    }
+
+    async fn walk_failing(
+        error_policy: ErrorPolicy,
+        failures: DistributionFailures,
+    ) -> Result<usize, Error<&'static str, anyhow::Error>> {
+        let visited = Arc::new(watch::Sender::new(0));
+        let source = MockSource {
+            distributions: 3,
+            advisories: 5,
+            blocking: false,
+            failing: Some(1),
+            failing_service: false,
+            loaded: Arc::new(watch::Sender::new(0)),
+            visited: visited.clone(),
+        };
+
+        Walker::new(source)
+            .with_error_policy(error_policy)
+            .with_distribution_failures(failures)
+            .walk({
+                let visited = visited.clone();
+                move |_: DiscoveredAdvisory| {
+                    visited.send_modify(|visited| *visited += 1);
+                    async { Ok::<_, &str>(()) }
+                }
+            })
+            .await?;
+
+        Ok(*visited.borrow())
+    }
+
+    #[tokio::test]
+    async fn error_policy() {
+        let failures = DistributionFailures::new();
+        let result = walk_failing(ErrorPolicy::FailFast, failures.clone()).await;
+        assert!(matches!(result, Err(Error::Source(_))));
+        assert_eq!(failures.len(), 1);
+
+        let failures = DistributionFailures::new();
+        let visited = walk_failing(ErrorPolicy::Continue, failures.clone())
+            .await
+            .expect("must walk");
+        assert_eq!(visited, 10);
+        assert_eq!(
+            failures.get().into_iter().collect::<Vec<_>>(),
+            vec![(
+                Url::parse("https://example.com/1/").expect("must parse"),
+                "failed to load index".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn service_error_policy() {
+        for workers in [1, 2] {
+            let walk = |error_policy, failures| {
+                let visited = Arc::new(watch::Sender::new(0));
+                let source = MockSource {
+                    distributions: 2,
+                    advisories: 5,
+                    blocking: false,
+                    failing: None,
+                    failing_service: true,
+                    loaded: Arc::new(watch::Sender::new(0)),
+                    visited: visited.clone(),
+                };
+                let walker = Walker::new(source)
+                    .with_error_policy(error_policy)
+                    .with_distribution_failures(failures);
+                let visitor = move |_: DiscoveredAdvisory| {
+                    visited.send_modify(|visited| *visited += 1);
+                    async { Ok::<_, &str>(()) }
+                };
+
+                async move {
+                    match workers {
+                        1 => walker.walk(visitor).await,
+                        n => walker.walk_parallel(n, visitor).await,
+                    }
+                }
+            };
+
+            let failures = DistributionFailures::new();
+            let result = walk(ErrorPolicy::FailFast, failures.clone()).await;
+            assert!(matches!(result, Err(Error::Source(_))));
+            assert_eq!(failures.len(), 1);
+
+            let failures = DistributionFailures::new();
+            walk(ErrorPolicy::Continue, failures.clone())
                 .await
-                .map_err(Error::Source)?,
-        ))
-    })
-}
+                .expect("must walk");
+            assert_eq!(
+                failures.get().into_keys().collect::<Vec<_>>(),
+                vec![Url::parse("https://example.com/service.json").expect("must parse")]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn cancel() {
+        walk_cancelled(1).await;
+    }
 
-fn collect_advisories<'s, V: DiscoveredVisitor + 's, S: Source>(
-    source: &'s S,
-    discover_contexts: Vec<DistributionContext>,
-) -> impl TryStream<Ok = DiscoveredAdvisory, Error = Error<V::Error, S::Error>> + 's {
-    collect_sources::<V, S>(source, discover_contexts)
-        .map_ok(|s| s.map(Ok))
-        .try_flatten()
+    #[tokio::test]
+    async fn cancel_parallel() {
+        walk_cancelled(2).await;
+    }
+
+    fn source(visited: &Arc<watch::Sender<usize>>) -> MockSource {
+        MockSource {
+            distributions: 2,
+            advisories: 5,
+            blocking: false,
+            failing: None,
+            failing_service: false,
+            loaded: Arc::new(watch::Sender::new(0)),
+            visited: visited.clone(),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn spawned() {
+        let visited = Arc::new(watch::Sender::new(0));
+        let walker = Walker::new(source(&visited));
+
+        // the test itself doesn't run on a worker thread
+        let main = std::thread::current().id();
+        let visitor = {
+            let visited = visited.clone();
+            move |_: DiscoveredAdvisory| {
+                let visited = visited.clone();
+                async move {
+                    if std::thread::current().id() == main {
+                        return Err("visited outside of the worker threads");
+                    }
+                    visited.send_modify(|visited| *visited += 1);
+                    Ok::<_, &'static str>(())
+                }
+            }
+        };
+
+        tokio::spawn(walker.walk_parallel_spawned(2, visitor))
+            .await
+            .expect("must join")
+            .expect("must walk");
+
+        assert_eq!(*visited.borrow(), 10);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "visitor panicked")]
+    async fn panic_parallel() {
+        let visited = Arc::new(watch::Sender::new(0));
+        let _ = Walker::new(source(&visited))
+            .walk_parallel_spawned(2, |_: DiscoveredAdvisory| async {
+                panic!("visitor panicked");
+                #[allow(unreachable_code)]
+                Ok::<_, &str>(())
+            })
+            .await;
+    }
 }
@@ -1,8 +1,10 @@
 use crate::visitors::SendVisitor;
 use reqwest::Url;
 use std::path::PathBuf;
-use walker_common::sender::{
-    HttpSender, HttpSenderOptions, provider::OpenIdTokenProviderConfigArguments,
+use walker_common::{
+    cli::client::parse_header,
+    client::{ClientIdentity, ClientOptions},
+    sender::{HttpSender, HttpSenderOptions, provider::OpenIdTokenProviderConfigArguments},
 };
 
 #[derive(Debug, clap::Parser)]
@@ -37,6 +39,43 @@ pub struct SendArguments {
     #[arg(id = "sender-tls-insecure", long)]
     pub tls_insecure: bool,
 
+    /// Proxy to use for contacting the target, overriding the proxy environment variables
+    #[arg(id = "sender-proxy", long)]
+    pub proxy: Option<Url>,
+
+    /// Hosts which should not use the sender proxy
+    #[arg(
+        id = "sender-no-proxy",
+        long,
+        value_delimiter = ',',
+        requires = "sender-proxy"
+    )]
+    pub no_proxy: Vec<String>,
+
+    /// Client certificate (PEM) for mutual TLS with the target
+    #[arg(
+        id = "sender-tls-client-certificate",
+        long,
+        requires = "sender-tls-client-key"
+    )]
+    pub tls_client_certificate: Option<PathBuf>,
+
+    /// Private key (PKCS #8, PEM) of the sender client certificate
+    #[arg(
+        id = "sender-tls-client-key",
+        long,
+        requires = "sender-tls-client-certificate"
+    )]
+    pub tls_client_key: Option<PathBuf>,
+
+    /// Override the user agent used for contacting the target
+    #[arg(id = "sender-user-agent", long)]
+    pub user_agent: Option<String>,
+
+    /// Additional header sent with every request to the target, in the format of `<name>: <value>`
+    #[arg(id = "sender-header", long, value_parser = parse_header)]
+    pub headers: Vec<(String, String)>,
+
     /// Number of retries in case of temporary failures
     #[arg(
         id = "sender-retries",
@@ -85,6 +124,12 @@ impl SendArguments {
             timeout,
             additional_root_certificates,
             tls_insecure,
+            proxy,
+            no_proxy,
+            tls_client_certificate,
+            tls_client_key,
+            user_agent,
+            headers,
             retries,
             min_delay,
             max_delay,
@@ -92,12 +137,25 @@ impl SendArguments {
             query,
         } = self;
 
+        let identity = match (tls_client_certificate, tls_client_key) {
+            (Some(certificate), Some(key)) => Some(ClientIdentity { certificate, key }),
+            _ => None,
+        };
+
         let provider = oidc.into_provider().await?;
         let sender = HttpSender::new(
             provider,
             HttpSenderOptions::default()
                 .connect_timeout(Some(connect_timeout.into()))
                 .timeout(Some(timeout.into()))
+                .client(
+                    ClientOptions::new()
+                        .proxy(proxy)
+                        .no_proxy(no_proxy)
+                        .identity(identity)
+                        .user_agent(user_agent)
+                        .headers(headers),
+                )
                 .tls_insecure(tls_insecure)
                 .additional_root_certificates(additional_root_certificates)
                 .query_parameters(query.into_iter().map(|entry| match entry.split_once('=') {
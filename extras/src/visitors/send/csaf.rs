@@ -11,7 +11,13 @@ pub enum SendRetrievedAdvisoryError<S: Source> {
     #[error(transparent)]
     Store(#[from] SendError),
     #[error(transparent)]
-    Retrieval(#[from] RetrievalError<DiscoveredAdvisory, S>),
+    Retrieval(Box<RetrievalError<DiscoveredAdvisory, S>>),
+}
+
+impl<S: Source> From<RetrievalError<DiscoveredAdvisory, S>> for SendRetrievedAdvisoryError<S> {
+    fn from(value: RetrievalError<DiscoveredAdvisory, S>) -> Self {
+        Self::Retrieval(Box::new(value))
+    }
 }
 
 impl<S: Source> RetrievedVisitor<S> for SendVisitor {
@@ -73,7 +79,7 @@ impl SendVisitor {
             ..
         } = advisory;
 
-        self.send_json(url.as_str(), data).await
+        self.send_json(url.as_str(), data.bytes().await?).await
     }
 
     pub async fn send_json(&self, name: &str, data: Bytes) -> Result<(), SendError> {
@@ -31,6 +31,8 @@ pub enum SendError {
     Server(StatusCode),
     #[error("unexpected status: {0}")]
     UnexpectedStatus(StatusCode),
+    #[error("failed to read data: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 /// Send data to a remote sink.
@@ -86,7 +86,7 @@ impl SendVisitor {
 
         let bzip2 = name.ends_with(".bz2");
 
-        self.send(url.as_str(), data, |mut request| {
+        self.send(url.as_str(), data.bytes().await?, |mut request| {
             request = request
                 .query(&[("id", name)])
                 .header(header::CONTENT_TYPE, "application/json");
@@ -87,14 +87,16 @@ impl Inspect {
                     discovered: DiscoveredSbom {
                         url,
                         modified: SystemTime::now(),
+                        local: None,
                     },
-                    data,
+                    data: data.into(),
                     signature: None,
                     sha256: None,
                     sha512: None,
                     metadata: Default::default(),
                 },
             },
+            &Default::default(),
         );
 
         Ok(())
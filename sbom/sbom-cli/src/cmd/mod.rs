@@ -3,6 +3,7 @@ use flexible_time::timestamp::StartTimestamp;
 use reqwest::Url;
 use sbom_walker::visitors::store::StoreVisitor;
 use std::path::PathBuf;
+use walker_common::{cli::retrieval::RetrievalArguments, store::Storage};
 
 pub mod discover;
 pub mod download;
@@ -21,6 +22,9 @@ pub struct DiscoverArguments {
     #[arg(short = 'k', long = "key")]
     /// URLs to keys which should be used for validation. The fragment part of a key can be used as the fingerprint.
     pub keys: Vec<Url>,
+
+    #[command(flatten)]
+    pub retrieval: RetrievalArguments,
 }
 
 #[derive(Debug, clap::Parser)]
@@ -30,9 +34,9 @@ pub struct StoreArguments {
     #[arg(long)]
     pub no_timestamps: bool,
 
-    /// Output path, defaults to the local directory.
+    /// Output path, defaults to the local directory. Using `s3://[<key id>:<access key>@]<region>/<bucket>[/<prefix>]`, SBOMs are stored in an S3 compatible bucket.
     #[arg(short, long)]
-    pub data: Option<PathBuf>,
+    pub data: Option<Storage>,
 }
 
 impl TryFrom<StoreArguments> for StoreVisitor {
@@ -41,7 +45,9 @@ impl TryFrom<StoreArguments> for StoreVisitor {
     fn try_from(value: StoreArguments) -> Result<Self, Self::Error> {
         let base = match value.data {
             Some(base) => base,
-            None => std::env::current_dir().context("Get the current working directory")?,
+            None => std::env::current_dir()
+                .context("Get the current working directory")?
+                .into(),
         };
 
         Ok(Self::new(base).no_timestamps(value.no_timestamps))
@@ -24,6 +24,7 @@ use walker_common::{
         CommandDefaults, client::ClientArguments, runner::RunnerArguments,
         validation::ValidationArguments,
     },
+    compression::DecompressionOptions,
     progress::Progress,
     report::{self, Statistics},
     utils::url::Urlify,
@@ -81,6 +82,8 @@ pub struct RenderOptions {
 impl Report {
     pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
         let options: ValidationOptions = self.validation.into();
+        let decompression =
+            DecompressionOptions::new().limit(self.discover.retrieval.max_document_size);
 
         let total: Arc<AtomicUsize> = Default::default();
         let errors: Arc<Mutex<BTreeMap<String, Vec<String>>>> = Default::default();
@@ -99,6 +102,7 @@ impl Report {
                         ValidationVisitor::new(
                             move |sbom: Result<ValidatedSbom, ValidationError<DispatchSource>>| {
                                 let errors = errors.clone();
+                                let decompression = decompression.clone();
                                 total.fetch_add(1, Ordering::SeqCst);
                                 async move {
                                     let name = match &sbom {
@@ -107,7 +111,7 @@ impl Report {
                                     };
 
                                     task::spawn_blocking(move || {
-                                        inspect(&(name, errors), sbom);
+                                        inspect(&(name, errors), sbom, &decompression);
                                     })
                                     .await
                                     .expect("unable to spawn inspection");
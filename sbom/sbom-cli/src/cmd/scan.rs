@@ -9,7 +9,7 @@ use walker_common::{
         CommandDefaults, client::ClientArguments, runner::RunnerArguments,
         validation::ValidationArguments,
     },
-    compression::decompress,
+    compression::{DecompressionOptions, decompress_with},
     progress::Progress,
     validate::ValidationError,
 };
@@ -34,45 +34,54 @@ impl CommandDefaults for Scan {}
 
 impl Scan {
     pub async fn run<P: Progress>(self, progress: P) -> anyhow::Result<()> {
+        let decompression =
+            DecompressionOptions::new().limit(self.discover.retrieval.max_document_size);
+
         walk_standard(
             progress,
             self.client,
             self.runner,
             self.discover,
             self.validation,
-            async |advisory: Result<ValidatedSbom, ValidationError<DispatchSource>>| {
-                match advisory {
-                    Ok(sbom) => {
-                        println!("Advisory: {}", sbom.url);
-                        log::debug!("  Metadata: {:?}", sbom.sha256);
-                        log::debug!("    SHA256: {:?}", sbom.sha256);
-                        log::debug!("    SHA512: {:?}", sbom.sha512);
+            move |advisory: Result<ValidatedSbom, ValidationError<DispatchSource>>| {
+                let decompression = decompression.clone();
+                async move {
+                    match advisory {
+                        Ok(sbom) => {
+                            println!("Advisory: {}", sbom.url);
+                            log::debug!("  Metadata: {:?}", sbom.sha256);
+                            log::debug!("    SHA256: {:?}", sbom.sha256);
+                            log::debug!("    SHA512: {:?}", sbom.sha512);
 
-                        let ValidatedSbom {
-                            retrieved:
-                                RetrievedSbom {
-                                    data,
-                                    discovered: DiscoveredSbom { url, .. },
-                                    ..
-                                },
-                        } = sbom;
+                            let ValidatedSbom {
+                                retrieved:
+                                    RetrievedSbom {
+                                        data,
+                                        discovered: DiscoveredSbom { url, .. },
+                                        ..
+                                    },
+                            } = sbom;
 
-                        let data =
-                            task::spawn_blocking(move || decompress(data, url.path())).await??;
+                            let data = data.bytes().await?;
+                            let data = task::spawn_blocking(move || {
+                                decompress_with(data, url.path(), &decompression)
+                            })
+                            .await??;
 
-                        match Sbom::try_parse_any(&data) {
-                            Ok(sbom) => process_sbom(sbom),
-                            Err(err) => {
-                                eprintln!("  Format error: {err}");
+                            match Sbom::try_parse_any(&data) {
+                                Ok(sbom) => process_sbom(sbom),
+                                Err(err) => {
+                                    eprintln!("  Format error: {err}");
+                                }
                             }
                         }
+                        Err(err) => {
+                            eprintln!("SBOM(ERR): {err}");
+                        }
                     }
-                    Err(err) => {
-                        eprintln!("SBOM(ERR): {err}");
-                    }
-                }
 
-                Ok::<_, anyhow::Error>(())
+                    Ok::<_, anyhow::Error>(())
+                }
             },
         )
         .await?;
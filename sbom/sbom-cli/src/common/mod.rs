@@ -28,7 +28,8 @@ pub async fn walk_standard<V, P>(
     visitor: V,
 ) -> anyhow::Result<()>
 where
-    V: ValidatedVisitor<DispatchSource>,
+    V: ValidatedVisitor<DispatchSource> + 'static,
+    V::Context: 'static,
     V::Error: Send + Sync + 'static,
     P: Progress,
 {
@@ -53,6 +54,7 @@ impl From<DiscoverArguments> for DiscoverConfig {
                 .into_iter()
                 .map(metadata::Key::from)
                 .collect::<Vec<_>>(),
+            retrieval: value.retrieval.into(),
         }
     }
 }
@@ -67,7 +69,8 @@ pub async fn walk_visitor<F, Fut, V, P>(
 where
     F: FnOnce(DispatchSource) -> Fut,
     Fut: Future<Output = anyhow::Result<V>>,
-    V: DiscoveredVisitor,
+    V: DiscoveredVisitor + 'static,
+    V::Context: 'static,
     V::Error: Send + Sync + 'static,
     P: Progress,
 {
@@ -85,21 +88,28 @@ pub async fn walk_source<F, Fut, V, P>(
 where
     F: FnOnce(DispatchSource) -> Fut,
     Fut: Future<Output = anyhow::Result<V>>,
-    V: DiscoveredVisitor,
+    V: DiscoveredVisitor + 'static,
+    V::Context: 'static,
     V::Error: Send + Sync + 'static,
     P: Progress,
 {
     let visitor = f(source.clone()).await?;
-    let walker = Walker::new(source).with_progress(progress);
+    let fetcher = match &source {
+        DispatchSource::Http(source) => Some(source.fetcher().clone()),
+        DispatchSource::File(_) | DispatchSource::S3(_) | DispatchSource::Custom(_) => None,
+    };
+    let walker = Walker::new(source)
+        .with_progress(progress)
+        .with_cancellation(runner.cancellation());
 
-    match runner.workers {
-        1 => {
-            walker.walk(visitor).await?;
-        }
-        n => {
-            walker.walk_parallel(n, visitor).await?;
-        }
+    let result = match runner.workers {
+        1 => walker.walk(visitor).await,
+        n => walker.walk_parallel_spawned(n, visitor).await,
+    };
+
+    if let Some(fetcher) = fetcher {
+        fetcher.stats().log();
     }
 
-    Ok(())
+    Ok(result?)
 }
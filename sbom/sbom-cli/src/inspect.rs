@@ -7,9 +7,16 @@ use sbom_walker::{
     source::Source,
     validation::ValidatedSbom,
 };
-use walker_common::{compression::decompress, validate::ValidationError};
+use walker_common::{
+    compression::{DecompressionOptions, decompress_reader},
+    validate::ValidationError,
+};
 
-pub fn inspect_validated(report: &dyn ReportSink, sbom: ValidatedSbom) {
+pub fn inspect_validated(
+    report: &dyn ReportSink,
+    sbom: ValidatedSbom,
+    decompression: &DecompressionOptions,
+) {
     let ValidatedSbom {
         retrieved:
             RetrievedSbom {
@@ -19,17 +26,15 @@ pub fn inspect_validated(report: &dyn ReportSink, sbom: ValidatedSbom) {
             },
     } = sbom;
 
-    let data = decompress(data, url.path());
-
-    let data = match data {
-        Ok(data) => data,
+    let reader = match decompress_reader(&data, url.path(), decompression) {
+        Ok(reader) => reader,
         Err(err) => {
             report.error(format!("Failed to decode file: {err}"));
             return;
         }
     };
 
-    let mut value = match serde_json::from_slice(&data) {
+    let mut value = match serde_json::from_reader(reader) {
         Ok(value) => value,
         Err(err) => {
             report.error(format!(
@@ -58,6 +63,7 @@ pub fn inspect_validated(report: &dyn ReportSink, sbom: ValidatedSbom) {
 pub fn inspect<S: Source>(
     report: &dyn ReportSink,
     sbom: Result<ValidatedSbom, ValidationError<S>>,
+    decompression: &DecompressionOptions,
 ) {
     let sbom = match sbom {
         Ok(sbom) => sbom,
@@ -67,5 +73,5 @@ pub fn inspect<S: Source>(
         }
     };
 
-    inspect_validated(report, sbom)
+    inspect_validated(report, sbom, decompression)
 }
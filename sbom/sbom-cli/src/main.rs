@@ -58,7 +58,11 @@ impl Deref for Command {
 }
 
 impl Command {
-    pub async fn run<P: Progress + Clone>(self, progress: P) -> anyhow::Result<()> {
+    pub async fn run<P>(self, progress: P) -> anyhow::Result<()>
+    where
+        // `Send` and `Sync` are commands
+        P: Progress + Clone + std::marker::Send + std::marker::Sync,
+    {
         match self {
             Self::Discover(cmd) => cmd.run(progress).await,
             Self::Download(cmd) => cmd.run(progress).await,
@@ -3,7 +3,10 @@
 use crate::{model::metadata, model::metadata::SourceMetadata};
 use std::{fmt::Debug, future::Future, ops::Deref, time::SystemTime};
 use url::Url;
-use walker_common::utils::url::Urlify;
+use walker_common::{
+    retrieve::{RetrievalMetadata, RetrievalOptions},
+    utils::url::Urlify,
+};
 
 /// Discovery configuration
 pub struct DiscoverConfig {
@@ -16,6 +19,9 @@ pub struct DiscoverConfig {
 
     /// Keys which can be used for validation
     pub keys: Vec<metadata::Key>,
+
+    /// How to retrieve the content of documents.
+    pub retrieval: RetrievalOptions,
 }
 
 impl DiscoverConfig {
@@ -23,6 +29,11 @@ impl DiscoverConfig {
         self.since = since.into();
         self
     }
+
+    pub fn with_retrieval(mut self, retrieval: RetrievalOptions) -> Self {
+        self.retrieval = retrieval;
+        self
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -31,6 +42,10 @@ pub struct DiscoveredSbom {
     pub url: Url,
     /// The "last changed" date from the change information
     pub modified: SystemTime,
+    /// The retrieval metadata of a local copy, if one is known
+    ///
+    /// This is used by sources to only retrieve the SBOM if it was modified.
+    pub local: Option<RetrievalMetadata>,
 }
 
 impl Urlify for DiscoveredSbom {
@@ -53,27 +68,27 @@ impl Deref for DiscoveredContext<'_> {
 }
 
 /// Visiting discovered SBOMs
-pub trait DiscoveredVisitor {
-    type Error: std::fmt::Display + Debug;
-    type Context;
+pub trait DiscoveredVisitor: Send + Sync {
+    type Error: std::fmt::Display + Debug + Send;
+    type Context: Send + Sync;
 
     fn visit_context(
         &self,
         context: &DiscoveredContext,
-    ) -> impl Future<Output = Result<Self::Context, Self::Error>>;
+    ) -> impl Future<Output = Result<Self::Context, Self::Error>> + Send;
 
     fn visit_sbom(
         &self,
         context: &Self::Context,
         sbom: DiscoveredSbom,
-    ) -> impl Future<Output = Result<(), Self::Error>>;
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
 }
 
 impl<F, E, Fut> DiscoveredVisitor for F
 where
-    F: Fn(DiscoveredSbom) -> Fut,
-    Fut: Future<Output = Result<(), E>>,
-    E: std::fmt::Display + Debug,
+    F: Fn(DiscoveredSbom) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), E>> + Send,
+    E: std::fmt::Display + Debug + Send,
 {
     type Error = E;
     type Context = ();
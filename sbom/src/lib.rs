@@ -6,6 +6,7 @@ pub mod model;
 pub mod report;
 pub mod retrieve;
 pub mod source;
+pub mod stream;
 pub mod validation;
 pub mod visitors;
 pub mod walker;
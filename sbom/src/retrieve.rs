@@ -4,7 +4,6 @@ use crate::{
     discover::{DiscoveredContext, DiscoveredSbom, DiscoveredVisitor},
     source::Source,
 };
-use bytes::Bytes;
 use sha2::{Sha256, Sha512};
 use std::{
     fmt::Debug,
@@ -13,7 +12,9 @@ use std::{
 };
 use url::Url;
 use walker_common::{
-    retrieve::{RetrievalError, RetrievalMetadata, RetrievedDigest, RetrievedDocument},
+    retrieve::{
+        RetrievalError, RetrievalMetadata, RetrievedData, RetrievedDigest, RetrievedDocument,
+    },
     utils::{openpgp::PublicKey, url::Urlify},
     validate::source::{KeySource, KeySourceError},
 };
@@ -25,7 +26,7 @@ pub struct RetrievedSbom {
     pub discovered: DiscoveredSbom,
 
     /// The advisory data
-    pub data: Bytes,
+    pub data: RetrievedData,
     /// Signature data
     pub signature: Option<String>,
 
@@ -79,27 +80,36 @@ impl<'c> Deref for RetrievalContext<'c> {
     }
 }
 
-pub trait RetrievedVisitor<S: Source> {
-    type Error: std::fmt::Display + Debug;
-    type Context;
+pub trait RetrievedVisitor<S: Source>: Send + Sync {
+    type Error: std::fmt::Display + Debug + Send;
+    type Context: Send + Sync;
 
     fn visit_context(
         &self,
         context: &RetrievalContext,
-    ) -> impl Future<Output = Result<Self::Context, Self::Error>>;
+    ) -> impl Future<Output = Result<Self::Context, Self::Error>> + Send;
 
     fn visit_sbom(
         &self,
         context: &Self::Context,
         result: Result<RetrievedSbom, RetrievalError<DiscoveredSbom, S>>,
-    ) -> impl Future<Output = Result<(), Self::Error>>;
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Visit an SBOM which was not modified since the local copy was retrieved.
+    fn visit_unchanged(
+        &self,
+        _context: &Self::Context,
+        _discovered: DiscoveredSbom,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
 }
 
 impl<F, E, Fut, S> RetrievedVisitor<S> for F
 where
-    F: Fn(Result<RetrievedSbom, RetrievalError<DiscoveredSbom, S>>) -> Fut,
-    Fut: Future<Output = Result<(), E>>,
-    E: std::fmt::Display + Debug,
+    F: Fn(Result<RetrievedSbom, RetrievalError<DiscoveredSbom, S>>) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), E>> + Send,
+    E: std::fmt::Display + Debug + Send,
     S: Source,
 {
     type Error = E;
@@ -203,11 +213,17 @@ where
         context: &Self::Context,
         discovered: DiscoveredSbom,
     ) -> Result<(), Self::Error> {
-        let sbom = self
-            .source
-            .load_sbom(discovered.clone())
-            .await
-            .map_err(|err| RetrievalError::Source { err, discovered });
+        let sbom = match self.source.load_sbom(discovered.clone()).await {
+            Ok(Some(sbom)) => Ok(sbom),
+            Ok(None) => {
+                return self
+                    .visitor
+                    .visit_unchanged(context, discovered)
+                    .await
+                    .map_err(Error::Visitor);
+            }
+            Err(err) => Err(RetrievalError::Source { err, discovered }),
+        };
 
         self.visitor
             .visit_sbom(context, sbom)
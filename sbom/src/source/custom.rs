@@ -0,0 +1,163 @@
+use super::Source;
+use crate::{
+    discover::{DiscoverConfig, DiscoveredSbom},
+    model::metadata::SourceMetadata,
+    retrieve::RetrievedSbom,
+};
+use futures::future::{BoxFuture, FutureExt};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, LazyLock, RwLock},
+};
+use url::Url;
+use walker_common::{
+    fetcher::Fetcher,
+    utils::openpgp::PublicKey,
+    validate::source::{Key, KeySource, KeySourceError, MapSourceError},
+};
+
+/// An object safe version of a [`Source`], which is a [`KeySource`] as well.
+///
+/// This allows plugging in sources which are not known to this crate, by wrapping them into a
+/// [`CustomSource`]. Any [`Source`] implementing [`KeySource`] implements this trait too, with its
+/// errors converted into [`anyhow::Error`].
+pub trait DynSource: Debug + Send + Sync {
+    fn load_metadata(&self) -> BoxFuture<'_, anyhow::Result<SourceMetadata>>;
+
+    fn load_index(&self) -> BoxFuture<'_, anyhow::Result<Vec<DiscoveredSbom>>>;
+
+    fn load_sbom(
+        &self,
+        sbom: DiscoveredSbom,
+    ) -> BoxFuture<'_, anyhow::Result<Option<RetrievedSbom>>>;
+
+    fn load_public_key<'a>(
+        &'a self,
+        key: Key<'a>,
+    ) -> BoxFuture<'a, Result<PublicKey, KeySourceError<anyhow::Error>>>;
+}
+
+impl<S> DynSource for S
+where
+    S: Source + KeySource,
+    <S as walker_common::source::Source>::Error: Into<anyhow::Error>,
+    <S as KeySource>::Error: Into<anyhow::Error>,
+{
+    fn load_metadata(&self) -> BoxFuture<'_, anyhow::Result<SourceMetadata>> {
+        async { Source::load_metadata(self).await.map_err(Into::into) }.boxed()
+    }
+
+    fn load_index(&self) -> BoxFuture<'_, anyhow::Result<Vec<DiscoveredSbom>>> {
+        async { Source::load_index(self).await.map_err(Into::into) }.boxed()
+    }
+
+    fn load_sbom(
+        &self,
+        sbom: DiscoveredSbom,
+    ) -> BoxFuture<'_, anyhow::Result<Option<RetrievedSbom>>> {
+        async { Source::load_sbom(self, sbom).await.map_err(Into::into) }.boxed()
+    }
+
+    fn load_public_key<'a>(
+        &'a self,
+        key: Key<'a>,
+    ) -> BoxFuture<'a, Result<PublicKey, KeySourceError<anyhow::Error>>> {
+        async {
+            KeySource::load_public_key(self, key)
+                .await
+                .map_source(Into::into)
+        }
+        .boxed()
+    }
+}
+
+/// A source, wrapping any [`DynSource`].
+///
+/// Unlike a [`DynSource`], this implements [`Source`] and [`KeySource`], so it can be used
+/// with the walker and all visitors.
+#[derive(Clone, Debug)]
+pub struct CustomSource(Arc<dyn DynSource>);
+
+impl CustomSource {
+    pub fn new(source: impl DynSource + 'static) -> Self {
+        Self(Arc::new(source))
+    }
+}
+
+impl From<Arc<dyn DynSource>> for CustomSource {
+    fn from(value: Arc<dyn DynSource>) -> Self {
+        Self(value)
+    }
+}
+
+impl walker_common::source::Source for CustomSource {
+    type Error = anyhow::Error;
+    type Retrieved = RetrievedSbom;
+}
+
+impl Source for CustomSource {
+    async fn load_metadata(&self) -> Result<SourceMetadata, Self::Error> {
+        self.0.load_metadata().await
+    }
+
+    async fn load_index(&self) -> Result<Vec<DiscoveredSbom>, Self::Error> {
+        self.0.load_index().await
+    }
+
+    async fn load_sbom(&self, sbom: DiscoveredSbom) -> Result<Option<RetrievedSbom>, Self::Error> {
+        self.0.load_sbom(sbom).await
+    }
+}
+
+impl KeySource for CustomSource {
+    type Error = anyhow::Error;
+
+    async fn load_public_key(
+        &self,
+        key: Key<'_>,
+    ) -> Result<PublicKey, KeySourceError<Self::Error>> {
+        self.0.load_public_key(key).await
+    }
+}
+
+/// Creating a source for a URL with a custom scheme.
+pub type SourceFactory =
+    dyn Fn(Url, DiscoverConfig, Fetcher) -> anyhow::Result<CustomSource> + Send + Sync;
+
+static REGISTRY: LazyLock<RwLock<HashMap<String, Arc<SourceFactory>>>> =
+    LazyLock::new(Default::default);
+
+/// The schemes handled by the built-in sources, which cannot be registered.
+const BUILTIN_SCHEMES: [&str; 4] = ["http", "https", "file", "s3"];
+
+/// Register a factory for sources using a custom URL scheme.
+///
+/// Once registered, [`super::new_source`] resolves URLs with that scheme into a
+/// [`super::DispatchSource::Custom`]. Registering a scheme again replaces the previous factory.
+/// Built-in schemes (`http`, `https`, `file`, and `s3`) cannot be registered.
+pub fn register_scheme<F>(scheme: &str, factory: F) -> anyhow::Result<()>
+where
+    F: Fn(Url, DiscoverConfig, Fetcher) -> anyhow::Result<CustomSource> + Send + Sync + 'static,
+{
+    let scheme = scheme.to_ascii_lowercase();
+    if BUILTIN_SCHEMES.contains(&scheme.as_str()) {
+        anyhow::bail!("The scheme '{scheme}' is handled by a built-in source");
+    }
+
+    REGISTRY
+        .write()
+        .unwrap_or_else(|err| err.into_inner())
+        .insert(scheme, Arc::new(factory));
+
+    Ok(())
+}
+
+/// Get the factory registered for a scheme.
+pub(crate) fn registered_scheme(scheme: &str) -> Option<Arc<SourceFactory>> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(&scheme.to_ascii_lowercase())
+        .cloned()
+}
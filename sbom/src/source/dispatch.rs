@@ -1,7 +1,7 @@
 use crate::discover::DiscoveredSbom;
 use crate::model::metadata::SourceMetadata;
 use crate::retrieve::RetrievedSbom;
-use crate::source::{FileSource, HttpSource, HttpSourceError, Source};
+use crate::source::{CustomSource, FileSource, HttpSource, HttpSourceError, S3Source, Source};
 use walker_common::{
     utils::openpgp::PublicKey,
     validate::source::{Key, KeySource, KeySourceError, MapSourceError},
@@ -9,15 +9,16 @@ use walker_common::{
 
 /// A common source type, dispatching to the known implementations.
 ///
-/// This helps creating implementations which don't need to know the exact type. Unfortunately, we
-/// cannot just "box" this, as the [`Source`] needs to implement [`Clone`], which requires [`Sized`],
-/// which prevents us from using `dyn` ("cannot be made into an object").
-///
-/// There may be a better way around this, feel free to send a PR ;-)
+/// This helps creating implementations which don't need to know the exact type. As the
+/// [`Source`] needs to implement [`Clone`], which requires [`Sized`], it cannot be used as `dyn`
+/// ("cannot be made into an object"). Other sources can still be used through a
+/// [`CustomSource`], wrapping the object safe [`super::DynSource`].
 #[derive(Clone, Debug)]
 pub enum DispatchSource {
     Http(HttpSource),
     File(FileSource),
+    S3(S3Source),
+    Custom(CustomSource),
 }
 
 impl From<HttpSource> for DispatchSource {
@@ -32,12 +33,28 @@ impl From<FileSource> for DispatchSource {
     }
 }
 
+impl From<S3Source> for DispatchSource {
+    fn from(value: S3Source) -> Self {
+        Self::S3(value)
+    }
+}
+
+impl From<CustomSource> for DispatchSource {
+    fn from(value: CustomSource) -> Self {
+        Self::Custom(value)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DispatchSourceError {
     #[error(transparent)]
     File(anyhow::Error),
     #[error(transparent)]
+    S3(anyhow::Error),
+    #[error(transparent)]
     Http(HttpSourceError),
+    #[error(transparent)]
+    Custom(anyhow::Error),
 }
 
 impl walker_common::source::Source for DispatchSource {
@@ -52,10 +69,18 @@ impl Source for DispatchSource {
                 .load_metadata()
                 .await
                 .map_err(DispatchSourceError::File)?),
+            Self::S3(source) => Ok(source
+                .load_metadata()
+                .await
+                .map_err(DispatchSourceError::S3)?),
             Self::Http(source) => Ok(source
                 .load_metadata()
                 .await
                 .map_err(DispatchSourceError::Http)?),
+            Self::Custom(source) => Ok(source
+                .load_metadata()
+                .await
+                .map_err(DispatchSourceError::Custom)?),
         }
     }
 
@@ -65,23 +90,36 @@ impl Source for DispatchSource {
                 .load_index()
                 .await
                 .map_err(DispatchSourceError::File)?),
+            Self::S3(source) => Ok(source.load_index().await.map_err(DispatchSourceError::S3)?),
             Self::Http(source) => Ok(source
                 .load_index()
                 .await
                 .map_err(DispatchSourceError::Http)?),
+            Self::Custom(source) => Ok(source
+                .load_index()
+                .await
+                .map_err(DispatchSourceError::Custom)?),
         }
     }
 
-    async fn load_sbom(&self, sbom: DiscoveredSbom) -> Result<RetrievedSbom, Self::Error> {
+    async fn load_sbom(&self, sbom: DiscoveredSbom) -> Result<Option<RetrievedSbom>, Self::Error> {
         match self {
             Self::File(source) => Ok(source
                 .load_sbom(sbom)
                 .await
                 .map_err(DispatchSourceError::File)?),
+            Self::S3(source) => Ok(source
+                .load_sbom(sbom)
+                .await
+                .map_err(DispatchSourceError::S3)?),
             Self::Http(source) => Ok(source
                 .load_sbom(sbom)
                 .await
                 .map_err(DispatchSourceError::Http)?),
+            Self::Custom(source) => Ok(source
+                .load_sbom(sbom)
+                .await
+                .map_err(DispatchSourceError::Custom)?),
         }
     }
 }
@@ -95,10 +133,12 @@ impl KeySource for DispatchSource {
     ) -> Result<PublicKey, KeySourceError<Self::Error>> {
         match self {
             Self::File(source) => source.load_public_key(key).await,
+            Self::S3(source) => source.load_public_key(key).await,
             Self::Http(source) => source
                 .load_public_key(key)
                 .await
                 .map_source(|err| err.into()),
+            Self::Custom(source) => source.load_public_key(key).await,
         }
     }
 }
@@ -18,6 +18,7 @@ use url::Url;
 use walker_common::{
     retrieve::RetrievalMetadata,
     source::file::{read_sig_and_digests, to_path},
+    store::staged_file,
     utils::{self, openpgp::PublicKey},
     validate::source::{Key, KeySource, KeySourceError},
 };
@@ -137,6 +138,15 @@ impl Source for FileSource {
                 None => continue,
             };
 
+            if let Some(staged) = staged_file(&path) {
+                // the store was interrupted, so the document is missing or still the previous one
+                log::warn!(
+                    "Skipping incomplete entry: {}, will be fetched again",
+                    staged.display()
+                );
+                continue;
+            }
+
             for ext in SKIP {
                 if name.ends_with(ext) {
                     log::debug!("Skipping file: {}", name);
@@ -157,13 +167,20 @@ impl Source for FileSource {
 
             let modified = path.metadata()?.modified()?;
 
-            result.push(DiscoveredSbom { url, modified })
+            result.push(DiscoveredSbom {
+                url,
+                modified,
+                local: None,
+            })
         }
 
         Ok(result)
     }
 
-    async fn load_sbom(&self, discovered: DiscoveredSbom) -> Result<RetrievedSbom, Self::Error> {
+    async fn load_sbom(
+        &self,
+        discovered: DiscoveredSbom,
+    ) -> Result<Option<RetrievedSbom>, Self::Error> {
         let path = discovered
             .url
             .to_file_path()
@@ -179,9 +196,9 @@ impl Source for FileSource {
             .and_then(|md| md.modified().ok())
             .map(OffsetDateTime::from);
 
-        Ok(RetrievedSbom {
+        Ok(Some(RetrievedSbom {
             discovered,
-            data,
+            data: data.into(),
             signature,
             sha256,
             sha512,
@@ -189,7 +206,7 @@ impl Source for FileSource {
                 last_modification,
                 etag: None,
             },
-        })
+        }))
     }
 }
 
@@ -4,7 +4,6 @@ use crate::{
     retrieve::RetrievedSbom,
     source::Source,
 };
-use bytes::{BufMut, Bytes, BytesMut};
 use digest::Digest;
 use futures::try_join;
 use reqwest::Response;
@@ -15,7 +14,10 @@ use url::{ParseError, Url};
 use walker_common::{
     changes::{self, ChangeEntry, ChangeSource},
     fetcher::{self, DataProcessor, Fetcher},
-    retrieve::{RetrievalMetadata, RetrievedDigest, RetrievingDigest},
+    retrieve::{
+        RetrievalMetadata, RetrievalOptions, RetrievedData, RetrievedDigest, RetrievingData,
+        RetrievingDigest,
+    },
     utils::openpgp::PublicKey,
     validate::source::{Key, KeySource, KeySourceError},
 };
@@ -25,6 +27,8 @@ use walker_common::{
 pub struct HttpOptions {
     pub since: Option<SystemTime>,
     pub keys: Vec<model::metadata::Key>,
+    /// How to retrieve the content of documents
+    pub retrieval: RetrievalOptions,
 }
 
 impl HttpOptions {
@@ -57,6 +61,11 @@ impl HttpOptions {
         self.keys.push(key.into());
         self
     }
+
+    pub fn retrieval(mut self, retrieval: RetrievalOptions) -> Self {
+        self.retrieval = retrieval;
+        self
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -74,6 +83,32 @@ impl HttpSource {
             options,
         }
     }
+
+    /// The fetcher used by this source.
+    pub fn fetcher(&self) -> &Fetcher {
+        &self.fetcher
+    }
+
+    /// Fetch the signature and the digest files of an SBOM.
+    async fn load_sidecars(
+        &self,
+        discovered: &DiscoveredSbom,
+    ) -> Result<(Option<String>, Option<String>, Option<String>), fetcher::Error> {
+        try_join!(
+            self.fetcher
+                .fetch::<Option<String>>(format!("{url}.asc", url = discovered.url)),
+            self.fetcher
+                .fetch::<Option<String>>(format!("{url}.sha256", url = discovered.url)),
+            self.fetcher
+                .fetch::<Option<String>>(format!("{url}.sha512", url = discovered.url)),
+        )
+    }
+}
+
+/// Get the expected digest from the content of a digest file.
+fn expected_digest(content: Option<String>) -> Option<String> {
+    // take the first "word" from the line
+    content.and_then(|expected| expected.split(' ').next().map(ToString::to_string))
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -84,6 +119,8 @@ pub enum HttpSourceError {
     Url(#[from] ParseError),
     #[error("CSV error: {0}")]
     Csv(#[from] csv::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 impl From<changes::Error> for HttpSourceError {
@@ -123,55 +160,87 @@ impl Source for HttpSource {
                 let modified = timestamp.into();
                 let url = base.join(&file)?;
 
-                Ok::<_, ParseError>(DiscoveredSbom { url, modified })
+                Ok::<_, ParseError>(DiscoveredSbom {
+                    url,
+                    modified,
+                    local: None,
+                })
             })
             // filter out advisories based in since, but only if we can be sure
             .filter(|advisory| match (advisory, &self.options.since) {
-                (Ok(DiscoveredSbom { url: _, modified }), Some(since)) => modified >= since,
+                (Ok(DiscoveredSbom { modified, .. }), Some(since)) => modified >= since,
                 _ => true,
             })
             .collect::<Result<_, _>>()?)
     }
 
-    async fn load_sbom(&self, discovered: DiscoveredSbom) -> Result<RetrievedSbom, Self::Error> {
-        let (signature, sha256, sha512) = try_join!(
-            self.fetcher
-                .fetch::<Option<String>>(format!("{url}.asc", url = discovered.url)),
-            self.fetcher
-                .fetch::<Option<String>>(format!("{url}.sha256", url = discovered.url)),
-            self.fetcher
-                .fetch::<Option<String>>(format!("{url}.sha512", url = discovered.url)),
-        )?;
+    async fn load_sbom(
+        &self,
+        discovered: DiscoveredSbom,
+    ) -> Result<Option<RetrievedSbom>, Self::Error> {
+        let Some(local) = &discovered.local else {
+            let (signature, sha256, sha512) = self.load_sidecars(&discovered).await?;
+
+            let processor = FetchingRetrievedSbom {
+                sha256: expected_digest(sha256).map(|expected| RetrievingDigest {
+                    expected,
+                    current: Sha256::new(),
+                }),
+                sha512: expected_digest(sha512).map(|expected| RetrievingDigest {
+                    expected,
+                    current: Sha512::new(),
+                }),
+                options: self.options.retrieval.clone(),
+            };
+
+            let sbom = self
+                .fetcher
+                .fetch_processed(discovered.url.clone(), processor)
+                .await??;
+
+            return Ok(Some(sbom.into_retrieved(discovered, signature)));
+        };
 
-        let sha256 = sha256
-            // take the first "word" from the line
-            .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
-            .map(|expected| RetrievingDigest {
-                expected,
+        // We only know which digests to expect once we have fetched the digest files. Which we
+        // only want to do when the document was modified. So we calculate both digests, and
+        // drop the ones without a digest file afterward.
+        let processor = FetchingRetrievedSbom {
+            sha256: Some(RetrievingDigest {
+                expected: Default::default(),
                 current: Sha256::new(),
-            });
-        let sha512 = sha512
-            // take the first "word" from the line
-            .and_then(|expected| expected.split(' ').next().map(ToString::to_string))
-            .map(|expected| RetrievingDigest {
-                expected,
+            }),
+            sha512: Some(RetrievingDigest {
+                expected: Default::default(),
                 current: Sha512::new(),
-            });
+            }),
+            options: self.options.retrieval.clone(),
+        };
 
-        let advisory = self
+        let Some(sbom) = self
             .fetcher
-            .fetch_processed(
-                discovered.url.clone(),
-                FetchingRetrievedSbom { sha256, sha512 },
-            )
-            .await?;
-
-        Ok(advisory.into_retrieved(discovered, signature))
+            .fetch_processed_conditional(discovered.url.clone(), processor, local)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let mut sbom = sbom?;
+
+        let (signature, sha256, sha512) = self.load_sidecars(&discovered).await?;
+        sbom.sha256 = sbom
+            .sha256
+            .zip(expected_digest(sha256))
+            .map(|(digest, expected)| RetrievedDigest { expected, ..digest });
+        sbom.sha512 = sbom
+            .sha512
+            .zip(expected_digest(sha512))
+            .map(|(digest, expected)| RetrievedDigest { expected, ..digest });
+
+        Ok(Some(sbom.into_retrieved(discovered, signature)))
     }
 }
 
 pub struct FetchedRetrievedSbom {
-    data: Bytes,
+    data: RetrievedData,
     sha256: Option<RetrievedDigest<Sha256>>,
     sha512: Option<RetrievedDigest<Sha512>>,
     metadata: RetrievalMetadata,
@@ -197,15 +266,19 @@ impl FetchedRetrievedSbom {
 pub struct FetchingRetrievedSbom {
     pub sha256: Option<RetrievingDigest<Sha256>>,
     pub sha512: Option<RetrievingDigest<Sha512>>,
+    pub options: RetrievalOptions,
 }
 
 impl DataProcessor for FetchingRetrievedSbom {
-    type Type = FetchedRetrievedSbom;
+    type Type = Result<FetchedRetrievedSbom, std::io::Error>;
 
     async fn process(&self, response: Response) -> Result<Self::Type, reqwest::Error> {
         let mut response = response.error_for_status()?;
 
-        let mut data = BytesMut::new();
+        let mut data = match RetrievingData::new(&self.options) {
+            Ok(data) => data,
+            Err(err) => return Ok(Err(err)),
+        };
         let mut sha256 = self.sha256.clone();
         let mut sha512 = self.sha512.clone();
 
@@ -216,7 +289,9 @@ impl DataProcessor for FetchingRetrievedSbom {
             if let Some(d) = &mut sha512 {
                 d.update(&chunk);
             }
-            data.put(chunk);
+            if let Err(err) = data.write(&chunk).await {
+                return Ok(Err(err));
+            }
         }
 
         let etag = response
@@ -231,15 +306,20 @@ impl DataProcessor for FetchingRetrievedSbom {
             .and_then(|s| s.to_str().ok())
             .and_then(|s| OffsetDateTime::parse(s, &Rfc2822).ok());
 
-        Ok(FetchedRetrievedSbom {
-            data: data.freeze(),
+        let data = match data.finish().await {
+            Ok(data) => data,
+            Err(err) => return Ok(Err(err)),
+        };
+
+        Ok(Ok(FetchedRetrievedSbom {
+            data,
             sha256: sha256.map(|d| d.into()),
             sha512: sha512.map(|d| d.into()),
             metadata: RetrievalMetadata {
                 last_modification,
                 etag,
             },
-        })
+        }))
     }
 }
 
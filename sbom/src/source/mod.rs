@@ -1,12 +1,16 @@
 //! Sources
 
+mod custom;
 mod dispatch;
 mod file;
 mod http;
+mod s3;
 
 pub use self::http::*;
+pub use custom::*;
 pub use dispatch::*;
 pub use file::*;
+pub use s3::*;
 
 use crate::{
     discover::{DiscoverConfig, DiscoveredSbom},
@@ -17,16 +21,23 @@ use anyhow::bail;
 use fluent_uri::UriRef;
 use std::{fmt::Debug, future::Future};
 use url::Url;
-use walker_common::fetcher::{Fetcher, FetcherOptions};
+use walker_common::{
+    fetcher::{Fetcher, FetcherOptions},
+    store::S3Storage,
+};
 
 /// A source of SBOM documents
-pub trait Source: walker_common::source::Source + Clone + Debug {
-    fn load_metadata(&self) -> impl Future<Output = Result<SourceMetadata, Self::Error>>;
-    fn load_index(&self) -> impl Future<Output = Result<Vec<DiscoveredSbom>, Self::Error>>;
+pub trait Source: walker_common::source::Source + Clone + Debug + Send + Sync {
+    fn load_metadata(&self) -> impl Future<Output = Result<SourceMetadata, Self::Error>> + Send;
+    fn load_index(&self) -> impl Future<Output = Result<Vec<DiscoveredSbom>, Self::Error>> + Send;
+    /// Load an SBOM.
+    ///
+    /// If the SBOM has a known local copy (see [`DiscoveredSbom::local`]), and the source can
+    /// tell that the SBOM was not modified since, it returns `Ok(None)`.
     fn load_sbom(
         &self,
         sbom: DiscoveredSbom,
-    ) -> impl Future<Output = Result<RetrievedSbom, Self::Error>>;
+    ) -> impl Future<Output = Result<Option<RetrievedSbom>, Self::Error>> + Send;
 }
 
 pub async fn new_source(
@@ -34,7 +45,7 @@ pub async fn new_source(
     fetcher: impl Into<FetcherOptions>,
 ) -> anyhow::Result<DispatchSource> {
     let discover = discover.into();
-    let source = discover.source;
+    let source = discover.source.clone();
 
     match UriRef::parse(source.as_str()) {
         Ok(uri) => match uri.scheme().map(|s| s.as_str()) {
@@ -43,13 +54,27 @@ pub async fn new_source(
                 log::debug!("Creating file source: {source}");
                 Ok(FileSource::new(source, FileOptions::new().since(discover.since))?.into())
             }
-            Some(_scheme) => {
-                log::debug!("Creating HTTP source: {source}");
+            Some("s3") => {
+                let storage = S3Storage::try_from(source.as_str())?;
+                log::debug!("Creating S3 source: {storage}");
+                Ok(S3Source::new(storage, FileOptions::new().since(discover.since)).into())
+            }
+            Some(scheme) => {
                 let fetcher = Fetcher::new(fetcher.into()).await?;
+
+                if let Some(factory) = registered_scheme(scheme) {
+                    log::debug!("Creating custom source: {source}");
+                    return Ok(factory(Url::parse(&source)?, discover, fetcher)?.into());
+                }
+
+                log::debug!("Creating HTTP source: {source}");
                 Ok(HttpSource::new(
                     Url::parse(&source)?,
                     fetcher,
-                    HttpOptions::new().since(discover.since).keys(discover.keys),
+                    HttpOptions::new()
+                        .since(discover.since)
+                        .keys(discover.keys)
+                        .retrieval(discover.retrieval),
                 )
                 .into())
             }
@@ -70,7 +95,7 @@ pub async fn new_source(
 #[cfg(test)]
 mod test {
     use crate::discover::DiscoverConfig;
-    use crate::source::new_source;
+    use crate::source::{CustomSource, DispatchSource, HttpSource, new_source, register_scheme};
     use walker_common::fetcher::FetcherOptions;
 
     #[tokio::test]
@@ -80,6 +105,7 @@ mod test {
                 source: "file:/".to_string(),
                 since: None,
                 keys: vec![],
+                retrieval: Default::default(),
             },
             FetcherOptions::default(),
         )
@@ -88,6 +114,22 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    pub async fn test_s3_source() {
+        let result = new_source(
+            DiscoverConfig {
+                source: "s3://us-east-1/bucket/mirror".to_string(),
+                since: None,
+                keys: vec![],
+                retrieval: Default::default(),
+            },
+            FetcherOptions::default(),
+        )
+        .await;
+
+        assert!(matches!(result, Ok(DispatchSource::S3(_))));
+    }
+
     #[tokio::test]
     pub async fn test_http_source() {
         let result = new_source(
@@ -95,6 +137,7 @@ mod test {
                 source: "https://foo.bar/baz".to_string(),
                 since: None,
                 keys: vec![],
+                retrieval: Default::default(),
             },
             FetcherOptions::default(),
         )
@@ -103,6 +146,33 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    pub async fn test_custom_source() {
+        assert!(register_scheme("https", |_, _, _| unreachable!()).is_err());
+        register_scheme("mock", |url, _, fetcher| {
+            // any source can be used as a custom one
+            Ok(CustomSource::new(HttpSource::new(
+                url,
+                fetcher,
+                Default::default(),
+            )))
+        })
+        .expect("must register");
+
+        let result = new_source(
+            DiscoverConfig {
+                source: "mock://foo.bar/baz".to_string(),
+                since: None,
+                keys: vec![],
+                retrieval: Default::default(),
+            },
+            FetcherOptions::default(),
+        )
+        .await;
+
+        assert!(matches!(result, Ok(DispatchSource::Custom(_))));
+    }
+
     #[tokio::test]
     pub async fn test_invalid_source() {
         let result = new_source(
@@ -110,6 +180,7 @@ mod test {
                 source: "/var/files".to_string(),
                 since: None,
                 keys: vec![],
+                retrieval: Default::default(),
             },
             FetcherOptions::default(),
         )
@@ -0,0 +1,157 @@
+use crate::{
+    discover::DiscoveredSbom,
+    model::metadata::{self, SourceMetadata},
+    retrieve::RetrievedSbom,
+    source::{FileOptions, Source},
+    visitors::store::DIR_METADATA,
+};
+use anyhow::{Context, anyhow};
+use url::Url;
+use walker_common::{
+    source::s3::{head_objects, read_document, scan_keys},
+    store::S3Storage,
+    utils::{self, openpgp::PublicKey},
+    validate::source::{Key, KeySource, KeySourceError},
+};
+
+/// A source reading from an S3 compatible bucket, written by the
+/// [`crate::visitors::store::StoreVisitor`].
+///
+/// SBOMs use URLs of the form `s3://<region>/<bucket>/<key>`. The modification timestamp of an
+/// SBOM is the one stored with it, falling back to the time the object was stored.
+#[derive(Clone, Debug)]
+pub struct S3Source {
+    base: S3Storage,
+    options: FileOptions,
+}
+
+impl S3Source {
+    pub fn new(base: S3Storage, options: impl Into<Option<FileOptions>>) -> Self {
+        Self {
+            base,
+            options: options.into().unwrap_or_default(),
+        }
+    }
+
+    /// The storage a URL of this source points to.
+    fn storage(&self, url: &Url) -> anyhow::Result<S3Storage> {
+        self.base
+            .from_url(url)
+            .ok_or_else(|| anyhow!("URL is not part of the source: {url}"))
+    }
+}
+
+impl walker_common::source::Source for S3Source {
+    type Error = anyhow::Error;
+    type Retrieved = RetrievedSbom;
+}
+
+impl Source for S3Source {
+    async fn load_metadata(&self) -> Result<SourceMetadata, Self::Error> {
+        let storage = self.base.join(DIR_METADATA).join("metadata.json");
+        let data = storage
+            .read_optional()
+            .await?
+            .ok_or_else(|| anyhow!("Failed to find metadata: {storage}"))?;
+
+        let mut metadata: SourceMetadata =
+            serde_json::from_slice(&data).context("Failed to read stored provider metadata")?;
+
+        metadata.keys = scan_keys(&self.base.join(DIR_METADATA).join("keys"))
+            .await?
+            .into_iter()
+            .map(|(fingerprint, url)| metadata::Key {
+                fingerprint: Some(fingerprint),
+                url,
+            })
+            .collect();
+
+        Ok(metadata)
+    }
+
+    async fn load_index(&self) -> Result<Vec<DiscoveredSbom>, Self::Error> {
+        const SKIP: &[&str] = &[".asc", ".sha256", ".sha512"];
+
+        log::info!("Loading index - since: {:?}", self.options.since);
+
+        let prefix = match self.base.key() {
+            "" => String::new(),
+            key => format!("{key}/"),
+        };
+
+        let objects = self.base.list().await?.into_iter().filter(|object| {
+            let Some(name) = object.storage.key().strip_prefix(&prefix) else {
+                return false;
+            };
+            // only objects directly below the base, like the files of a directory
+            if name.contains('/') {
+                return false;
+            }
+
+            if SKIP.iter().any(|ext| name.ends_with(ext)) {
+                log::debug!("Skipping object: {}", name);
+                return false;
+            }
+
+            true
+        });
+
+        let mut result = vec![];
+
+        for (object, head) in head_objects(objects).await? {
+            let modified = head.modified();
+
+            if let Some(since) = self.options.since {
+                if modified < since {
+                    log::debug!("Skipping object due to modification constraint: {modified:?}");
+                    continue;
+                }
+            }
+
+            result.push(DiscoveredSbom {
+                url: object.storage.url(),
+                modified,
+                local: None,
+            })
+        }
+
+        Ok(result)
+    }
+
+    async fn load_sbom(
+        &self,
+        discovered: DiscoveredSbom,
+    ) -> Result<Option<RetrievedSbom>, Self::Error> {
+        let storage = self.storage(&discovered.url)?;
+
+        let Some(document) = read_document(&storage, discovered.local.as_ref()).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(RetrievedSbom {
+            discovered,
+            data: document.data.into(),
+            signature: document.signature,
+            sha256: document.sha256,
+            sha512: document.sha512,
+            metadata: document.metadata,
+        }))
+    }
+}
+
+impl KeySource for S3Source {
+    type Error = anyhow::Error;
+
+    async fn load_public_key(
+        &self,
+        key: Key<'_>,
+    ) -> Result<PublicKey, KeySourceError<Self::Error>> {
+        let storage = self.storage(key.url).map_err(KeySourceError::Source)?;
+        let bytes = storage
+            .read_optional()
+            .await
+            .map_err(|err| KeySourceError::Source(err.into()))?
+            .ok_or_else(|| KeySourceError::Source(anyhow!("Key not found: {storage}")))?;
+        utils::openpgp::validate_keys(bytes, key.fingerprint).map_err(KeySourceError::OpenPgp)
+    }
+}
@@ -0,0 +1,165 @@
+//! Walking as a stream
+//!
+//! Instead of implementing a chain of visitors, it is possible to consume the outcome of a walk
+//! as a [`Stream`]. This allows using ordinary stream combinators and the backpressure they
+//! provide.
+
+use crate::{
+    discover::DiscoveredSbom,
+    retrieve::{self, RetrievedSbom, RetrievingVisitor},
+    source::Source,
+    validation::{self, ValidatedSbom, ValidationVisitor},
+    walker::{self, Walker},
+};
+use futures::Stream;
+use std::{convert::Infallible, fmt::Debug};
+use url::ParseError;
+use walker_common::{
+    progress::Progress,
+    retrieve::RetrievalError,
+    utils::stream::walk_stream,
+    validate::{
+        ValidationError, ValidationOptions,
+        source::{KeySource, KeySourceError},
+    },
+};
+
+/// An error aborting a walk, which is streamed.
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError<SE, KSE>
+where
+    SE: std::fmt::Display + Debug,
+    KSE: std::fmt::Display + Debug,
+{
+    #[error("Source error: {0}")]
+    Source(SE),
+    #[error("Key source error: {0}")]
+    KeySource(KeySourceError<KSE>),
+    #[error("URL error: {0}")]
+    Url(#[from] ParseError),
+    #[error("Severe validation error: {0}")]
+    Validation(anyhow::Error),
+    #[error("Walk was cancelled")]
+    Cancelled,
+}
+
+/// The error of streaming a source.
+pub type SourceStreamError<S> =
+    StreamError<<S as walker_common::source::Source>::Error, <S as KeySource>::Error>;
+
+impl<VE, SE, KSE> From<walker::Error<retrieve::Error<VE, SE, KSE>, SE>> for StreamError<SE, KSE>
+where
+    VE: Into<StreamError<SE, KSE>> + std::fmt::Display + Debug,
+    SE: std::fmt::Display + Debug,
+    KSE: std::fmt::Display + Debug,
+{
+    fn from(value: walker::Error<retrieve::Error<VE, SE, KSE>, SE>) -> Self {
+        match value {
+            walker::Error::Source(err) => Self::Source(err),
+            walker::Error::Url(err) => Self::Url(err),
+            walker::Error::Visitor(retrieve::Error::Source(err)) => Self::Source(err),
+            walker::Error::Visitor(retrieve::Error::KeySource(err)) => Self::KeySource(err),
+            walker::Error::Visitor(retrieve::Error::Visitor(err)) => err.into(),
+            walker::Error::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+impl<SE, KSE> From<Infallible> for StreamError<SE, KSE>
+where
+    SE: std::fmt::Display + Debug,
+    KSE: std::fmt::Display + Debug,
+{
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl<SE, KSE> From<validation::Error<Infallible>> for StreamError<SE, KSE>
+where
+    SE: std::fmt::Display + Debug,
+    KSE: std::fmt::Display + Debug,
+{
+    fn from(value: validation::Error<Infallible>) -> Self {
+        match value {
+            validation::Error::Visitor(err) => match err {},
+            validation::Error::Validation(err) => Self::Validation(err),
+        }
+    }
+}
+
+impl<S, P> Walker<S, P>
+where
+    S: Source<Retrieved = RetrievedSbom> + KeySource,
+    P: Progress,
+{
+    /// Walk the source, streaming the outcome of retrieving each SBOM.
+    ///
+    /// Up to `limit` SBOMs are processed concurrently. The outer result reports an error
+    /// aborting the walk, the inner one the outcome of an individual SBOM.
+    pub fn stream_retrieved(
+        self,
+        limit: usize,
+    ) -> impl Stream<
+        Item = Result<
+            Result<RetrievedSbom, RetrievalError<DiscoveredSbom, S>>,
+            SourceStreamError<S>,
+        >,
+    > {
+        let source = self.source.clone();
+
+        walk_stream(limit, async move |tx| {
+            self.walk_parallel(
+                limit,
+                RetrievingVisitor::new(
+                    source,
+                    move |outcome: Result<RetrievedSbom, RetrievalError<DiscoveredSbom, S>>| {
+                        let tx = tx.clone();
+                        async move {
+                            // the stream was dropped in the meantime, and the walk will be too
+                            let _ = tx.send(outcome).await;
+                            Ok::<_, Infallible>(())
+                        }
+                    },
+                ),
+            )
+            .await
+            .map_err(StreamError::from)
+        })
+    }
+
+    /// Walk the source, streaming the outcome of validating each SBOM.
+    ///
+    /// Up to `limit` SBOMs are processed concurrently. The outer result reports an error
+    /// aborting the walk, the inner one the outcome of an individual SBOM.
+    pub fn stream_validated(
+        self,
+        limit: usize,
+        options: ValidationOptions,
+    ) -> impl Stream<Item = Result<Result<ValidatedSbom, ValidationError<S>>, SourceStreamError<S>>>
+    {
+        let source = self.source.clone();
+
+        walk_stream(limit, async move |tx| {
+            self.walk_parallel(
+                limit,
+                RetrievingVisitor::new(
+                    source,
+                    ValidationVisitor::new(
+                        move |outcome: Result<ValidatedSbom, ValidationError<S>>| {
+                            let tx = tx.clone();
+                            async move {
+                                // the stream was dropped in the meantime, and the walk will be too
+                                let _ = tx.send(outcome).await;
+                                Ok::<_, Infallible>(())
+                            }
+                        },
+                    )
+                    .with_options(options),
+                ),
+            )
+            .await
+            .map_err(StreamError::from)
+        })
+    }
+}
@@ -60,27 +60,36 @@ impl<'c> Deref for ValidationContext<'c> {
     }
 }
 
-pub trait ValidatedVisitor<S: Source> {
-    type Error: Display + Debug;
-    type Context;
+pub trait ValidatedVisitor<S: Source>: Send + Sync {
+    type Error: Display + Debug + Send;
+    type Context: Send + Sync;
 
     fn visit_context(
         &self,
         context: &ValidationContext,
-    ) -> impl Future<Output = Result<Self::Context, Self::Error>>;
+    ) -> impl Future<Output = Result<Self::Context, Self::Error>> + Send;
 
     fn visit_sbom(
         &self,
         context: &Self::Context,
         result: Result<ValidatedSbom, ValidationError<S>>,
-    ) -> impl Future<Output = Result<(), Self::Error>>;
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Visit an SBOM which was not modified since the local copy was retrieved.
+    fn visit_unchanged(
+        &self,
+        _context: &Self::Context,
+        _discovered: DiscoveredSbom,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
 }
 
 impl<F, E, Fut, S> ValidatedVisitor<S> for F
 where
-    F: Fn(Result<ValidatedSbom, ValidationError<S>>) -> Fut,
-    Fut: Future<Output = Result<(), E>>,
-    E: Display + Debug,
+    F: Fn(Result<ValidatedSbom, ValidationError<S>>) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), E>> + Send,
+    E: Display + Debug + Send,
     S: Source,
 {
     type Error = E;
@@ -177,7 +186,7 @@ where
         }
 
         if let Some(signature) = &retrieved.signature {
-            match openpgp::validate_signature(
+            match openpgp::validate_signature_data(
                 &self.options,
                 &context.keys,
                 signature,
@@ -248,4 +257,15 @@ where
 
         Ok(())
     }
+
+    async fn visit_unchanged(
+        &self,
+        context: &Self::Context,
+        discovered: DiscoveredSbom,
+    ) -> Result<(), Self::Error> {
+        self.visitor
+            .visit_unchanged(&context.context, discovered)
+            .await
+            .map_err(Error::Visitor)
+    }
 }
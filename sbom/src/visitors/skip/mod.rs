@@ -8,8 +8,11 @@ use std::{
     path::PathBuf,
     time::SystemTime,
 };
-use tokio::fs;
-use walker_common::{utils::url::Urlify, validate::ValidationError};
+use walker_common::{
+    store::{Storage, StoreError},
+    utils::url::Urlify,
+    validate::ValidationError,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error<VE: Display + Debug> {
@@ -17,6 +20,8 @@ pub enum Error<VE: Display + Debug> {
     Visitor(VE),
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Store(#[from] StoreError),
     #[error("Unable to get name from URL")]
     Name,
 }
@@ -24,7 +29,7 @@ pub enum Error<VE: Display + Debug> {
 /// A visitor, skipping advisories for existing files.
 pub struct SkipExistingVisitor<V: DiscoveredVisitor> {
     pub visitor: V,
-    pub output: PathBuf,
+    pub output: Storage,
     /// The time "since" when we consider changes "new"
     ///
     /// Overrides the "file modified" timestamp which is used by default.
@@ -48,24 +53,30 @@ impl<V: DiscoveredVisitor> DiscoveredVisitor for SkipExistingVisitor<V> {
     async fn visit_sbom(
         &self,
         context: &Self::Context,
-        sbom: DiscoveredSbom,
+        mut sbom: DiscoveredSbom,
     ) -> Result<(), Self::Error> {
         let name = PathBuf::from(sbom.url.path());
-        let name = name.file_name().ok_or(Error::Name)?;
+        let name = name
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(Error::Name)?;
 
         let path = self.output.join(name);
 
-        if fs::try_exists(&path).await? {
+        if let Some(local) = path.stored_metadata().await? {
             // if we have a "since", we use it as the file modification timestamp
             let file_modified = match self.since {
                 Some(since) => since,
-                None => fs::metadata(&path).await?.modified()?,
+                None => local
+                    .last_modification
+                    .map(SystemTime::from)
+                    .unwrap_or(SystemTime::UNIX_EPOCH),
             };
 
             log::debug!(
                 "Advisory modified: {}, file ({}) modified: {} ({:?})",
                 humantime::Timestamp::from(sbom.modified),
-                name.to_string_lossy(),
+                name,
                 humantime::Timestamp::from(file_modified),
                 self.since.map(humantime::Timestamp::from)
             );
@@ -74,6 +85,12 @@ impl<V: DiscoveredVisitor> DiscoveredVisitor for SkipExistingVisitor<V> {
                 // the file was modified after the change date, skip it
                 return Ok(());
             }
+
+            // We have a local copy, the source may only retrieve the SBOM if it was modified.
+            // Unless we have a "since", which forces all changes after it to be re-synced.
+            if self.since.is_none() {
+                sbom.local = Some(local);
+            }
         }
 
         self.visitor
@@ -123,4 +140,12 @@ impl<V: ValidatedVisitor<S>, S: Source> ValidatedVisitor<S> for SkipFailedVisito
             }
         }
     }
+
+    async fn visit_unchanged(
+        &self,
+        context: &Self::Context,
+        discovered: DiscoveredSbom,
+    ) -> Result<(), Self::Error> {
+        self.visitor.visit_unchanged(context, discovered).await
+    }
 }
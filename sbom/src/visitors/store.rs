@@ -7,14 +7,10 @@ use crate::{
 };
 use anyhow::Context;
 use sequoia_openpgp::{Cert, armor::Kind, serialize::SerializeInto};
-use std::{
-    io::{ErrorKind, Write},
-    path::{Path, PathBuf},
-};
-use tokio::fs;
+use std::{io::Write, path::PathBuf};
 use walker_common::{
     retrieve::RetrievalError,
-    store::{Document, StoreError, store_document},
+    store::{Document, Storage, StoreError},
     utils::openpgp::PublicKey,
     validate::ValidationError,
 };
@@ -24,19 +20,21 @@ pub const DIR_METADATA: &str = "metadata";
 /// Stores all data so that it can be used as a [`crate::source::Source`] later.
 #[non_exhaustive]
 pub struct StoreVisitor {
-    /// the output base
-    pub base: PathBuf,
+    /// the output base, a directory or a key prefix in an S3 bucket
+    pub base: Storage,
 
     /// whether to set the file modification timestamps
     pub no_timestamps: bool,
 
     /// whether to store additional metadata (like the etag) using extended attributes
+    ///
+    /// When storing in an S3 bucket, the metadata is always stored with the object.
     #[cfg(any(target_os = "linux", target_os = "macos"))]
     pub no_xattrs: bool,
 }
 
 impl StoreVisitor {
-    pub fn new(base: impl Into<PathBuf>) -> Self {
+    pub fn new(base: impl Into<Storage>) -> Self {
         Self {
             base: base.into(),
             no_timestamps: false,
@@ -81,6 +79,7 @@ impl<S: Source> RetrievedVisitor<S> for StoreVisitor {
         &self,
         context: &RetrievalContext<'_>,
     ) -> Result<Self::Context, Self::Error> {
+        self.remove_staged_files()?;
         self.store_provider_metadata(context.metadata).await?;
         self.store_keys(context.keys).await?;
         Ok(())
@@ -94,9 +93,21 @@ impl<S: Source> RetrievedVisitor<S> for StoreVisitor {
         self.store(&result?).await?;
         Ok(())
     }
+
+    async fn visit_unchanged(
+        &self,
+        _context: &Self::Context,
+        discovered: DiscoveredSbom,
+    ) -> Result<(), Self::Error> {
+        self.unchanged(&discovered).await?;
+        Ok(())
+    }
 }
 
-impl<S: Source> ValidatedVisitor<S> for StoreVisitor {
+impl<S> ValidatedVisitor<S> for StoreVisitor
+where
+    S: Source<Retrieved = RetrievedSbom>,
+{
     type Error = StoreValidatedError<S>;
     type Context = ();
 
@@ -104,6 +115,7 @@ impl<S: Source> ValidatedVisitor<S> for StoreVisitor {
         &self,
         context: &ValidationContext<'_>,
     ) -> Result<Self::Context, Self::Error> {
+        self.remove_staged_files()?;
         self.store_provider_metadata(context.metadata).await?;
         self.store_keys(context.retrieval.keys).await?;
         Ok(())
@@ -117,52 +129,44 @@ impl<S: Source> ValidatedVisitor<S> for StoreVisitor {
         self.store(&result?.retrieved).await?;
         Ok(())
     }
+
+    async fn visit_unchanged(
+        &self,
+        _context: &Self::Context,
+        discovered: DiscoveredSbom,
+    ) -> Result<(), Self::Error> {
+        self.unchanged(&discovered).await?;
+        Ok(())
+    }
 }
 
 impl StoreVisitor {
-    async fn store_provider_metadata(&self, metadata: &SourceMetadata) -> Result<(), StoreError> {
-        let metadir = self.base.join(DIR_METADATA);
-
-        fs::create_dir(&metadir)
-            .await
-            .or_else(|err| match err.kind() {
-                ErrorKind::AlreadyExists => Ok(()),
-                _ => Err(err),
-            })
-            .with_context(|| format!("Failed to create metadata directory: {}", metadir.display()))
-            .map_err(StoreError::Io)?;
+    /// Remove staging files left behind by an interrupted run, before storing new documents.
+    fn remove_staged_files(&self) -> Result<(), StoreError> {
+        let removed = self.base.remove_staged_files()?;
+        if removed > 0 {
+            log::info!(
+                "Removed {removed} stale staging file(s) from: {}",
+                self.base
+            );
+        }
+        Ok(())
+    }
 
-        let file = metadir.join("metadata.json");
-        let mut out = std::fs::File::create(&file)
-            .with_context(|| {
-                format!(
-                    "Unable to open provider metadata file for writing: {}",
-                    file.display()
-                )
-            })
-            .map_err(StoreError::Io)?;
-        serde_json::to_writer_pretty(&mut out, metadata)
+    async fn store_provider_metadata(&self, metadata: &SourceMetadata) -> Result<(), StoreError> {
+        let data = serde_json::to_vec_pretty(metadata)
             .context("Failed serializing provider metadata")
             .map_err(StoreError::Io)?;
-        Ok(())
+
+        self.base
+            .join(DIR_METADATA)
+            .join("metadata.json")
+            .write(data)
+            .await
     }
 
     async fn store_keys(&self, keys: &[PublicKey]) -> Result<(), StoreError> {
         let metadata = self.base.join(DIR_METADATA).join("keys");
-        fs::create_dir(&metadata)
-            .await
-            // ignore if the directory already exists
-            .or_else(|err| match err.kind() {
-                ErrorKind::AlreadyExists => Ok(()),
-                _ => Err(err),
-            })
-            .with_context(|| {
-                format!(
-                    "Failed to create metadata directory: {}",
-                    metadata.display()
-                )
-            })
-            .map_err(StoreError::Io)?;
 
         for cert in keys.iter().flat_map(|k| &k.certs) {
             log::info!("Storing key: {}", cert.fingerprint());
@@ -172,16 +176,12 @@ impl StoreVisitor {
         Ok(())
     }
 
-    async fn store_cert(&self, cert: &Cert, path: &Path) -> Result<(), StoreError> {
-        let name = path.join(format!("{}.txt", cert.fingerprint().to_hex()));
+    async fn store_cert(&self, cert: &Cert, base: &Storage) -> Result<(), StoreError> {
+        let name = base.join(&format!("{}.txt", cert.fingerprint().to_hex()));
 
         let data = Self::serialize_key(cert).map_err(StoreError::SerializeKey)?;
 
-        fs::write(&name, data)
-            .await
-            .with_context(|| format!("Failed to store key: {}", name.display()))
-            .map_err(StoreError::Io)?;
-        Ok(())
+        name.write(data).await
     }
 
     fn serialize_key(cert: &Cert) -> Result<Vec<u8>, anyhow::Error> {
@@ -190,6 +190,31 @@ impl StoreVisitor {
         Ok(writer.finalize()?)
     }
 
+    /// The location to store an SBOM at, named after the file name of its URL.
+    fn file(&self, sbom: &DiscoveredSbom) -> Result<Storage, StoreError> {
+        PathBuf::from(sbom.url.path())
+            .file_name()
+            .and_then(|file| file.to_str())
+            .map(|file| self.base.join(file))
+            .ok_or_else(|| StoreError::Filename(sbom.url.to_string()))
+    }
+
+    /// Handle an SBOM which was not modified since the local copy was stored.
+    ///
+    /// The local copy is up-to-date with the change information, so we bump its modification
+    /// timestamp to prevent re-checking it with the next run.
+    async fn unchanged(&self, sbom: &DiscoveredSbom) -> Result<(), StoreError> {
+        log::info!("Unchanged: {}", sbom.url);
+
+        if self.no_timestamps {
+            return Ok(());
+        }
+
+        let file = self.file(sbom)?;
+
+        file.set_document_timestamp(sbom.modified).await
+    }
+
     async fn store(&self, sbom: &RetrievedSbom) -> Result<(), StoreError> {
         log::info!(
             "Storing: {} (modified: {:?})",
@@ -197,27 +222,20 @@ impl StoreVisitor {
             sbom.metadata.last_modification
         );
 
-        let file = PathBuf::from(sbom.url.path())
-            .file_name()
-            .map(|file| self.base.join(file))
-            .ok_or_else(|| StoreError::Filename(sbom.url.to_string()))?;
-
-        log::debug!("Writing {}", file.display());
-
-        store_document(
-            &file,
-            Document {
-                data: &sbom.data,
-                changed: sbom.modified,
-                metadata: &sbom.metadata,
-                sha256: &sbom.sha256,
-                sha512: &sbom.sha512,
-                signature: &sbom.signature,
-                no_timestamps: self.no_timestamps,
-                #[cfg(any(target_os = "linux", target_os = "macos"))]
-                no_xattrs: self.no_xattrs,
-            },
-        )
+        let file = self.file(&sbom.discovered)?;
+
+        file.store_document(Document {
+            data: &sbom.data,
+            changed: sbom.modified,
+            metadata: &sbom.metadata,
+            attributes: &[],
+            sha256: &sbom.sha256,
+            sha512: &sbom.sha512,
+            signature: &sbom.signature,
+            no_timestamps: self.no_timestamps,
+            #[cfg(any(target_os = "linux", target_os = "macos"))]
+            no_xattrs: self.no_xattrs,
+        })
         .await?;
 
         Ok(())
@@ -1,11 +1,12 @@
 //! The actual walker
 
 use crate::{
-    discover::{DiscoveredContext, DiscoveredVisitor},
+    discover::{DiscoveredContext, DiscoveredSbom, DiscoveredVisitor},
     source::Source,
 };
-use futures::{StreamExt, TryFutureExt, TryStreamExt, stream};
-use std::{fmt::Debug, sync::Arc};
+use futures::{StreamExt, TryStreamExt, stream};
+use std::{fmt::Debug, future::Future, sync::Arc};
+use tokio_util::{sync::CancellationToken, task::AbortOnDropHandle};
 use url::ParseError;
 use walker_common::progress::{Progress, ProgressBar};
 
@@ -21,11 +22,14 @@ where
     Url(#[from] ParseError),
     #[error("Visitor error: {0}")]
     Visitor(VE),
+    #[error("Walk was cancelled")]
+    Cancelled,
 }
 
 pub struct Walker<S: Source, P: Progress> {
-    source: S,
+    pub(crate) source: S,
     progress: P,
+    cancellation: CancellationToken,
 }
 
 impl<S: Source> Walker<S, ()> {
@@ -33,6 +37,7 @@ impl<S: Source> Walker<S, ()> {
         Self {
             source,
             progress: (),
+            cancellation: CancellationToken::new(),
         }
     }
 }
@@ -42,9 +47,19 @@ impl<S: Source, P: Progress> Walker<S, P> {
         Walker {
             source: self.source,
             progress,
+            cancellation: self.cancellation,
         }
     }
 
+    /// Set a token for cancelling the walk.
+    ///
+    /// Once cancelled, SBOMs already being visited will be completed, but no new ones will be
+    /// started. The walk will then fail with [`Error::Cancelled`].
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
     pub async fn walk<V>(self, visitor: V) -> Result<(), Error<V::Error, S::Error>>
     where
         V: DiscoveredVisitor,
@@ -62,6 +77,10 @@ impl<S: Source, P: Progress> Walker<S, P> {
         let mut progress = self.progress.start(index.len());
 
         for sbom in index {
+            if self.cancellation.is_cancelled() {
+                return Err(Error::Cancelled);
+            }
+
             log::debug!("  Discovered SBOM: {sbom:?}");
             progress
                 .set_message(
@@ -85,6 +104,11 @@ impl<S: Source, P: Progress> Walker<S, P> {
         Ok(())
     }
 
+    /// Walk all SBOMs, visiting up to `limit` SBOMs concurrently.
+    ///
+    /// The SBOMs are visited as part of the returned future, so they are processed concurrently,
+    /// but not in parallel. Use [`Self::walk_parallel_spawned`] to spread them over the worker
+    /// threads of a multi-threaded runtime.
     pub async fn walk_parallel<V>(
         self,
         limit: usize,
@@ -92,6 +116,66 @@ impl<S: Source, P: Progress> Walker<S, P> {
     ) -> Result<(), Error<V::Error, S::Error>>
     where
         V: DiscoveredVisitor,
+    {
+        self.walk_concurrent(limit, &visitor, |context, sbom| {
+            let visitor = &visitor;
+            async move {
+                visitor
+                    .visit_sbom(&context, sbom)
+                    .await
+                    .map_err(Error::Visitor)
+            }
+        })
+        .await
+    }
+
+    /// Walk all SBOMs, visiting up to `limit` SBOMs concurrently, each in its own task.
+    ///
+    /// Unlike [`Self::walk_parallel`], the visits can run on all worker threads of a
+    /// multi-threaded runtime. This must be called from within a Tokio runtime, and requires a
+    /// visitor which can be moved into a task.
+    pub async fn walk_parallel_spawned<V>(
+        self,
+        limit: usize,
+        visitor: V,
+    ) -> Result<(), Error<V::Error, S::Error>>
+    where
+        V: DiscoveredVisitor + 'static,
+        V::Context: 'static,
+        V::Error: 'static,
+    {
+        let visitor = Arc::new(visitor);
+
+        self.walk_concurrent(limit, &*visitor, |context, sbom| {
+            let visitor = visitor.clone();
+            let task = AbortOnDropHandle::new(tokio::spawn(async move {
+                visitor.visit_sbom(&context, sbom).await
+            }));
+
+            async move {
+                match task.await {
+                    Ok(result) => result.map_err(Error::Visitor),
+                    Err(err) => match err.try_into_panic() {
+                        Ok(panic) => std::panic::resume_unwind(panic),
+                        Err(_) => Err(Error::Cancelled),
+                    },
+                }
+            }
+        })
+        .await
+    }
+
+    /// Walk all SBOMs, visiting up to `limit` SBOMs concurrently using `visit`.
+    async fn walk_concurrent<V, F, Fut>(
+        self,
+        limit: usize,
+        visitor: &V,
+        visit: F,
+    ) -> Result<(), Error<V::Error, S::Error>>
+    where
+        V: DiscoveredVisitor,
+        F: Fn(Arc<V::Context>, DiscoveredSbom) -> Fut,
+        Fut: Future<Output = Result<(), Error<V::Error, S::Error>>>,
     {
         log::debug!("Running {limit} workers");
 
@@ -102,22 +186,22 @@ impl<S: Source, P: Progress> Walker<S, P> {
             })
             .await
             .map_err(Error::Visitor)?;
-
-        let visitor = Arc::new(visitor);
         let context = Arc::new(context);
 
         stream::iter(self.source.load_index().await.map_err(Error::Source)?)
+            .take_until(self.cancellation.cancelled())
             .map(Ok)
             .try_for_each_concurrent(limit, async |sbom| {
                 log::debug!("Discovered advisory: {}", sbom.url);
 
-                visitor
-                    .visit_sbom(&context, sbom)
-                    .map_err(Error::Visitor)
-                    .await
+                visit(context.clone(), sbom).await
             })
             .await?;
 
+        if self.cancellation.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+
         Ok(())
     }
 }